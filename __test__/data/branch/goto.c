@@ -0,0 +1,12 @@
+int main() {
+    int i = 0, sum = 0;
+again:
+    sum = sum + i;
+    i = i + 1;
+    if (i <= 10)
+        goto again;
+    goto done;
+    sum = 0;
+done:
+    return sum;
+}
@@ -0,0 +1,46 @@
+int dense(int x) {
+    switch (x) {
+    case 0:
+        return 10;
+    case 1:
+    case 2:
+        return 20;
+    case 4:
+        x = x + 1;
+    case 5:
+        return x * 10;
+    case 3:
+        break;
+    default:
+        return 99;
+    }
+    return 1;
+}
+
+int sparse(int x) {
+    int r = 0;
+    switch (x) {
+    case -1000:
+        r = 1;
+        break;
+    case 7:
+        r = 2;
+    case 4000000000:
+        r = r + 3;
+        break;
+    }
+    return r;
+}
+
+int main() {
+    int i, sum = 0;
+    for (i = 0; i < 8; i = i + 1) {
+        switch (i) {
+        case 6:
+            continue;
+        default:
+            sum = sum + dense(i);
+        }
+    }
+    return sum - 200 + sparse(-1000) + sparse(7) * 10 + sparse(4000000000) + sparse(8);
+}
@@ -0,0 +1,25 @@
+struct pair {
+    char c;
+    long l;
+};
+
+int table[sizeof(struct pair) / sizeof(long)];
+long size = sizeof table;
+
+int count(int *n) {
+    *n = *n + 1;
+    return *n;
+}
+
+int main() {
+    int n = 0;
+    char s[6] = "hello";
+    struct pair *p = 0;
+    // the operands are not evaluated.
+    long total = sizeof count(&n) + sizeof s + sizeof p->c + sizeof *p;
+    switch (sizeof(short)) {
+    case sizeof(char) + 1:
+        total = total + _Alignof(struct pair) + alignof(char[7]);
+    }
+    return total + size + n;
+}
@@ -0,0 +1,8 @@
+char g[] = "hello";
+int a[] = {1, 2, 3};
+
+int main() {
+    char s[] = "world";
+    int b[] = {4, 5, 6, 7};
+    return sizeof g + sizeof a + sizeof s + sizeof b + s[4] - 'd' + a[2] + b[3];
+}
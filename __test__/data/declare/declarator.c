@@ -0,0 +1,22 @@
+int sum(int v[], int n) {
+    int s = 0, i;
+    for (i = 0; i < n; i = i + 1)
+        s = s + v[i];
+    return s;
+}
+
+int main() {
+    int a[2][3], i, j;
+    for (i = 0; i < 2; i = i + 1)
+        for (j = 0; j < 3; j = j + 1)
+            a[i][j] = i * 3 + j;
+
+    int (*row)[3] = a;
+    int *cells[2], x = 10, y = 20;
+    cells[0] = &x;
+    cells[1] = &y;
+
+    const int k = 4;
+    const char *const s = "declarator";
+    return sum(a[1], 3) + row[1][2] * k + (*row)[1] + *cells[1] + s[2];
+}
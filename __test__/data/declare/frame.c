@@ -0,0 +1,17 @@
+int add(int a, int b) {
+    int sum = a + b;
+    return sum;
+}
+int fib(int n) {
+    int a;
+    int b;
+    if (n < 2) return n;
+    a = fib(n - 1);
+    b = fib(n - 2);
+    return a + b;
+}
+int main() {
+    int x = add(3, 4);
+    int y = fib(10);
+    return x + y;
+}
@@ -0,0 +1,33 @@
+int is_even(int n);
+int is_odd(int);
+int sum(int *values, int n);
+
+extern int total;
+
+int main() {
+    int values[4] = {1, 2, 3, 4};
+    total = sum(values, 4);
+    return is_even(total) * 100 + is_odd(7) * 10 + total;
+}
+
+int is_even(int n) {
+    if (n == 0)
+        return 1;
+    return is_odd(n - 1);
+}
+
+int is_odd(int n) {
+    if (n == 0)
+        return 0;
+    return is_even(n - 1);
+}
+
+int sum(int *values, int n) {
+    int s = 0;
+    int i;
+    for (i = 0; i < n; i = i + 1)
+        s = s + values[i];
+    return s;
+}
+
+int total;
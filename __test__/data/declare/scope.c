@@ -0,0 +1,18 @@
+int main() {
+    int x = 1;
+    int y = 0;
+    {
+        int x = 2;
+        y = y + x;
+        {
+            int x = 4;
+            y = y + x;
+        }
+        y = y + x * 10;
+    }
+    {
+        int z = 100;
+        y = y + z;
+    }
+    return y + x;
+}
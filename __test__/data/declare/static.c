@@ -0,0 +1,26 @@
+static int total;
+
+int counter(void) {
+    static int n = 0;
+    return ++n;
+}
+
+static int other(void) {
+    static int n;
+    n += 10;
+    {
+        static int n = 100;
+        n++;
+        total += n;
+    }
+    return n;
+}
+
+int main() {
+    counter();
+    counter();
+    int c = counter();
+    other();
+    int o = other();
+    return c * 10 + o + total;
+}
@@ -0,0 +1,5 @@
+int main() {
+    char s[6] = "hello";
+    char t[8] = "ab";
+    return s[1] - 'e' + t[2] + t[7] + s[4];
+}
@@ -0,0 +1,15 @@
+int add();
+double half();
+
+int main() {
+    char c = 3;
+    return add(c, 4) * 10 + (int)half(5.0);
+}
+
+int add(int a, int b) {
+    return a + b;
+}
+
+double half(double x) {
+    return x / 2;
+}
@@ -0,0 +1,8 @@
+#define MAX_COUNT 5
+
+int main() {
+    int item_count = MAX_COUNT;
+    int x1 = 2;
+    int _tmp = item_count * x1;
+    return _tmp;
+}
@@ -0,0 +1,18 @@
+int g;
+
+void set(int v) {
+    g = v;
+}
+
+void add(int v) {
+    if (v <= 0)
+        return;
+    g = g + v;
+}
+
+int main() {
+    set(5);
+    add(-3);
+    add(2);
+    return g;
+}
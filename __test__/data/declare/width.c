@@ -0,0 +1,34 @@
+int abs(int n);
+
+struct mixed {
+    char c;
+    short s;
+    int i;
+    long l;
+};
+
+int g[3] = {1, -2, 3};
+
+long widen(char c, short s, int i) { return c + s + i; }
+
+int main() {
+    char c = -1;
+    short s = -300;
+    int i = 2147483647;
+    long l = 1;
+    // each store only writes the bytes of its variable.
+    c = 5;
+    s = 7;
+    l = l << 40;
+
+    struct mixed m = {1, 2, 3, 4};
+    m.s = -1;
+    m.c = 0;
+
+    int a[4] = {1, 2, 3, 4};
+    a[1] = 0;
+
+    i = i + 1;
+    return (i < 0) + c + s + (l >> 40) + m.c + m.s + m.i + m.l + a[0] + a[1] + a[2] +
+           sizeof(m) + g[1] + abs(-10) + widen(-1, -2, -3) + 100;
+}
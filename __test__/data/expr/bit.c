@@ -0,0 +1,5 @@
+int main() {
+    int a = 12;
+    int b = 10;
+    return (a & b) + (a | b) + (a ^ b) + (~a + 13);
+}
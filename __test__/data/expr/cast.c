@@ -0,0 +1,22 @@
+struct pair {
+    char tag;
+    long value;
+};
+
+long read_value(void *p) {
+    struct pair *pair = p;
+    return pair->value;
+}
+
+int main() {
+    long x = 321;
+    char c = (char)x;
+    int n = (char)200 + (short)65537 + (long)(char)-1;
+
+    long word = 0x0102030405060708;
+    char *bytes = (char *)&word;
+
+    struct pair pair = {'a', 40};
+    (void)read_value(&pair);
+    return c + n + bytes[1] * 10 + (int)read_value((void *)&pair);
+}
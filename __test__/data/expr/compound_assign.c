@@ -0,0 +1,17 @@
+int main() {
+    int a = 5;
+    a += 3;
+    a -= 1;
+    a *= 6;
+    a /= 2;
+    a %= 8;
+    a <<= 3;
+    a >>= 1;
+    a |= 1;
+    a &= 13;
+    a ^= 3;
+    int b;
+    int c;
+    b = c = a;
+    return b + c;
+}
@@ -0,0 +1,12 @@
+int sign(int x) { return x < 0 ? -1 : x > 0 ? 1 : 0; }
+
+int main() {
+    int i, j, sum = 0;
+    for (i = 0, j = 10; i < j; i++, j--)
+        sum = sum + (i % 2 ? i : j);
+
+    int a = 1, b = 2;
+    int *p = a > b ? &a : &b;
+    *p = (a = 5, b + 10);
+    return sum + sign(-7) + sign(0) * 100 + sign(3) + a + b;
+}
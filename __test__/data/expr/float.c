@@ -0,0 +1,56 @@
+int sprintf(char *buf, const char *fmt, ...);
+int strcmp(const char *a, const char *b);
+
+double newton_sqrt(double x) {
+    double g = x / 2;
+    int i;
+    for (i = 0; i < 20; i++)
+        g = (g + x / g) / 2;
+    return g;
+}
+
+float average(int n, float a, double b, long c, float d) {
+    return (a + b + c + d) / n;
+}
+
+double scale = 2.5;
+float third = 1.0f / 3;
+double table[3] = {1, -0.5, (double)3 / 4};
+unsigned long huge = (unsigned long)1e19;
+int truncated = 3.99;
+
+int main() {
+    char buf[64];
+    float f = 0.1f;
+    double d = 1.5;
+    double nan = 0.0 / 0.0;
+    unsigned long u = 18446744073709551615ul;
+    int i = 10;
+    int r = 0;
+
+    d += 2;
+    d *= 3;
+    d--;
+    i += 2.7;
+    r += (int)(newton_sqrt(1764) + 0.5) == 42;  // 1
+    r += average(4, 1.5f, 2.5, 3, 5.0f) == 3;   // 1
+    r += d == 9.5;                              // 1
+    r += i;                                     // 12
+    r += (int)-d;                               // -9
+    r += scale * 4 + truncated;                 // 13
+    r += third > 0.333f && third < 0.334f;      // 1
+    r += table[0] + table[1] + table[2] == 1.25; // 1
+    r += huge == 10000000000000000000ul;        // 1
+    r += (double)u == 18446744073709551616.0;   // 1
+    r += (unsigned long)(double)(1ul << 63) == 1ul << 63; // 1
+    r += (nan == nan) + (nan < 1) + (nan <= 1); // 0
+    r += (nan != nan) + (_Bool)nan + !0.0 + !!0.25; // 4
+    r += 0.1 + 0.2 != 0.3;                      // 1
+    r += (float)0.1 == f && (double)f != 0.1;   // 1
+    while (f < 1)
+        f += 0.3f;
+    r += f == 1 ? 2 : 0;                        // 2
+    sprintf(buf, "%.4f %e %g", newton_sqrt(2), -d, (double)f);
+    r += strcmp(buf, "1.4142 -9.500000e+00 1") == 0; // 1
+    return r;
+}
@@ -0,0 +1,11 @@
+int main() {
+    int a = 5;
+    int b = a++;
+    int c = ++a;
+    int d = a--;
+    --a;
+    int sum = 0;
+    int i;
+    for (i = 0; i < 5; i++) sum += i;
+    return a * 10 + b + c + d + sum;
+}
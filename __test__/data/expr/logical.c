@@ -0,0 +1,7 @@
+int main() {
+    int a = 0;
+    int b = 0;
+    0 && (a = 1);
+    1 || (b = 1);
+    return (1 && 2) + (0 || 3) * 2 + !0 * 4 + !5 + (a + b) * 100;
+}
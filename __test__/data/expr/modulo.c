@@ -0,0 +1,4 @@
+int main() {
+    int a = 17;
+    return a % 5 + 10 % 3 * 2;
+}
@@ -0,0 +1,4 @@
+int main() {
+    int a = 3;
+    return (a << 4) + (256 >> 3) + (-8 >> 1) + 4;
+}
@@ -0,0 +1,50 @@
+unsigned int hash(const char *s) {
+    unsigned int h = 2166136261u;
+    while (*s) {
+        h ^= (unsigned char)*s++;
+        h *= 16777619;
+    }
+    return h;
+}
+
+int popcount(unsigned long x) {
+    int n = 0;
+    for (; x; x >>= 1)
+        n += x & 1;
+    return n;
+}
+
+unsigned long long big = 18446744073709551615ull / 3;
+short negative = -2;
+unsigned char table[2] = {-1, 256 + 7};
+
+int main() {
+    unsigned int u = 0;
+    unsigned char c = 250;
+    signed char s = -6;
+    unsigned short us = 65535;
+    long long int ll = -1;
+    _Bool b = 42;
+    int r = 0;
+
+    u = u - 1;
+    c += 10;
+    us++;
+    r += u > 5;                      // 1
+    r += -1 < 0u;                    // 0, -1 converts to UINT_MAX
+    r += -1 < 0;                     // 1
+    r += (u >> 28) == 15;            // 1, a logical shift
+    r += ((int)u >> 28) == -1;       // 1, an arithmetic shift
+    r += u / 2 == 2147483647;        // 1
+    r += (unsigned long)-1 % 10;     // 5
+    r += c + us;                     // 4
+    r += s / 4 == -1;                // 1
+    r += b + (_Bool)0 + !!b;         // 2
+    r += sizeof(long long) + sizeof(_Bool) + sizeof(unsigned short); // 11
+    r += ll < 0;                     // 1
+    r += popcount(0xf0f0) + hash("abc") % 7;
+    r += big == 6148914691236517205ull;
+    r += table[0] == 255 && table[1] == 7;
+    r += negative * 3 == -6;
+    return r;
+}
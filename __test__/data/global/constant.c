@@ -0,0 +1,7 @@
+int t;
+int t = (1 << 4) + 10 * 2 - 3 % 2;
+int u = -1 + ~0 + !0;
+int main() {
+    int u = 2;
+    return t + u;
+}
@@ -0,0 +1,10 @@
+int counter;
+int add(int n) {
+    counter = counter + n;
+    return counter;
+}
+int main() {
+    add(3);
+    add(4);
+    return counter;
+}
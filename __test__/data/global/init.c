@@ -0,0 +1,8 @@
+int a = 3;
+int b[4] = {1, 2, 3};
+int *p = &b[1];
+char *msg = "hi";
+char s[4] = "abc";
+int main() {
+    return a + b[0] + b[1] + b[2] + b[3] + *p + msg[1] + s[2];
+}
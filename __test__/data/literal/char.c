@@ -0,0 +1,3 @@
+int main() {
+    return 'a' - '\n' + '\0' + '\'' - '\x41' + '\101' - ' ';
+}
@@ -0,0 +1,4 @@
+int main() {
+    char *s = "a\tb\\\"\x41\101\n";
+    return s[1] + s[3] + s[4] + s[6] + s[7];
+}
@@ -0,0 +1,5 @@
+int main() {
+    long big = 0x100000000;
+    long shifted = big >> 28;
+    return 0x1f + 017 + 0b101 + 10ul + shifted + (big == 4294967296l);
+}
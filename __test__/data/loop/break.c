@@ -0,0 +1,27 @@
+int main() {
+    int i, sum = 0;
+    for (i = 0; i < 100; i = i + 1) {
+        if (i % 2 == 0)
+            continue;
+        if (i > 10)
+            break;
+        sum = sum + i;
+    }
+
+    int n = 0;
+    while (1) {
+        n = n + 1;
+        if (n < 5)
+            continue;
+        break;
+    }
+
+    int j = 0;
+    do {
+        j = j + 1;
+        if (j == 3)
+            continue;
+        sum = sum + j;
+    } while (j < 4);
+    return sum * 10 + n;
+}
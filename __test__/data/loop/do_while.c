@@ -0,0 +1,12 @@
+int main() {
+    int n = 0;
+    do
+        n = n + 7;
+    while (n < 30);
+
+    int m = 100;
+    do {
+        m = m + 1;
+    } while (0);
+    return n + m;
+}
@@ -0,0 +1,11 @@
+int main() {
+    int s = 0;
+    int i = 100;
+    for (int i = 0, j = 10; i < 5; i++)
+        s = s + i + j;
+    for (int i = 1; i < 3; i++) {
+        int i = 7;
+        s = s + i;
+    }
+    return s + i;
+}
@@ -0,0 +1,5 @@
+int main() {
+    int n = 0;
+    for (;;) { if (n == 3) break; n++; }
+    return n;
+}
@@ -0,0 +1,22 @@
+int sum(int *p, int n) {
+    int s = 0;
+    int *q;
+    for (q = p; q < p + n; q++)
+        s += *q;
+    return s;
+}
+
+int length(const char *s) {
+    int n = 0;
+    while (*(s + n))
+        n++;
+    return n;
+}
+
+int main() {
+    int a[4] = {1, 2, 3, 4};
+    int *p = a;
+    int *end = &a[3];
+    char c = 3;
+    return *(p + 1) + *(1 + p + 2) * 10 + (end - p) * 20 + sum(a, 4) - c + length("hello");
+}
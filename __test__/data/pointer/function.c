@@ -0,0 +1,26 @@
+void qsort(void *base, long n, long size, int (*compare)(const void *, const void *));
+
+int add(int a, int b) { return a + b; }
+int sub(int a, int b) { return a - b; }
+int twice(int x) { return x * 2; }
+
+int by_value(const void *a, const void *b) {
+    const int *x = a;
+    const int *y = b;
+    return *x - *y;
+}
+
+int apply(int (*f)(int, int), int a, int b) { return f(a, b); }
+
+int (*ops[2])(int, int) = {add, &sub};
+
+int main() {
+    int (*fp)(int) = twice;
+    int v[4] = {4, 1, 3, 2};
+    qsort(v, 4, sizeof(int), by_value);
+
+    int i, sum = 0;
+    for (i = 0; i < 2; i = i + 1)
+        sum = sum + ops[i](10, 3);
+    return fp(sum) + (*fp)(1) + apply(sub, 7, 2) + v[0] * 1000 % 7 + v[3] * 10;
+}
@@ -0,0 +1,6 @@
+#include "include.h"
+#include "include.h"
+
+int main() {
+    return answer();
+}
@@ -0,0 +1,5 @@
+#pragma once
+
+int answer() {
+    return 42;
+}
@@ -0,0 +1,13 @@
+#define ten 10
+#define add(a, b) ((a) + (b))
+#define twice(x) add(x, x)
+
+#if ten > 5 && defined(add)
+int main() {
+    return twice(add(ten, 1));
+}
+#else
+int main() {
+    return 0;
+}
+#endif
@@ -0,0 +1,6 @@
+#include <stdio.h>
+
+int main() {
+    // "42 printf\n"
+    return printf("%d %s\n", 42, "printf");
+}
@@ -0,0 +1,21 @@
+struct value {
+    int kind;
+    union {
+        int i;
+        char c;
+        struct {
+            short lo;
+            short hi;
+        };
+    };
+};
+
+int main() {
+    struct value v;
+    struct value *p = &v;
+    v.kind = 1;
+    p->i = 0;
+    v.c = 3;
+    p->hi = 1;
+    return v.kind * 100 + p->lo * 10 + v.hi + sizeof(struct value);
+}
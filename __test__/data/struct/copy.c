@@ -0,0 +1,19 @@
+struct pair {
+    char a;
+    char b;
+    int c;
+};
+
+struct pair global = {1, 2, 3};
+struct pair *p = &global;
+int *c = &global.c;
+
+int main() {
+    struct pair x = {10, 20};
+    struct pair y;
+    y = x;
+    x.a = 0;
+    struct pair z = global;
+    *c = 4;
+    return y.a + y.b + y.c + z.a + z.b + z.c + p->c + x.a;
+}
@@ -0,0 +1,28 @@
+struct inner {
+    char a;
+    short b;
+};
+
+struct outer {
+    char c;
+    struct inner in;
+    char d[3];
+    long e;
+};
+
+union value {
+    char c;
+    long l;
+    char bytes[12];
+};
+
+int main() {
+    struct outer o;
+    union value v;
+    v.l = 258;
+    o.in.a = 1;
+    o.in.b = 2;
+    o.d[2] = 4;
+    // 4 + 24 + 16 + 1 + 2 + 4 + 2
+    return sizeof(o.in) + sizeof(o) + sizeof(v) + o.in.a + o.in.b + o.d[2] + v.bytes[0];
+}
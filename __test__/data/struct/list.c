@@ -0,0 +1,26 @@
+struct node {
+    int value;
+    struct node *next;
+};
+
+int sum(struct node *node) {
+    int s = 0;
+    while (node != 0) {
+        s = s + node->value;
+        node = node->next;
+    }
+    return s;
+}
+
+int main() {
+    struct node a;
+    struct node b;
+    struct node c;
+    a.value = 1;
+    a.next = &b;
+    b.value = 20;
+    b.next = &c;
+    c.value = 30;
+    c.next = 0;
+    return sum(&a) + a.next->next->value;
+}
@@ -0,0 +1,14 @@
+struct point {
+    char tag;
+    int x, y;
+};
+
+int main() {
+    struct point p;
+    struct point *q = &p;
+    p.tag = 3;
+    p.x = 10;
+    q->y = 20;
+    q->x += 5;
+    return p.tag + p.x + q->y + sizeof(p);
+}
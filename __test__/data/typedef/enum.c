@@ -0,0 +1,16 @@
+enum color { RED, GREEN = 5, BLUE, };
+enum { SHIFT = 2, MASK = (1 << SHIFT) - 1 };
+
+int weight(enum color c) {
+    if (c == RED)
+        return 1;
+    if (c == GREEN)
+        return 10;
+    return 100;
+}
+
+int main() {
+    enum color c = BLUE;
+    int values[3] = {RED, GREEN, BLUE};
+    return weight(c) + weight(GREEN) + values[1] + values[2] + MASK + sizeof(c);
+}
@@ -0,0 +1,40 @@
+typedef int number;
+typedef struct node node;
+typedef number *pointer;
+
+struct node {
+    number value;
+    node *next;
+};
+
+typedef struct {
+    char x;
+    char y;
+} pair;
+
+number sum(node *n) {
+    number s = 0;
+    while (n != 0) {
+        s = s + n->value;
+        n = n->next;
+    }
+    return s;
+}
+
+int main() {
+    node a;
+    node b;
+    pair p = {3, 4};
+    number x = 5;
+    pointer q = &x;
+    a.value = 10;
+    a.next = &b;
+    b.value = 20;
+    b.next = 0;
+    {
+        // a variable hides the typedef name.
+        int number = 2;
+        x = x * number;
+    }
+    return sum(&a) + p.x + p.y + *q + sizeof(p);
+}
@@ -26,6 +26,17 @@ assert 47 "${TEST_DATA_DIR}/expr/mul.c"
 assert 15 "${TEST_DATA_DIR}/expr/grouped.c"
 assert 4 "${TEST_DATA_DIR}/expr/grouped2.c"
 assert 12 "${TEST_DATA_DIR}/expr/assign.c"
+assert 4 "${TEST_DATA_DIR}/expr/modulo.c"
+assert 28 "${TEST_DATA_DIR}/expr/bit.c"
+assert 100 "${TEST_DATA_DIR}/expr/long.c"
+assert 80 "${TEST_DATA_DIR}/expr/shift.c"
+assert 7 "${TEST_DATA_DIR}/expr/logical.c"
+assert 12 "${TEST_DATA_DIR}/expr/compound_assign.c"
+assert 79 "${TEST_DATA_DIR}/expr/increment.c"
+assert 45 "${TEST_DATA_DIR}/expr/conditional.c"
+assert 119 "${TEST_DATA_DIR}/expr/cast.c"
+assert 45 "${TEST_DATA_DIR}/expr/unsigned.c"
+assert 33 "${TEST_DATA_DIR}/expr/float.c"
 
 assert 1 "${TEST_DATA_DIR}/comp/equivalence2.c"
 assert 0 "${TEST_DATA_DIR}/comp/equivalence.c"
@@ -47,31 +58,71 @@ assert 0 "${TEST_DATA_DIR}/comp/gte3.c"
 assert 4 "${TEST_DATA_DIR}/declare/var.c"
 assert 7 "${TEST_DATA_DIR}/declare/var2.c"
 assert 7 "${TEST_DATA_DIR}/declare/var3.c"
+assert 10 "${TEST_DATA_DIR}/declare/var4.c"
 assert 10 "${TEST_DATA_DIR}/declare/func.c"
+assert 127 "${TEST_DATA_DIR}/declare/scope.c"
+assert 62 "${TEST_DATA_DIR}/declare/frame.c"
+assert 120 "${TEST_DATA_DIR}/declare/prototype.c"
+assert 72 "${TEST_DATA_DIR}/declare/unprototyped.c"
+assert 253 "${TEST_DATA_DIR}/declare/static.c"
+assert 7 "${TEST_DATA_DIR}/declare/void.c"
+assert 0 "${TEST_DATA_DIR}/declare/fall_off.c"
+assert 152 "${TEST_DATA_DIR}/declare/declarator.c"
+assert 142 "${TEST_DATA_DIR}/declare/width.c"
 assert 1 "${TEST_DATA_DIR}/declare/array/deref.c"
-# assert 3 "${TEST_DATA_DIR}/declare/array/deref2.c"  # FIXME: this is not working
+assert 1 "${TEST_DATA_DIR}/declare/array/deref2.c"
 assert 1 "${TEST_DATA_DIR}/declare/array/deref3.c"
 assert 1 "${TEST_DATA_DIR}/declare/array/index.c"
 assert 2 "${TEST_DATA_DIR}/declare/array/index2.c"
 assert 1 "${TEST_DATA_DIR}/declare/array/init.c"
 assert 10 "${TEST_DATA_DIR}/declare/array/init2.c"
+assert 50 "${TEST_DATA_DIR}/declare/array/init3.c"
 assert 0 "${TEST_DATA_DIR}/declare/string/init.c"
+assert 111 "${TEST_DATA_DIR}/declare/string/init2.c"
 assert 65 "${TEST_DATA_DIR}/declare/string/head.c"
 assert 70 "${TEST_DATA_DIR}/declare/string/index.c"
+assert 94 "${TEST_DATA_DIR}/literal/char.c"
+assert 210 "${TEST_DATA_DIR}/literal/escape.c"
+assert 78 "${TEST_DATA_DIR}/literal/integer.c"
 
 assert 54 "${TEST_DATA_DIR}/branch/if.c"
 assert 110 "${TEST_DATA_DIR}/branch/if2.c"
 assert 150 "${TEST_DATA_DIR}/branch/if3.c"
+assert 104 "${TEST_DATA_DIR}/branch/switch.c"
+assert 55 "${TEST_DATA_DIR}/branch/goto.c"
 
 assert 10 "${TEST_DATA_DIR}/loop/while.c"
 assert 10 "${TEST_DATA_DIR}/loop/for.c"
+assert 174 "${TEST_DATA_DIR}/loop/for_declaration.c"
+assert 3 "${TEST_DATA_DIR}/loop/for_empty.c"
+assert 136 "${TEST_DATA_DIR}/loop/do_while.c"
+assert 69 "${TEST_DATA_DIR}/loop/break.c"
 
 assert 3 "${TEST_DATA_DIR}/pointer/ref.c"
 assert 3 "${TEST_DATA_DIR}/pointer/deref_assign.c"
 assert 200 "${TEST_DATA_DIR}/pointer/ref_inc.c"
 # assert 200 "${TEST_DATA_DIR}/pointer/ref_inc2.c"  # FIXME: this is not working
 assert 100 "${TEST_DATA_DIR}/pointer/ref_dec.c"
+assert 93 "${TEST_DATA_DIR}/pointer/function.c"
+assert 114 "${TEST_DATA_DIR}/pointer/arith.c"
 
-assert 8 "${TEST_DATA_DIR}/builtin/sizeof.c"
+assert 7 "${TEST_DATA_DIR}/global/counter.c"
+assert 215 "${TEST_DATA_DIR}/global/init.c"
+assert 37 "${TEST_DATA_DIR}/global/constant.c"
+
+assert 50 "${TEST_DATA_DIR}/struct/member.c"
+assert 53 "${TEST_DATA_DIR}/struct/layout.c"
+assert 81 "${TEST_DATA_DIR}/struct/list.c"
+assert 40 "${TEST_DATA_DIR}/struct/copy.c"
+assert 139 "${TEST_DATA_DIR}/struct/anonymous.c"
+assert 128 "${TEST_DATA_DIR}/typedef/enum.c"
+assert 49 "${TEST_DATA_DIR}/typedef/typedef.c"
+
+assert 4 "${TEST_DATA_DIR}/builtin/sizeof.c"
+assert 44 "${TEST_DATA_DIR}/builtin/sizeof2.c"
 assert 0 "${TEST_DATA_DIR}/comment/line.c"
 assert 0 "${TEST_DATA_DIR}/comment/block.c"
+
+assert 22 "${TEST_DATA_DIR}/preprocess/macro.c"
+assert 42 "${TEST_DATA_DIR}/preprocess/include.c"
+assert 10 "${TEST_DATA_DIR}/preprocess/stdio.c"
@@ -0,0 +1,249 @@
+use crate::{
+    common_type, is_arithmetic, is_integer, promoted, type_of, BinaryOperator, Expression,
+    ExpressionKind, Type, TypeEnum, UnaryOperator,
+};
+
+/// value of an arithmetic constant expression.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Value {
+    /// in the representation `convert_constant` gives it.
+    Integer(i64),
+    /// rounded to the precision of its type.
+    Float(f64),
+}
+
+impl Value {
+    fn is_true(self) -> bool {
+        match self {
+            Value::Integer(value) => value != 0,
+            Value::Float(value) => value != 0.0,
+        }
+    }
+}
+
+/// operation of an expression, as far as folding it goes.
+pub enum Operation<'a, E> {
+    Integer(u64),
+    Float(f64),
+    Unary(&'a UnaryOperator, &'a E),
+    Binary(&'a E, &'a BinaryOperator, &'a E),
+    Conditional(&'a E, &'a E, &'a E),
+    /// conversion of the operand to the type of the expression.
+    Cast(&'a E),
+    /// anything else, which is never constant.
+    Other,
+}
+
+/// expression a constant can be folded from, parsed or checked.
+pub trait Fold: Sized {
+    /// type of the value of the expression, `None` if it has none.
+    fn type_(&self) -> Option<Type>;
+    fn operation(&self) -> Operation<'_, Self>;
+}
+
+impl Fold for Expression {
+    fn type_(&self) -> Option<Type> {
+        type_of(self)
+    }
+
+    fn operation(&self) -> Operation<'_, Self> {
+        match &self.kind {
+            ExpressionKind::Integer(value, _) => Operation::Integer(*value),
+            ExpressionKind::Float(value, _) => Operation::Float(*value),
+            ExpressionKind::Unary { expr, op } => Operation::Unary(op, expr),
+            ExpressionKind::Binary { lhs, op, rhs } => Operation::Binary(lhs, op, rhs),
+            ExpressionKind::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => Operation::Conditional(condition, then, otherwise),
+            ExpressionKind::Cast { expr, .. } => Operation::Cast(expr),
+            _ => Operation::Other,
+        }
+    }
+}
+
+/// value of the arithmetic constant expression `expr` in its type, `None` if it is not one. the
+/// operands are converted like sema converts them, so that the value is the one the generated
+/// code computes, and a checked expression folds to the value of the parsed one.
+pub fn fold<E: Fold>(expr: &E) -> Option<Value> {
+    let type_ = expr.type_()?;
+    let (value, from) = match expr.operation() {
+        Operation::Integer(value) => (Value::Integer(value as i64), type_.clone()),
+        Operation::Float(value) => (Value::Float(value), type_.clone()),
+        Operation::Unary(op, operand) => fold_unary(op, operand)?,
+        Operation::Binary(lhs, op, rhs) => fold_binary_operation(lhs, op, rhs)?,
+        Operation::Conditional(condition, then, otherwise) => {
+            let branch = if fold(condition)?.is_true() {
+                then
+            } else {
+                otherwise
+            };
+            (fold(branch)?, branch.type_()?)
+        }
+        Operation::Cast(operand) => (fold(operand)?, operand.type_()?),
+        Operation::Other => return None,
+    };
+    convert(value, &from, &type_)
+}
+
+/// value of the integer constant expression `expr`, `None` if it is not one.
+pub fn fold_integer<E: Fold>(expr: &E) -> Option<i64> {
+    match fold(expr)? {
+        Value::Integer(value) => Some(value),
+        Value::Float(_) => None,
+    }
+}
+
+/// `op operand`, with the type it is computed in.
+fn fold_unary<E: Fold>(op: &UnaryOperator, operand: &E) -> Option<(Value, Type)> {
+    let from = operand.type_()?;
+    let value = fold(operand)?;
+    if *op == UnaryOperator::Not {
+        let value = Value::Integer(!value.is_true() as i64);
+        return Some((value, Type::Primitive(TypeEnum::Int)));
+    }
+    let type_ = promoted(&from);
+    let value = match convert(value, &from, &type_)? {
+        Value::Integer(value) => Value::Integer(op.fold(value)?),
+        Value::Float(value) => match op {
+            UnaryOperator::Plus => Value::Float(value),
+            UnaryOperator::Minus => Value::Float(-value),
+            _ => return None,
+        },
+    };
+    Some((value, type_))
+}
+
+/// `lhs op rhs` for arithmetic operands, with the type it is computed in.
+fn fold_binary_operation<E: Fold>(lhs: &E, op: &BinaryOperator, rhs: &E) -> Option<(Value, Type)> {
+    let (lhs_type, rhs_type) = (lhs.type_()?, rhs.type_()?);
+    if !is_arithmetic(&lhs_type) || !is_arithmetic(&rhs_type) {
+        return None;
+    }
+    let (l, r) = (fold(lhs)?, fold(rhs)?);
+    let int = Type::Primitive(TypeEnum::Int);
+    // the operands are converted to a common type, but for the shifts and the logical operators.
+    let operands = match op {
+        BinaryOperator::And => {
+            return Some((Value::Integer((l.is_true() && r.is_true()) as i64), int))
+        }
+        BinaryOperator::Or => {
+            return Some((Value::Integer((l.is_true() || r.is_true()) as i64), int))
+        }
+        BinaryOperator::LShift | BinaryOperator::RShift => {
+            let type_ = promoted(&lhs_type);
+            return match (convert(l, &lhs_type, &type_)?, r) {
+                (Value::Integer(l), Value::Integer(r)) => {
+                    Some((Value::Integer(fold_binary(op, l, r, &type_)?), type_))
+                }
+                _ => None,
+            };
+        }
+        _ => common_type(&lhs_type, &rhs_type),
+    };
+    let value = match (
+        convert(l, &lhs_type, &operands)?,
+        convert(r, &rhs_type, &operands)?,
+    ) {
+        (Value::Integer(l), Value::Integer(r)) => Value::Integer(fold_binary(op, l, r, &operands)?),
+        (Value::Float(l), Value::Float(r)) => fold_float(op, l, r, &operands)?,
+        _ => return None,
+    };
+    let type_ = match op {
+        BinaryOperator::Lt | BinaryOperator::LtEq | BinaryOperator::Eq | BinaryOperator::NotEq => {
+            int
+        }
+        _ => operands,
+    };
+    Some((value, type_))
+}
+
+/// `value` of type `from` converted to the scalar `to`, `None` if it does not convert. a
+/// floating value is truncated toward zero, and `_Bool` is 1 for any value but zeros.
+fn convert(value: Value, from: &Type, to: &Type) -> Option<Value> {
+    let to = to.unqualified();
+    let value = match value {
+        Value::Integer(value) if is_integer(to) || matches!(to, Type::Pointer(_)) => {
+            Value::Integer(convert_constant(value, to))
+        }
+        Value::Integer(value) if is_arithmetic(to) => Value::Float(round(
+            match from.is_signed() {
+                true => value as f64,
+                false => value as u64 as f64,
+            },
+            to,
+        )),
+        Value::Float(value) if *to == Type::Primitive(TypeEnum::Bool) => {
+            Value::Integer((value != 0.0) as i64)
+        }
+        Value::Float(value) if is_integer(to) => Value::Integer(match to.is_signed() {
+            true => convert_constant(value as i64, to),
+            false => convert_constant(value as u64 as i64, to),
+        }),
+        Value::Float(value) if is_arithmetic(to) => Value::Float(round(value, to)),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// `value` rounded to the floating `type_`.
+fn round(value: f64, type_: &Type) -> f64 {
+    match type_ {
+        Type::Primitive(TypeEnum::Float) => value as f32 as f64,
+        _ => value,
+    }
+}
+
+/// `lhs op rhs` for operands of `type_`, before the result is converted to its own type.
+/// `None` if it cannot be evaluated.
+fn fold_binary(op: &BinaryOperator, lhs: i64, rhs: i64, type_: &Type) -> Option<i64> {
+    if type_.is_signed() {
+        return op.fold(lhs, rhs);
+    }
+    let (l, r) = (lhs as u64, rhs as u64);
+    let value = match op {
+        BinaryOperator::Slash => l.checked_div(r)? as i64,
+        BinaryOperator::Percent => l.checked_rem(r)? as i64,
+        BinaryOperator::RShift => l.wrapping_shr(r as u32) as i64,
+        BinaryOperator::Lt => (l < r) as i64,
+        BinaryOperator::LtEq => (l <= r) as i64,
+        _ => return op.fold(lhs, rhs),
+    };
+    Some(value)
+}
+
+/// `lhs op rhs` for floating operands of `type_`, comparisons giving an integer. `None` for the
+/// operators which take integers only.
+fn fold_float(op: &BinaryOperator, lhs: f64, rhs: f64, type_: &Type) -> Option<Value> {
+    let value = match op {
+        BinaryOperator::Plus => lhs + rhs,
+        BinaryOperator::Minus => lhs - rhs,
+        BinaryOperator::Asterisk => lhs * rhs,
+        BinaryOperator::Slash => lhs / rhs,
+        BinaryOperator::Lt => return Some(Value::Integer((lhs < rhs) as i64)),
+        BinaryOperator::LtEq => return Some(Value::Integer((lhs <= rhs) as i64)),
+        BinaryOperator::Eq => return Some(Value::Integer((lhs == rhs) as i64)),
+        BinaryOperator::NotEq => return Some(Value::Integer((lhs != rhs) as i64)),
+        _ => return None,
+    };
+    Some(Value::Float(round(value, type_)))
+}
+
+/// `value` converted to the integer `type_`, as the generated code converts it: truncated to
+/// its size and extended back to 64 bits with its sign.
+pub fn convert_constant(value: i64, type_: &Type) -> i64 {
+    let signed = type_.is_signed();
+    match type_.unqualified() {
+        Type::Primitive(TypeEnum::Bool) => (value != 0) as i64,
+        type_ => match (type_.size(), signed) {
+            (1, true) => value as i8 as i64,
+            (1, false) => value as u8 as i64,
+            (2, true) => value as i16 as i64,
+            (2, false) => value as u16 as i64,
+            (4, true) => value as i32 as i64,
+            (4, false) => value as u32 as i64,
+            _ => value,
+        },
+    }
+}
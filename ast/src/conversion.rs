@@ -0,0 +1,88 @@
+use crate::{Type, TypeEnum};
+
+pub fn is_integer(type_: &Type) -> bool {
+    matches!(
+        type_,
+        Type::Primitive(
+            TypeEnum::Bool
+                | TypeEnum::Char
+                | TypeEnum::SignedChar
+                | TypeEnum::UnsignedChar
+                | TypeEnum::Short
+                | TypeEnum::UnsignedShort
+                | TypeEnum::Int
+                | TypeEnum::UnsignedInt
+                | TypeEnum::Long
+                | TypeEnum::UnsignedLong
+                | TypeEnum::LongLong
+                | TypeEnum::UnsignedLongLong
+        )
+    )
+}
+
+pub fn is_arithmetic(type_: &Type) -> bool {
+    is_integer(type_) || matches!(type_, Type::Primitive(TypeEnum::Float | TypeEnum::Double))
+}
+
+/// arithmetic types and pointers, those which can be compared with 0.
+pub fn is_scalar(type_: &Type) -> bool {
+    is_arithmetic(type_) || matches!(type_, Type::Pointer(_))
+}
+
+/// `type_` after the integer promotions. `int` holds every value of the smaller types,
+/// unsigned ones included.
+pub fn promoted(type_: &Type) -> Type {
+    match type_ {
+        Type::Primitive(
+            TypeEnum::Bool
+            | TypeEnum::Char
+            | TypeEnum::SignedChar
+            | TypeEnum::UnsignedChar
+            | TypeEnum::Short
+            | TypeEnum::UnsignedShort,
+        ) => Type::Primitive(TypeEnum::Int),
+        type_ => type_.clone(),
+    }
+}
+
+/// common type of arithmetic operands after the usual arithmetic conversions.
+pub fn common_type(lhs: &Type, rhs: &Type) -> Type {
+    if let Some(float) = [TypeEnum::Double, TypeEnum::Float]
+        .into_iter()
+        .map(Type::Primitive)
+        .find(|type_| type_ == lhs || type_ == rhs)
+    {
+        return float;
+    }
+    let (lhs, rhs) = (promoted(lhs), promoted(rhs));
+    let (higher, lower) = match rank(&lhs) >= rank(&rhs) {
+        true => (lhs, rhs),
+        false => (rhs, lhs),
+    };
+    // the unsigned type wins unless the signed one of a higher rank holds all its values.
+    match (higher.is_signed(), lower.is_signed()) {
+        (true, false) if higher.size() == lower.size() => unsigned(&higher),
+        _ => higher,
+    }
+}
+
+/// integer conversion rank of a promoted integer type, the same for its signed and unsigned
+/// variants.
+fn rank(type_: &Type) -> usize {
+    match type_ {
+        Type::Primitive(TypeEnum::Int | TypeEnum::UnsignedInt) => 1,
+        Type::Primitive(TypeEnum::Long | TypeEnum::UnsignedLong) => 2,
+        Type::Primitive(TypeEnum::LongLong | TypeEnum::UnsignedLongLong) => 3,
+        _ => 0,
+    }
+}
+
+/// unsigned variant of the promoted integer `type_`.
+fn unsigned(type_: &Type) -> Type {
+    Type::Primitive(match type_ {
+        Type::Primitive(TypeEnum::Int) => TypeEnum::UnsignedInt,
+        Type::Primitive(TypeEnum::Long) => TypeEnum::UnsignedLong,
+        Type::Primitive(TypeEnum::LongLong) => TypeEnum::UnsignedLongLong,
+        _ => return type_.clone(),
+    })
+}
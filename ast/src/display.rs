@@ -0,0 +1,536 @@
+//! C-like rendering of the AST, used in diagnostics.
+use std::fmt;
+
+use crate::{
+    BinaryOperator, Expression, ExpressionKind, Qualifiers, Type, TypeEnum, UnaryOperator,
+};
+
+impl fmt::Display for TypeEnum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            TypeEnum::Void => "void",
+            TypeEnum::Bool => "_Bool",
+            TypeEnum::Char => "char",
+            TypeEnum::SignedChar => "signed char",
+            TypeEnum::UnsignedChar => "unsigned char",
+            TypeEnum::Short => "short",
+            TypeEnum::UnsignedShort => "unsigned short",
+            TypeEnum::Int => "int",
+            TypeEnum::UnsignedInt => "unsigned int",
+            TypeEnum::Long => "long",
+            TypeEnum::UnsignedLong => "unsigned long",
+            TypeEnum::LongLong => "long long",
+            TypeEnum::UnsignedLongLong => "unsigned long long",
+            TypeEnum::Float => "float",
+            TypeEnum::Double => "double",
+            TypeEnum::LongDouble => "long double",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl Type {
+    /// writes the type as a declaration of `declarator`, e.g. `int *x[3]`.
+    fn fmt_declaration(&self, declarator: String, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::Primitive(t) => {
+                if declarator.is_empty() || declarator.starts_with('[') {
+                    write!(f, "{}{}", t, declarator)
+                } else {
+                    write!(f, "{} {}", t, declarator)
+                }
+            }
+            Type::Pointer(t) => t.fmt_pointer(String::from("*"), declarator, f),
+            Type::Array { type_, size } => {
+                type_.fmt_declaration(format!("{}[{}]", declarator, size), f)
+            }
+            Type::Function {
+                return_type,
+                parameters,
+                variadic,
+                prototyped,
+            } => {
+                let mut list = parameters.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+                if *variadic {
+                    list.push(String::from("..."));
+                }
+                let list = match (list.is_empty(), prototyped) {
+                    (true, true) => String::from("void"),
+                    _ => list.join(", "),
+                };
+                return_type.fmt_declaration(format!("{}({})", declarator, list), f)
+            }
+            Type::Struct(struct_) => {
+                let keyword = if struct_.union { "union" } else { "struct" };
+                let tag = struct_.tag.as_deref().unwrap_or("<anonymous>");
+                if declarator.is_empty() || declarator.starts_with('[') {
+                    write!(f, "{} {}{}", keyword, tag, declarator)
+                } else {
+                    write!(f, "{} {} {}", keyword, tag, declarator)
+                }
+            }
+            // a qualified pointer has its qualifiers after the `*`, e.g. `char *const p`.
+            Type::Qualified { qualifiers, type_ } => match type_.as_ref() {
+                Type::Pointer(t) => {
+                    let pointer = format!("*{}", qualifiers);
+                    let declarator = match declarator.is_empty() {
+                        true => declarator,
+                        false => format!(" {}", declarator),
+                    };
+                    t.fmt_pointer(pointer, declarator, f)
+                }
+                _ => {
+                    write!(f, "{} ", qualifiers)?;
+                    type_.fmt_declaration(declarator, f)
+                }
+            },
+        }
+    }
+
+    /// writes a pointer to `self`, `pointer` being the `*` with its qualifiers.
+    fn fmt_pointer(
+        &self,
+        pointer: String,
+        declarator: String,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        match self {
+            Type::Array { .. } | Type::Function { .. } => {
+                self.fmt_declaration(format!("({}{})", pointer, declarator), f)
+            }
+            _ => self.fmt_declaration(format!("{}{}", pointer, declarator), f),
+        }
+    }
+}
+
+impl fmt::Display for Qualifiers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let qualifiers = [
+            (self.const_, "const"),
+            (self.volatile, "volatile"),
+            (self.restrict, "restrict"),
+        ];
+        let names = qualifiers
+            .iter()
+            .filter(|(set, _)| *set)
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>();
+        write!(f, "{}", names.join(" "))
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_declaration(String::new(), f)
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            BinaryOperator::Assignment => "=",
+            BinaryOperator::Plus => "+",
+            BinaryOperator::Minus => "-",
+            BinaryOperator::Slash => "/",
+            BinaryOperator::Asterisk => "*",
+            BinaryOperator::Percent => "%",
+            BinaryOperator::Lt => "<",
+            BinaryOperator::LtEq => "<=",
+            BinaryOperator::Eq => "==",
+            BinaryOperator::NotEq => "!=",
+            BinaryOperator::LShift => "<<",
+            BinaryOperator::RShift => ">>",
+            BinaryOperator::BitAnd => "&",
+            BinaryOperator::BitOr => "|",
+            BinaryOperator::BitXor => "^",
+            BinaryOperator::And => "&&",
+            BinaryOperator::Or => "||",
+            BinaryOperator::PlusAssignment => "+=",
+            BinaryOperator::MinusAssignment => "-=",
+            BinaryOperator::AsteriskAssignment => "*=",
+            BinaryOperator::SlashAssignment => "/=",
+            BinaryOperator::PercentAssignment => "%=",
+            BinaryOperator::LShiftAssignment => "<<=",
+            BinaryOperator::RShiftAssignment => ">>=",
+            BinaryOperator::BitAndAssignment => "&=",
+            BinaryOperator::BitOrAssignment => "|=",
+            BinaryOperator::BitXorAssignment => "^=",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            UnaryOperator::Plus => "+",
+            UnaryOperator::Minus => "-",
+            UnaryOperator::Dereference => "*",
+            UnaryOperator::Reference => "&",
+            UnaryOperator::Not => "!",
+            UnaryOperator::BitNot => "~",
+            UnaryOperator::Increment | UnaryOperator::PostIncrement => "++",
+            UnaryOperator::Decrement | UnaryOperator::PostDecrement => "--",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// operands which are themselves operations are parenthesized.
+struct Operand<'a>(&'a Expression);
+
+impl fmt::Display for Operand<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0.kind {
+            ExpressionKind::Binary { .. }
+            | ExpressionKind::Conditional { .. }
+            | ExpressionKind::Comma { .. } => write!(f, "({})", self.0),
+            _ => write!(f, "{}", self.0),
+        }
+    }
+}
+
+fn fmt_list(f: &mut fmt::Formatter, expressions: &[Expression]) -> fmt::Result {
+    for (i, e) in expressions.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        match e.kind {
+            ExpressionKind::Comma { .. } => write!(f, "({})", e)?,
+            _ => write!(f, "{}", e)?,
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ExpressionKind::LocalVariable { name, .. }
+            | ExpressionKind::GlobalVariable { name, .. }
+            | ExpressionKind::Function { name, .. } => write!(f, "{}", name),
+            ExpressionKind::Integer(n, type_) => write!(f, "{}{}", n, type_.suffix()),
+            ExpressionKind::Float(n, type_) => write!(f, "{:?}{}", n, type_.suffix()),
+            ExpressionKind::Binary { lhs, op, rhs } => {
+                write!(f, "{} {} {}", Operand(lhs), op, Operand(rhs))
+            }
+            ExpressionKind::Unary {
+                expr,
+                op: op @ (UnaryOperator::PostIncrement | UnaryOperator::PostDecrement),
+            } => write!(f, "{}{}", Operand(expr), op),
+            ExpressionKind::Unary { expr, op } => write!(f, "{}{}", op, Operand(expr)),
+            ExpressionKind::Call { callee, arguments } => {
+                match callee.kind {
+                    ExpressionKind::Unary { .. } => write!(f, "({})(", callee)?,
+                    _ => write!(f, "{}(", Operand(callee))?,
+                }
+                fmt_list(f, arguments)?;
+                write!(f, ")")
+            }
+            ExpressionKind::Index { expr, index } => write!(f, "{}[{}]", Operand(expr), index),
+            ExpressionKind::Member { expr, name, .. } => match &expr.kind {
+                ExpressionKind::Unary {
+                    expr,
+                    op: UnaryOperator::Dereference,
+                } => write!(f, "{}->{}", Operand(expr), name),
+                _ => write!(f, "{}.{}", Operand(expr), name),
+            },
+            ExpressionKind::Array { elements } => {
+                write!(f, "{{")?;
+                fmt_list(f, elements)?;
+                write!(f, "}}")
+            }
+            ExpressionKind::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => write!(
+                f,
+                "{} ? {} : {}",
+                Operand(condition),
+                then,
+                Operand(otherwise)
+            ),
+            ExpressionKind::Comma { lhs, rhs } => write!(f, "{}, {}", lhs, rhs),
+            ExpressionKind::Cast { expr, type_ } => write!(f, "({}){}", type_, Operand(expr)),
+            ExpressionKind::String(s) => {
+                let s = s.iter().flat_map(|b| std::ascii::escape_default(*b));
+                write!(f, "\"{}\"", s.map(char::from).collect::<String>())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ExpressionKind, IntegerType, StructType};
+
+    #[test]
+    fn test_display_type() {
+        let int = || Box::new(Type::Primitive(TypeEnum::Int));
+        let cases = vec![
+            (Type::Primitive(TypeEnum::Int), "int"),
+            (Type::Pointer(int()), "int *"),
+            (Type::Pointer(Box::new(Type::Pointer(int()))), "int **"),
+            (
+                Type::Array {
+                    type_: int(),
+                    size: 3,
+                },
+                "int[3]",
+            ),
+            (
+                Type::Array {
+                    type_: Box::new(Type::Pointer(int())),
+                    size: 3,
+                },
+                "int *[3]",
+            ),
+            (
+                Type::Pointer(Box::new(Type::Array {
+                    type_: int(),
+                    size: 3,
+                })),
+                "int (*)[3]",
+            ),
+            (
+                Type::Function {
+                    return_type: int(),
+                    parameters: vec![Type::Pointer(Box::new(Type::Primitive(TypeEnum::Char)))],
+                    variadic: true,
+                    prototyped: true,
+                },
+                "int (char *, ...)",
+            ),
+            (
+                Type::Pointer(Box::new(Type::Function {
+                    return_type: Box::new(Type::Primitive(TypeEnum::Void)),
+                    parameters: vec![],
+                    variadic: false,
+                    prototyped: true,
+                })),
+                "void (*)(void)",
+            ),
+            (
+                Type::Function {
+                    return_type: int(),
+                    parameters: vec![],
+                    variadic: false,
+                    prototyped: false,
+                },
+                "int ()",
+            ),
+            (
+                Type::Pointer(Box::new(Type::Struct(StructType::incomplete(
+                    String::from("node"),
+                    false,
+                )))),
+                "struct node *",
+            ),
+            (
+                Type::Array {
+                    type_: Box::new(Type::Struct(StructType::new(None, true, vec![]))),
+                    size: 2,
+                },
+                "union <anonymous>[2]",
+            ),
+            (
+                Type::Pointer(Box::new(Type::Primitive(TypeEnum::Char).qualified(
+                    Qualifiers {
+                        const_: true,
+                        ..Qualifiers::default()
+                    },
+                ))),
+                "const char *",
+            ),
+            (
+                Type::Pointer(int()).qualified(Qualifiers {
+                    const_: true,
+                    volatile: true,
+                    ..Qualifiers::default()
+                }),
+                "int *const volatile",
+            ),
+            (
+                Type::Pointer(Box::new(Type::Array {
+                    type_: int(),
+                    size: 4,
+                }))
+                .qualified(Qualifiers {
+                    restrict: true,
+                    ..Qualifiers::default()
+                }),
+                "int (*restrict)[4]",
+            ),
+        ];
+        for (type_, expected) in cases {
+            assert_eq!(type_.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_display_expression() {
+        let x = || {
+            Box::new(
+                ExpressionKind::LocalVariable {
+                    name: String::from("x"),
+                    offset: 8,
+                    type_: Type::Primitive(TypeEnum::Int),
+                }
+                .into(),
+            )
+        };
+        let cases: Vec<(Expression, &str)> = vec![
+            (
+                ExpressionKind::Binary {
+                    lhs: Box::new(
+                        ExpressionKind::Binary {
+                            lhs: x(),
+                            op: BinaryOperator::Plus,
+                            rhs: Box::new(ExpressionKind::Integer(1, IntegerType::Int).into()),
+                        }
+                        .into(),
+                    ),
+                    op: BinaryOperator::Asterisk,
+                    rhs: Box::new(ExpressionKind::Integer(2, IntegerType::Int).into()),
+                }
+                .into(),
+                "(x + 1) * 2",
+            ),
+            (
+                ExpressionKind::Unary {
+                    expr: x(),
+                    op: UnaryOperator::Dereference,
+                }
+                .into(),
+                "*x",
+            ),
+            (
+                ExpressionKind::Call {
+                    callee: Box::new(
+                        ExpressionKind::Function {
+                            name: String::from("foo"),
+                            type_: Type::Function {
+                                return_type: Box::new(Type::Primitive(TypeEnum::Int)),
+                                parameters: vec![],
+                                variadic: true,
+                                prototyped: true,
+                            },
+                        }
+                        .into(),
+                    ),
+                    arguments: vec![*x(), ExpressionKind::String(b"a\n".to_vec()).into()],
+                }
+                .into(),
+                "foo(x, \"a\\n\")",
+            ),
+            (
+                ExpressionKind::Call {
+                    callee: Box::new(
+                        ExpressionKind::Unary {
+                            expr: x(),
+                            op: UnaryOperator::Dereference,
+                        }
+                        .into(),
+                    ),
+                    arguments: vec![],
+                }
+                .into(),
+                "(*x)()",
+            ),
+            (
+                ExpressionKind::Conditional {
+                    condition: x(),
+                    then: Box::new(ExpressionKind::Comma { lhs: x(), rhs: x() }.into()),
+                    otherwise: Box::new(
+                        ExpressionKind::Conditional {
+                            condition: x(),
+                            then: x(),
+                            otherwise: x(),
+                        }
+                        .into(),
+                    ),
+                }
+                .into(),
+                "x ? x, x : (x ? x : x)",
+            ),
+            (
+                ExpressionKind::Cast {
+                    expr: Box::new(
+                        ExpressionKind::Binary {
+                            lhs: x(),
+                            op: BinaryOperator::Plus,
+                            rhs: x(),
+                        }
+                        .into(),
+                    ),
+                    type_: Type::Pointer(Box::new(Type::Primitive(TypeEnum::Char))),
+                }
+                .into(),
+                "(char *)(x + x)",
+            ),
+            (
+                ExpressionKind::Index {
+                    expr: x(),
+                    index: Box::new(ExpressionKind::Integer(0, IntegerType::Int).into()),
+                }
+                .into(),
+                "x[0]",
+            ),
+            (
+                ExpressionKind::Member {
+                    expr: Box::new(
+                        ExpressionKind::Unary {
+                            expr: x(),
+                            op: UnaryOperator::Dereference,
+                        }
+                        .into(),
+                    ),
+                    name: String::from("next"),
+                    offset: 8,
+                    type_: Type::Primitive(TypeEnum::Int),
+                }
+                .into(),
+                "x->next",
+            ),
+            (
+                ExpressionKind::Member {
+                    expr: x(),
+                    name: String::from("y"),
+                    offset: 8,
+                    type_: Type::Primitive(TypeEnum::Int),
+                }
+                .into(),
+                "x.y",
+            ),
+            (
+                ExpressionKind::Unary {
+                    expr: x(),
+                    op: UnaryOperator::PostIncrement,
+                }
+                .into(),
+                "x++",
+            ),
+            (
+                ExpressionKind::Binary {
+                    lhs: x(),
+                    op: BinaryOperator::LShiftAssignment,
+                    rhs: Box::new(
+                        ExpressionKind::Binary {
+                            lhs: x(),
+                            op: BinaryOperator::And,
+                            rhs: Box::new(ExpressionKind::Integer(1, IntegerType::Int).into()),
+                        }
+                        .into(),
+                    ),
+                }
+                .into(),
+                "x <<= (x && 1)",
+            ),
+        ];
+        for (expr, expected) in cases {
+            assert_eq!(expr.to_string(), expected);
+        }
+    }
+}
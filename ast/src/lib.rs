@@ -1,87 +1,405 @@
-#[derive(Debug, PartialEq, Eq)]
-pub struct Program {
-    pub statements: Vec<Statement>,
+mod constant;
+mod conversion;
+mod display;
+mod typing;
+
+use span::Span;
+
+pub use constant::{convert_constant, fold, fold_integer, Fold, Operation, Value};
+pub use conversion::{common_type, is_arithmetic, is_integer, is_scalar, promoted};
+pub use typing::{binary_type, conditional_type, expression_type, type_of, unary_type};
+
+/// statements of a translation unit, with expressions of type `E`: `Expression` as parsed, or
+/// annotated with their types once they are checked.
+#[derive(Debug, PartialEq)]
+pub struct Program<E = Expression> {
+    pub statements: Vec<Statement<E>>,
 }
-impl Program {
-    pub fn new(statements: Vec<Statement>) -> Self {
+impl<E> Program<E> {
+    pub fn new(statements: Vec<Statement<E>>) -> Self {
         Self { statements }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub enum Statement {
-    Expression(Expression),
+#[derive(Debug, PartialEq, Clone)]
+pub enum Statement<E = Expression> {
+    Expression(E),
     If {
-        condition: Expression,
-        consequence: Box<Statement>,
-        alternative: Option<Box<Statement>>,
+        condition: E,
+        consequence: Box<Statement<E>>,
+        alternative: Option<Box<Statement<E>>>,
     },
     While {
-        condition: Expression,
-        body: Box<Statement>,
+        condition: E,
+        body: Box<Statement<E>>,
     },
     For {
-        init: Option<Box<Statement>>,
-        condition: Option<Expression>,
-        post: Option<Box<Statement>>,
-        body: Box<Statement>,
+        init: Option<Box<Statement<E>>>,
+        condition: Option<E>,
+        post: Option<Box<Statement<E>>>,
+        body: Box<Statement<E>>,
+    },
+    DoWhile {
+        body: Box<Statement<E>>,
+        condition: E,
+    },
+    /// `cases` are the values of the `case` labels in `body`, `default` whether it has a
+    /// `default` label.
+    Switch {
+        condition: E,
+        body: Box<Statement<E>>,
+        cases: Vec<i64>,
+        default: bool,
+    },
+    /// `case value:` labelling `body`, `default:` when `value` is `None`.
+    Case {
+        value: Option<i64>,
+        body: Box<Statement<E>>,
+    },
+    Break,
+    Continue,
+    /// `name:` labelling `body`, the target of `goto name;` within the function.
+    Label {
+        name: String,
+        body: Box<Statement<E>>,
     },
-    Block(Vec<Statement>),
-    Return(Expression),
+    Goto(String),
+    Block(Vec<Statement<E>>),
+    /// `return;` when the value is `None`.
+    Return(Option<E>),
     FunctionDefinition {
         name: String,
-        arguments: Vec<Expression>, // Expression::LocalVariable
-        body: Vec<Statement>,
+        return_type: Type,
+        arguments: Vec<E>, // Expression::LocalVariable
+        body: Vec<Statement<E>>,
+        /// bytes of the frame below `rbp`, a multiple of 16.
+        stack_size: usize,
+        /// declared `static`, not visible outside the file.
+        static_: bool,
     },
     InitDeclaration {
         name: String,
         offset: usize,
         type_: Type,
-        init: Option<Expression>,
+        init: Option<E>,
+    },
+    /// declaration without a definition: a function prototype or an `extern` variable.
+    Declaration {
+        name: String,
+        type_: Type,
+    },
+    /// variable with a slot of its own in `.data` or `.bss`: a file scope one, or a block scope
+    /// `static` one under the label `name`. `init` must be a constant expression.
+    GlobalDeclaration {
+        name: String,
+        type_: Type,
+        init: Option<E>,
+        /// declared `static`, not visible outside the file.
+        static_: bool,
     },
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Type {
     Primitive(TypeEnum),
-    Array { type_: Box<Type>, size: i32 },
+    Array {
+        type_: Box<Type>,
+        size: i32,
+    },
     Pointer(Box<Type>),
+    /// `prototyped` is false for `()`, which unlike `(void)` says nothing of the parameters: the
+    /// arguments of a call are not checked, and get the default argument promotions.
+    Function {
+        return_type: Box<Type>,
+        parameters: Vec<Type>,
+        variadic: bool,
+        prototyped: bool,
+    },
+    Struct(StructType),
+    /// `type_` with qualifiers, never nested. the qualifiers of array elements are on the
+    /// element type.
+    Qualified {
+        qualifiers: Qualifiers,
+        type_: Box<Type>,
+    },
 }
 impl Type {
     pub fn size(&self) -> usize {
         match self {
             Type::Primitive(TypeEnum::Void) => 0,
-            Type::Primitive(TypeEnum::Char) => 1,
-            Type::Primitive(TypeEnum::Short) => 2,
-            Type::Primitive(TypeEnum::Int) => 8, // FIXME: clash with 4 now.
-            Type::Primitive(TypeEnum::Long) => 8,
-            Type::Primitive(TypeEnum::Float) => 4,
-            Type::Primitive(TypeEnum::Double) => 8,
+            Type::Primitive(
+                TypeEnum::Bool | TypeEnum::Char | TypeEnum::SignedChar | TypeEnum::UnsignedChar,
+            ) => 1,
+            Type::Primitive(TypeEnum::Short | TypeEnum::UnsignedShort) => 2,
+            Type::Primitive(TypeEnum::Int | TypeEnum::UnsignedInt | TypeEnum::Float) => 4,
+            Type::Primitive(
+                TypeEnum::Long
+                | TypeEnum::UnsignedLong
+                | TypeEnum::LongLong
+                | TypeEnum::UnsignedLongLong
+                | TypeEnum::Double,
+            ) => 8,
+            Type::Primitive(TypeEnum::LongDouble) => 16,
             Type::Pointer(_) => 8,
             Type::Array { size, type_, .. } => *size as usize * type_.size(),
+            // like GCC, for arithmetic on function pointers.
+            Type::Function { .. } => 1,
+            Type::Struct(struct_) => struct_.size(),
+            Type::Qualified { type_, .. } => type_.size(),
+        }
+    }
+
+    pub fn align(&self) -> usize {
+        match self {
+            Type::Primitive(TypeEnum::Void) => 1,
+            Type::Array { type_, .. } => type_.align(),
+            Type::Struct(struct_) => struct_.align(),
+            Type::Qualified { type_, .. } => type_.align(),
+            _ => self.size(),
+        }
+    }
+
+    /// whether `self` is a signed integer type. `char` is signed, pointers are compared as
+    /// unsigned addresses.
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self.unqualified(),
+            Type::Primitive(
+                TypeEnum::Char
+                    | TypeEnum::SignedChar
+                    | TypeEnum::Short
+                    | TypeEnum::Int
+                    | TypeEnum::Long
+                    | TypeEnum::LongLong
+            )
+        )
+    }
+
+    /// `self` with `qualifiers` added to its own.
+    pub fn qualified(self, qualifiers: Qualifiers) -> Type {
+        match self {
+            _ if qualifiers.is_empty() => self,
+            Type::Qualified {
+                qualifiers: own,
+                type_,
+            } => Type::Qualified {
+                qualifiers: own.union(qualifiers),
+                type_,
+            },
+            type_ => Type::Qualified {
+                qualifiers,
+                type_: Box::new(type_),
+            },
         }
     }
+
+    /// `self` without its qualifiers.
+    pub fn unqualified(&self) -> &Type {
+        match self {
+            Type::Qualified { type_, .. } => type_,
+            type_ => type_,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Qualifiers {
+    pub const_: bool,
+    pub volatile: bool,
+    pub restrict: bool,
+}
+impl Qualifiers {
+    pub fn is_empty(&self) -> bool {
+        *self == Qualifiers::default()
+    }
+
+    pub fn union(self, other: Qualifiers) -> Qualifiers {
+        Qualifiers {
+            const_: self.const_ || other.const_,
+            volatile: self.volatile || other.volatile,
+            restrict: self.restrict || other.restrict,
+        }
+    }
+}
+
+/// `struct` or `union`. `members` is `None` while it is incomplete, like the type of the `next`
+/// pointer inside the definition of a linked list node.
+#[derive(Debug, Clone, Eq)]
+pub struct StructType {
+    pub tag: Option<String>,
+    pub union: bool,
+    pub members: Option<Vec<Member>>,
+}
+impl StructType {
+    /// complete type laying out `members` in order, or all at offset 0 for a union.
+    pub fn new(tag: Option<String>, union: bool, members: Vec<(String, Type)>) -> Self {
+        let mut end = 0;
+        let members = members
+            .into_iter()
+            .map(|(name, type_)| {
+                let offset = match union {
+                    true => 0,
+                    false => align_to(end, type_.align()),
+                };
+                end = end.max(offset + type_.size());
+                Member {
+                    name,
+                    type_,
+                    offset,
+                }
+            })
+            .collect();
+        Self {
+            tag,
+            union,
+            members: Some(members),
+        }
+    }
+
+    pub fn incomplete(tag: String, union: bool) -> Self {
+        Self {
+            tag: Some(tag),
+            union,
+            members: None,
+        }
+    }
+
+    /// member called `name`, one of an anonymous member with the offset of the latter added.
+    pub fn member(&self, name: &str) -> Option<Member> {
+        self.members
+            .as_ref()?
+            .iter()
+            .find_map(|m| match m.type_.unqualified() {
+                Type::Struct(inner) if m.name.is_empty() => {
+                    let member = inner.member(name)?;
+                    Some(Member {
+                        offset: m.offset + member.offset,
+                        ..member
+                    })
+                }
+                _ if m.name == name => Some(m.clone()),
+                _ => None,
+            })
+    }
+
+    /// end of the last member rounded up to the alignment, 0 while incomplete.
+    pub fn size(&self) -> usize {
+        let end = self
+            .members
+            .iter()
+            .flatten()
+            .map(|m| m.offset + m.type_.size())
+            .max()
+            .unwrap_or(0);
+        align_to(end, self.align())
+    }
+
+    pub fn align(&self) -> usize {
+        self.members
+            .iter()
+            .flatten()
+            .map(|m| m.type_.align())
+            .max()
+            .unwrap_or(1)
+    }
+}
+
+/// tagged types are the same when their tags are, whether they are complete or not.
+impl PartialEq for StructType {
+    fn eq(&self, other: &Self) -> bool {
+        self.union == other.union
+            && match (&self.tag, &other.tag) {
+                (Some(tag), Some(other)) => tag == other,
+                (None, None) => self.members == other.members,
+                _ => false,
+            }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Member {
+    /// empty for an anonymous struct or union, whose members are those of the enclosing type.
+    pub name: String,
+    pub type_: Type,
+    /// bytes from the start of the struct.
+    pub offset: usize,
+}
+
+fn align_to(n: usize, align: usize) -> usize {
+    (n + align - 1) / align * align
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum TypeEnum {
     Void,
+    /// `_Bool`, which only holds 0 or 1.
+    Bool,
+    /// plain `char`, signed but a distinct type from `signed char`.
     Char,
+    SignedChar,
+    UnsignedChar,
     Short,
+    UnsignedShort,
     Int,
+    UnsignedInt,
     Long,
+    UnsignedLong,
+    LongLong,
+    UnsignedLongLong,
     Float,
     Double,
+    /// only named by declarations, e.g. in the system headers: no value or object can have it.
+    LongDouble,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub enum Expression {
+/// expression as parsed, with the span of its source.
+#[derive(Debug, Clone)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
+impl Expression {
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+/// expression without a source, e.g. in tests.
+impl From<ExpressionKind> for Expression {
+    fn from(kind: ExpressionKind) -> Self {
+        Self::new(kind, Span::default())
+    }
+}
+
+/// expressions are equal when they have the same structure, wherever they come from.
+impl PartialEq for Expression {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ExpressionKind {
+    /// `offset` is the start of the variable from the bottom of the frame.
     LocalVariable {
         name: String,
         offset: usize,
         type_: Type,
     },
-    Integer(i32),
+    /// variable with static storage, addressed through its label.
+    GlobalVariable {
+        name: String,
+        type_: Type,
+    },
+    /// function designator, used through its address.
+    Function {
+        name: String,
+        type_: Type,
+    },
+    /// value and type of an integer constant, negative values are sign extended to 64 bits.
+    Integer(u64, IntegerType),
+    Float(f64, FloatType),
     Binary {
         lhs: Box<Expression>,
         op: BinaryOperator,
@@ -91,18 +409,115 @@ pub enum Expression {
         expr: Box<Expression>,
         op: UnaryOperator,
     },
+    /// call to a function designator, or through a pointer to a function.
     Call {
-        callee_name: String,
+        callee: Box<Expression>,
         arguments: Vec<Expression>,
     },
     Index {
         expr: Box<Expression>,
         index: Box<Expression>,
     },
+    /// `expr.name`, `p->name` is `(*p).name`.
+    Member {
+        expr: Box<Expression>,
+        name: String,
+        offset: usize,
+        type_: Type,
+    },
     Array {
         elements: Vec<Expression>,
     },
-    String(String),
+    /// `condition ? then : otherwise`.
+    Conditional {
+        condition: Box<Expression>,
+        then: Box<Expression>,
+        otherwise: Box<Expression>,
+    },
+    /// `lhs, rhs`, the value of `rhs` once `lhs` is evaluated.
+    Comma {
+        lhs: Box<Expression>,
+        rhs: Box<Expression>,
+    },
+    /// `(type_)expr`, `type_` is a scalar type or `void`.
+    Cast {
+        expr: Box<Expression>,
+        type_: Type,
+    },
+    /// bytes of a string literal, without the terminating null.
+    String(Vec<u8>),
+}
+
+/// type of an integer constant, picked from its value, base and suffix.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum IntegerType {
+    Int,
+    UnsignedInt,
+    Long,
+    UnsignedLong,
+    LongLong,
+    UnsignedLongLong,
+}
+
+impl IntegerType {
+    /// largest value of the type.
+    pub fn max(&self) -> u64 {
+        match self {
+            IntegerType::Int => i32::MAX as u64,
+            IntegerType::UnsignedInt => u32::MAX as u64,
+            IntegerType::Long | IntegerType::LongLong => i64::MAX as u64,
+            IntegerType::UnsignedLong | IntegerType::UnsignedLongLong => u64::MAX,
+        }
+    }
+
+    pub fn type_(&self) -> Type {
+        Type::Primitive(match self {
+            IntegerType::Int => TypeEnum::Int,
+            IntegerType::UnsignedInt => TypeEnum::UnsignedInt,
+            IntegerType::Long => TypeEnum::Long,
+            IntegerType::UnsignedLong => TypeEnum::UnsignedLong,
+            IntegerType::LongLong => TypeEnum::LongLong,
+            IntegerType::UnsignedLongLong => TypeEnum::UnsignedLongLong,
+        })
+    }
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            IntegerType::Int => "",
+            IntegerType::UnsignedInt => "u",
+            IntegerType::Long => "l",
+            IntegerType::UnsignedLong => "ul",
+            IntegerType::LongLong => "ll",
+            IntegerType::UnsignedLongLong => "ull",
+        }
+    }
+}
+
+/// type of a floating constant, picked from its suffix.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FloatType {
+    Float,
+    Double,
+    LongDouble,
+}
+
+impl FloatType {
+    /// type of a constant with the suffix.
+    pub fn type_(&self) -> Type {
+        Type::Primitive(match self {
+            FloatType::Float => TypeEnum::Float,
+            FloatType::Double => TypeEnum::Double,
+            FloatType::LongDouble => TypeEnum::LongDouble,
+        })
+    }
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            FloatType::Float => "f",
+            FloatType::Double => "",
+            FloatType::LongDouble => "l",
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -112,17 +527,104 @@ pub enum BinaryOperator {
     Minus,
     Slash,
     Asterisk,
+    Percent,
     Lt,
     LtEq,
     Eq,
     NotEq,
+    LShift,
+    RShift,
+    BitAnd,
+    BitOr,
+    BitXor,
+    /// `&&`, the rhs is only evaluated if the lhs is true.
+    And,
+    /// `||`, the rhs is only evaluated if the lhs is false.
+    Or,
+    PlusAssignment,
+    MinusAssignment,
+    AsteriskAssignment,
+    SlashAssignment,
+    PercentAssignment,
+    LShiftAssignment,
+    RShiftAssignment,
+    BitAndAssignment,
+    BitOrAssignment,
+    BitXorAssignment,
+}
+impl BinaryOperator {
+    /// operator applied by a compound assignment, e.g. `Plus` for `+=`.
+    pub fn compound_operator(&self) -> Option<BinaryOperator> {
+        let op = match self {
+            BinaryOperator::PlusAssignment => BinaryOperator::Plus,
+            BinaryOperator::MinusAssignment => BinaryOperator::Minus,
+            BinaryOperator::AsteriskAssignment => BinaryOperator::Asterisk,
+            BinaryOperator::SlashAssignment => BinaryOperator::Slash,
+            BinaryOperator::PercentAssignment => BinaryOperator::Percent,
+            BinaryOperator::LShiftAssignment => BinaryOperator::LShift,
+            BinaryOperator::RShiftAssignment => BinaryOperator::RShift,
+            BinaryOperator::BitAndAssignment => BinaryOperator::BitAnd,
+            BinaryOperator::BitOrAssignment => BinaryOperator::BitOr,
+            BinaryOperator::BitXorAssignment => BinaryOperator::BitXor,
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    /// `lhs op rhs` like the generated code computes it, `None` if it cannot be evaluated.
+    pub fn fold(&self, lhs: i64, rhs: i64) -> Option<i64> {
+        let value = match self {
+            BinaryOperator::Plus => lhs.wrapping_add(rhs),
+            BinaryOperator::Minus => lhs.wrapping_sub(rhs),
+            BinaryOperator::Asterisk => lhs.wrapping_mul(rhs),
+            BinaryOperator::Slash => lhs.checked_div(rhs)?,
+            BinaryOperator::Percent => lhs.checked_rem(rhs)?,
+            BinaryOperator::LShift => lhs.wrapping_shl(rhs as u32),
+            BinaryOperator::RShift => lhs.wrapping_shr(rhs as u32),
+            BinaryOperator::BitAnd => lhs & rhs,
+            BinaryOperator::BitOr => lhs | rhs,
+            BinaryOperator::BitXor => lhs ^ rhs,
+            BinaryOperator::Lt => (lhs < rhs) as i64,
+            BinaryOperator::LtEq => (lhs <= rhs) as i64,
+            BinaryOperator::Eq => (lhs == rhs) as i64,
+            BinaryOperator::NotEq => (lhs != rhs) as i64,
+            BinaryOperator::And => (lhs != 0 && rhs != 0) as i64,
+            BinaryOperator::Or => (lhs != 0 || rhs != 0) as i64,
+            _ => return None,
+        };
+        Some(value)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum UnaryOperator {
+    Plus,
     Minus,
     Dereference,
     Reference,
-    // Increment,
-    // Decrement,
+    /// `!`
+    Not,
+    /// `~`
+    BitNot,
+    /// prefix `++`
+    Increment,
+    /// prefix `--`
+    Decrement,
+    /// postfix `++`
+    PostIncrement,
+    /// postfix `--`
+    PostDecrement,
+}
+impl UnaryOperator {
+    /// `op value` for the arithmetic operators, `None` for the others.
+    pub fn fold(&self, value: i64) -> Option<i64> {
+        let value = match self {
+            UnaryOperator::Plus => value,
+            UnaryOperator::Minus => value.wrapping_neg(),
+            UnaryOperator::Not => (value == 0) as i64,
+            UnaryOperator::BitNot => !value,
+            _ => return None,
+        };
+        Some(value)
+    }
 }
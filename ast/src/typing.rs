@@ -0,0 +1,159 @@
+use crate::{
+    common_type, is_arithmetic, is_integer, is_scalar, promoted, BinaryOperator, Expression,
+    ExpressionKind, Type, TypeEnum, UnaryOperator,
+};
+
+/// type of `op operand`, where `operand` is the type of the object for `&` and that of the value
+/// for the other operators. `None` if the operator does not take it.
+pub fn unary_type(op: &UnaryOperator, operand: &Type) -> Option<Type> {
+    let type_ = match op {
+        UnaryOperator::Reference => Type::Pointer(Box::new(operand.clone())),
+        UnaryOperator::Dereference => pointee(operand)?,
+        UnaryOperator::Not if is_scalar(operand) => Type::Primitive(TypeEnum::Int),
+        UnaryOperator::Plus | UnaryOperator::Minus if is_arithmetic(operand) => promoted(operand),
+        UnaryOperator::BitNot if is_integer(operand) => promoted(operand),
+        UnaryOperator::Increment
+        | UnaryOperator::Decrement
+        | UnaryOperator::PostIncrement
+        | UnaryOperator::PostDecrement
+            if is_scalar(operand) =>
+        {
+            operand.clone()
+        }
+        _ => return None,
+    };
+    Some(type_)
+}
+
+/// type of `lhs op rhs` from the types of the values of its operands, `None` if the operator does
+/// not take them. an assignment has the type of its lhs.
+pub fn binary_type(op: &BinaryOperator, lhs: &Type, rhs: &Type) -> Option<Type> {
+    let int = Type::Primitive(TypeEnum::Int);
+    let arithmetic = is_arithmetic(lhs) && is_arithmetic(rhs);
+    let integer = is_integer(lhs) && is_integer(rhs);
+    let type_ = match (op, lhs, rhs) {
+        (op, _, _) if *op == BinaryOperator::Assignment || op.compound_operator().is_some() => {
+            lhs.clone()
+        }
+        (BinaryOperator::And | BinaryOperator::Or, l, r) if is_scalar(l) && is_scalar(r) => int,
+        (
+            BinaryOperator::Lt | BinaryOperator::LtEq | BinaryOperator::Eq | BinaryOperator::NotEq,
+            l,
+            r,
+        ) => match (l, r) {
+            _ if arithmetic => int,
+            (Type::Pointer(_), Type::Pointer(_)) => int,
+            (Type::Pointer(_), other) | (other, Type::Pointer(_)) if is_integer(other) => int,
+            _ => return None,
+        },
+        (BinaryOperator::Plus | BinaryOperator::Minus, Type::Pointer(_), r) if is_integer(r) => {
+            lhs.clone()
+        }
+        (BinaryOperator::Plus, l, Type::Pointer(_)) if is_integer(l) => rhs.clone(),
+        // the difference of two pointers is a `ptrdiff_t`.
+        (BinaryOperator::Minus, Type::Pointer(l), Type::Pointer(r))
+            if l.unqualified() == r.unqualified() =>
+        {
+            Type::Primitive(TypeEnum::Long)
+        }
+        (BinaryOperator::LShift | BinaryOperator::RShift, l, _) if integer => promoted(l),
+        (
+            BinaryOperator::Percent
+            | BinaryOperator::BitAnd
+            | BinaryOperator::BitOr
+            | BinaryOperator::BitXor,
+            l,
+            r,
+        ) if integer => common_type(l, r),
+        (
+            BinaryOperator::Plus
+            | BinaryOperator::Minus
+            | BinaryOperator::Asterisk
+            | BinaryOperator::Slash,
+            l,
+            r,
+        ) if arithmetic => common_type(l, r),
+        _ => return None,
+    };
+    Some(type_)
+}
+
+/// type of `condition ? then : otherwise` from the types of the values of its branches, `None` if
+/// they have no type in common.
+pub fn conditional_type(then: &Type, otherwise: &Type) -> Option<Type> {
+    let type_ = match (then, otherwise) {
+        (l, r) if is_arithmetic(l) && is_arithmetic(r) => common_type(l, r),
+        (pointer @ Type::Pointer(_), r) if is_integer(r) => pointer.clone(),
+        (l, pointer @ Type::Pointer(_)) if is_integer(l) => pointer.clone(),
+        // `void *` takes any other pointer.
+        (Type::Pointer(l), pointer @ Type::Pointer(_))
+            if *l.unqualified() == Type::Primitive(TypeEnum::Void) =>
+        {
+            pointer.clone()
+        }
+        (l, r) if l == r => l.clone(),
+        (l @ Type::Pointer(_), Type::Pointer(_)) => l.clone(),
+        _ => return None,
+    };
+    Some(type_)
+}
+
+/// type of the value or the object `expr` designates, without qualifiers, from the types of the
+/// operands it depends on. `None` where they do not have the types it needs, which sema reports.
+pub fn type_of(expr: &Expression) -> Option<Type> {
+    let type_ = match &expr.kind {
+        ExpressionKind::LocalVariable { type_, .. }
+        | ExpressionKind::GlobalVariable { type_, .. }
+        | ExpressionKind::Function { type_, .. }
+        | ExpressionKind::Member { type_, .. }
+        | ExpressionKind::Cast { type_, .. } => type_.clone(),
+        ExpressionKind::Integer(_, type_) => type_.type_(),
+        ExpressionKind::Float(_, type_) => type_.type_(),
+        // the literal holds its terminating null.
+        ExpressionKind::String(bytes) => Type::Array {
+            type_: Box::new(Type::Primitive(TypeEnum::Char)),
+            size: bytes.len() as i32 + 1,
+        },
+        ExpressionKind::Unary {
+            expr,
+            op: op @ UnaryOperator::Reference,
+        } => unary_type(op, &type_of(expr)?)?,
+        ExpressionKind::Unary { expr, op } => unary_type(op, &expression_type(expr)?)?,
+        ExpressionKind::Binary { lhs, op, rhs } => {
+            binary_type(op, &expression_type(lhs)?, &expression_type(rhs)?)?
+        }
+        ExpressionKind::Call { callee, .. } => match pointee(&expression_type(callee)?)? {
+            Type::Function { return_type, .. } => *return_type,
+            _ => return None,
+        },
+        ExpressionKind::Index { expr, index } => {
+            match (expression_type(expr)?, expression_type(index)?) {
+                (Type::Pointer(element), _) | (_, Type::Pointer(element)) => *element,
+                _ => return None,
+            }
+        }
+        ExpressionKind::Array { .. } => return None,
+        ExpressionKind::Conditional {
+            then, otherwise, ..
+        } => conditional_type(&expression_type(then)?, &expression_type(otherwise)?)?,
+        ExpressionKind::Comma { rhs, .. } => expression_type(rhs)?,
+    };
+    Some(type_.unqualified().clone())
+}
+
+/// type of the value of `expr`, arrays and functions converted to pointers.
+pub fn expression_type(expr: &Expression) -> Option<Type> {
+    match type_of(expr)? {
+        Type::Array { type_, .. } => Some(Type::Pointer(type_)),
+        function @ Type::Function { .. } => Some(Type::Pointer(Box::new(function))),
+        type_ => Some(type_),
+    }
+}
+
+/// type `pointer` points to, `None` if it is not a pointer.
+fn pointee(pointer: &Type) -> Option<Type> {
+    match pointer {
+        Type::Pointer(type_) => Some(type_.unqualified().clone()),
+        _ => None,
+    }
+}
@@ -1,55 +1,193 @@
-use ast::{Expression, Statement};
+use ast::convert_constant;
+use diagnostic::Diagnostic;
 use helper::rand::rand;
+use sema::TypedExpression;
 
-use crate::CodeGenerator;
+use crate::{unsupported, CodeGenerator, Statement};
 
 impl CodeGenerator {
     pub(super) fn gen_if(
         &mut self,
-        condition: &Expression,
-        consequence: &Box<Statement>,
+        condition: &TypedExpression,
+        consequence: &Statement,
         alternative: &Option<Box<Statement>>,
-    ) {
+    ) -> Result<(), Diagnostic> {
         println!("# -- start if");
         match alternative {
             Some(alternative) => {
                 let label_else = format!(".Lelse{}", rand());
                 let label_end = format!(".Lend{}", rand());
-                self.gen_expr(condition);
+                self.gen_expr(condition)?;
                 println!("  pop rax");
                 println!("  cmp rax, 0");
                 println!("  je {label_else}");
-                self.gen_stmt(consequence);
+                self.gen_stmt(consequence)?;
                 println!("  jmp {label_end}");
                 println!("{label_else}:");
-                self.gen_stmt(alternative);
+                self.gen_stmt(alternative)?;
                 println!("{label_end}:");
             }
             None => {
                 let label = format!(".Lend{}", rand());
-                self.gen_expr(condition);
+                self.gen_expr(condition)?;
                 println!("  pop rax");
                 println!("  cmp rax, 0");
                 println!("  je {}", label);
-                self.gen_stmt(consequence);
+                self.gen_stmt(consequence)?;
                 println!("{label}:");
             }
         }
         println!("# -- end if");
+        Ok(())
     }
 
-    pub(super) fn gen_while(&mut self, condition: &Expression, body: &Box<Statement>) {
+    pub(super) fn gen_while(
+        &mut self,
+        condition: &TypedExpression,
+        body: &Statement,
+    ) -> Result<(), Diagnostic> {
         println!("# -- start while");
         let label_begin = format!(".Lbegin{}", rand());
         let label_end = format!(".Lend{}", rand());
         println!("{label_begin}:");
-        self.gen_expr(condition);
+        self.gen_expr(condition)?;
         println!("  pop rax");
         println!("  cmp rax, 0");
         println!("  je {label_end}");
-        self.gen_stmt(body);
+        self.gen_loop_body(body, &label_end, &label_begin)?;
         println!("  jmp {label_begin}");
         println!("{label_end}:");
         println!("# -- end while");
+        Ok(())
+    }
+
+    /// jumps to the label of the case matching `condition`, through a table when the values of
+    /// `cases` are dense enough, or else after comparing it with each of them.
+    pub(super) fn gen_switch(
+        &mut self,
+        condition: &TypedExpression,
+        body: &Statement,
+        cases: &[i64],
+        default: bool,
+    ) -> Result<(), Diagnostic> {
+        println!("# -- start switch");
+        let label = format!(".Lswitch{}", rand());
+        let label_end = format!("{label}_end");
+        let label_default = match default {
+            true => format!("{label}_default"),
+            false => label_end.clone(),
+        };
+        self.gen_expr(condition)?;
+        println!("  pop rax");
+
+        // the case values converted to the type of the condition, with the values they are
+        // labelled by.
+        let values = cases
+            .iter()
+            .map(|value| (convert_constant(*value, &condition.type_), *value))
+            .collect::<Vec<_>>();
+        let min = values
+            .iter()
+            .map(|(value, _)| *value)
+            .min()
+            .unwrap_or_default();
+        let max = values
+            .iter()
+            .map(|(value, _)| *value)
+            .max()
+            .unwrap_or_default();
+        let range = max as i128 - min as i128 + 1;
+        if cases.len() >= 4 && range <= 2 * cases.len() as i128 {
+            // indices below `min` wrap around to large unsigned ones.
+            gen_sub(min);
+            println!("  cmp rax, {}", range - 1);
+            println!("  ja {label_default}");
+            println!("  lea rdi, {label}_table[rip]");
+            println!("  jmp [rdi+rax*8]");
+            println!("  .section .rodata");
+            println!("  .align 8");
+            println!("{label}_table:");
+            for i in 0..range {
+                let value = (min as i128 + i) as i64;
+                match values.iter().find(|(converted, _)| *converted == value) {
+                    Some((_, case)) => println!("  .quad {}", case_label(&label, *case)),
+                    None => println!("  .quad {label_default}"),
+                }
+            }
+            println!("  .text");
+        } else {
+            for (value, case) in values {
+                gen_cmp(value);
+                println!("  je {}", case_label(&label, case));
+            }
+            println!("  jmp {label_default}");
+        }
+
+        self.switch_labels.push(label);
+        self.break_labels.push(label_end.clone());
+        let result = self.gen_stmt(body);
+        self.break_labels.pop();
+        self.switch_labels.pop();
+        result?;
+        println!("{label_end}:");
+        println!("# -- end switch");
+        Ok(())
+    }
+
+    pub(super) fn gen_case(
+        &mut self,
+        value: Option<i64>,
+        body: &Statement,
+    ) -> Result<(), Diagnostic> {
+        let Some(label) = self.switch_labels.last() else {
+            return Err(unsupported(String::from("case label outside of a switch")));
+        };
+        match value {
+            Some(value) => println!("{}:", case_label(label, value)),
+            None => println!("{label}_default:"),
+        }
+        self.gen_stmt(body)
+    }
+
+    pub(super) fn gen_label(&mut self, name: &str, body: &Statement) -> Result<(), Diagnostic> {
+        println!("{}:", self.user_label(name));
+        self.gen_stmt(body)
+    }
+
+    pub(super) fn gen_goto(&self, name: &str) {
+        println!("  jmp {}", self.user_label(name));
+    }
+
+    /// assembly label of the label `name` of the function being generated.
+    fn user_label(&self, name: &str) -> String {
+        format!(".L{}.{}", self.function, name)
+    }
+}
+
+/// label of `case value:` in the switch labelled `switch`.
+fn case_label(switch: &str, value: i64) -> String {
+    format!("{}_{:x}", switch, value as u64)
+}
+
+/// `rax -= value`.
+fn gen_sub(value: i64) {
+    match i32::try_from(value) {
+        Ok(0) => {}
+        Ok(value) => println!("  sub rax, {}", value),
+        Err(_) => {
+            println!("  mov rdi, {}", value);
+            println!("  sub rax, rdi");
+        }
+    }
+}
+
+/// compares `rax` with `value`, which may not fit in an immediate.
+fn gen_cmp(value: i64) {
+    match i32::try_from(value) {
+        Ok(value) => println!("  cmp rax, {}", value),
+        Err(_) => {
+            println!("  mov rdi, {}", value);
+            println!("  cmp rax, rdi");
+        }
     }
 }
@@ -1,303 +1,650 @@
-use ast::{BinaryOperator, Expression, Type, TypeEnum, UnaryOperator};
+use ast::{BinaryOperator, Type, TypeEnum, UnaryOperator};
+use diagnostic::Diagnostic;
+use helper::rand::rand;
+use sema::{ExpressionKind, TypedExpression};
 
-use crate::CodeGenerator;
+use crate::float::{
+    gen_float_arithmetic, gen_float_negate, gen_float_to_float, gen_float_to_integer, gen_from_xmm,
+    gen_integer_to_float, is_floating,
+};
+use crate::{unsupported, CodeGenerator};
 
 impl CodeGenerator {
-    pub(super) fn gen_expr(&mut self, node: &Expression) {
-        match node {
-            Expression::Integer(int) => {
-                println!("  push {}", int);
+    /// pushes the value of `node`. each kind of expression is generated by a function of its
+    /// own, which keeps the frames of this recursion small.
+    pub(super) fn gen_expr(&mut self, node: &TypedExpression) -> Result<(), Diagnostic> {
+        match &node.kind {
+            ExpressionKind::Unary { expr, op } => self.gen_unary(node, expr, op),
+            ExpressionKind::Call { callee, arguments } => self.gen_call(node, callee, arguments),
+            ExpressionKind::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => self.gen_conditional(condition, then, otherwise),
+            ExpressionKind::Comma { lhs, rhs } => self.gen_comma(lhs, rhs),
+            ExpressionKind::Cast { expr, .. } => self.gen_cast(node, expr),
+            ExpressionKind::Binary { lhs, op, rhs } => self.gen_binary(node, lhs, op, rhs),
+            _ => self.gen_primary(node),
+        }
+    }
+
+    /// pushes the value of a variable, a function designator or a literal.
+    fn gen_primary(&mut self, node: &TypedExpression) -> Result<(), Diagnostic> {
+        match &node.kind {
+            ExpressionKind::Integer(value, _) => {
+                // `push` takes a sign extended 32 bit immediate only.
+                let value = *value as i64;
+                if i32::try_from(value).is_ok() {
+                    println!("  push {}", value);
+                } else {
+                    println!("  mov rax, {}", value);
+                    println!("  push rax");
+                }
+            }
+            ExpressionKind::Float(value, _) => {
+                let label = self.add_float_lit(*value, &node.type_);
+                println!("  lea rax, {}[rip]", label);
+                gen_load(&node.type_);
+                println!("  push rax");
+            }
+            ExpressionKind::LocalVariable { .. }
+            | ExpressionKind::GlobalVariable { .. }
+            | ExpressionKind::Member { .. }
+            | ExpressionKind::Index { .. } => {
+                self.gen_address(node)?;
+                println!("  pop rax");
+                gen_load(&node.type_);
+                println!("  push rax");
+            }
+            ExpressionKind::Function { .. } | ExpressionKind::String(_) => {
+                self.gen_address(node)?
+            }
+            ExpressionKind::Array { .. } => {
+                return Err(
+                    unsupported(format!("`{}` can only be used as an initializer", node))
+                        .with_primary_label(node.span, "not in a declaration"),
+                )
+            }
+            _ => unreachable!("`{}` is not a primary expression", node),
+        }
+        Ok(())
+    }
+
+    /// pushes the value of `rhs` once `lhs` is evaluated.
+    fn gen_comma(
+        &mut self,
+        lhs: &TypedExpression,
+        rhs: &TypedExpression,
+    ) -> Result<(), Diagnostic> {
+        self.gen_expr(lhs)?;
+        println!("  pop rax");
+        self.gen_expr(rhs)
+    }
+
+    /// pushes the value of `expr` converted to the type of the cast `node`.
+    fn gen_cast(
+        &mut self,
+        node: &TypedExpression,
+        expr: &TypedExpression,
+    ) -> Result<(), Diagnostic> {
+        self.gen_expr(expr)?;
+        println!("  pop rax");
+        gen_convert(&expr.type_, &node.type_);
+        println!("  push rax");
+        Ok(())
+    }
+
+    /// pushes the value of `op expr`, `node`.
+    fn gen_unary(
+        &mut self,
+        node: &TypedExpression,
+        expr: &TypedExpression,
+        op: &UnaryOperator,
+    ) -> Result<(), Diagnostic> {
+        match op {
+            UnaryOperator::Plus => self.gen_expr(expr),
+            UnaryOperator::Increment => {
+                self.gen_compound_assignment(expr, &BinaryOperator::Plus, None, false)
+            }
+            UnaryOperator::Decrement => {
+                self.gen_compound_assignment(expr, &BinaryOperator::Minus, None, false)
+            }
+            UnaryOperator::PostIncrement => {
+                self.gen_compound_assignment(expr, &BinaryOperator::Plus, None, true)
+            }
+            UnaryOperator::PostDecrement => {
+                self.gen_compound_assignment(expr, &BinaryOperator::Minus, None, true)
+            }
+            UnaryOperator::Reference => self.gen_address(expr),
+            UnaryOperator::Minus
+            | UnaryOperator::Not
+            | UnaryOperator::BitNot
+            | UnaryOperator::Dereference => {
+                self.gen_expr(expr)?;
+                gen_unary_operation(op, &node.type_);
+                Ok(())
+            }
+        }
+    }
+
+    /// pushes the value returned by the call `node`.
+    fn gen_call(
+        &mut self,
+        node: &TypedExpression,
+        callee: &TypedExpression,
+        arguments: &[TypedExpression],
+    ) -> Result<(), Diagnostic> {
+        if let Type::Struct(_) = node.type_.unqualified() {
+            return Err(unsupported(format!(
+                "call to `{}` returning `{}` is not supported yet",
+                callee, node.type_
+            ))
+            .with_primary_label(node.span, "returns a struct"));
+        }
+        let registers =
+            argument_registers(arguments.iter().map(|arg| &arg.type_)).map_err(|i| {
+                unsupported(format!(
+                    "call to `{}` passes {} arguments, at most {} integer and {} \
+                     floating ones are supported",
+                    callee,
+                    arguments.len(),
+                    INTEGER_REGISTERS.len(),
+                    FLOATING_REGISTERS
+                ))
+                .with_primary_label(arguments[i].span, "no register left for this")
+            })?;
+        // a function designator is called directly, anything else through its value.
+        let direct = match &callee.kind {
+            ExpressionKind::Cast { expr, .. } => match &expr.kind {
+                ExpressionKind::Function { name } => Some(name),
+                _ => None,
+            },
+            _ => None,
+        };
+        if direct.is_none() {
+            self.gen_expr(callee)?;
+        }
+        // every argument is evaluated before the registers are set, as the evaluation
+        // of one may call another function.
+        for arg in arguments {
+            if let Type::Struct(_) = arg.type_ {
+                return Err(unsupported(format!(
+                    "passing `{}` of type `{}` by value is not supported yet",
+                    arg, arg.type_
+                ))
+                .with_primary_label(arg.span, "passed by value"));
             }
-            Expression::Unary { expr, op } => match op {
-                UnaryOperator::Minus => {
-                    self.gen_expr(expr);
+            self.gen_expr(arg)?;
+        }
+        for register in registers.iter().rev() {
+            match register {
+                Register::Integer(name) => println!("  pop {}", name),
+                Register::Floating(i) => {
                     println!("  pop rax");
-                    println!("  neg rax");
+                    println!("  movq xmm{}, rax", i);
                 }
-                UnaryOperator::Reference => {
-                    self.gen_lval(expr);
-                }
-                UnaryOperator::Dereference => {
-                    self.gen_expr(expr);
+            }
+        }
+        // a variadic callee is told how many vector registers hold arguments.
+        let floating = registers
+            .iter()
+            .filter(|register| matches!(register, Register::Floating(_)))
+            .count();
+        println!("  mov eax, {}", floating);
+        if direct.is_none() {
+            println!("  pop r10");
+        }
+        // the callee expects `rsp` 16-byte aligned, whatever the temporaries pushed. the
+        // old `rsp` is saved above the alignment, where the call returns.
+        println!("  mov r11, rsp");
+        println!("  and rsp, -16");
+        println!("  sub rsp, 8");
+        println!("  push r11");
+        match direct {
+            Some(name) => println!("  call {}", name),
+            None => println!("  call r10"),
+        }
+        println!("  mov rsp, [rsp]");
+        // only the bytes of the result type are defined, the callee may not extend them.
+        match is_floating(&node.type_) {
+            true => gen_from_xmm(&node.type_),
+            false => gen_extend(&node.type_),
+        }
+        println!("  push rax");
+        Ok(())
+    }
+
+    /// pushes the value of `condition ? then : otherwise`.
+    fn gen_conditional(
+        &mut self,
+        condition: &TypedExpression,
+        then: &TypedExpression,
+        otherwise: &TypedExpression,
+    ) -> Result<(), Diagnostic> {
+        let label_else = format!(".Lelse{}", rand());
+        let label_end = format!(".Lend{}", rand());
+        self.gen_expr(condition)?;
+        println!("  pop rax");
+        println!("  cmp rax, 0");
+        println!("  je {label_else}");
+        self.gen_expr(then)?;
+        println!("  jmp {label_end}");
+        println!("{label_else}:");
+        self.gen_expr(otherwise)?;
+        println!("{label_end}:");
+        Ok(())
+    }
+
+    /// pushes the value of `lhs op rhs`, `node`.
+    fn gen_binary(
+        &mut self,
+        node: &TypedExpression,
+        lhs: &TypedExpression,
+        op: &BinaryOperator,
+        rhs: &TypedExpression,
+    ) -> Result<(), Diagnostic> {
+        match op {
+            BinaryOperator::Assignment => self.gen_assignment(lhs, rhs),
+            BinaryOperator::And | BinaryOperator::Or => self.gen_logical(lhs, op, rhs),
+            _ => match op.compound_operator() {
+                Some(op) => self.gen_compound_assignment(lhs, &op, Some(rhs), false),
+                None => {
+                    self.gen_expr(lhs)?;
+                    self.gen_expr(rhs)?;
+                    println!("  pop rdi");
                     println!("  pop rax");
-                    match expr.as_ref() {
-                        Expression::LocalVariable { type_, .. } => {
-                            match type_ {
-                                Type::Pointer(ptr) => {
-                                    match ptr.as_ref() {
-                                        Type::Primitive(pt) => {
-                                            match pt {
-                                                // TODO:
-                                                TypeEnum::Int => println!("  mov rax, [rax]"),
-                                                TypeEnum::Char => {
-                                                    println!("  movzx rax, byte ptr [rax]")
-                                                }
-                                                // TODO:
-                                                _ => println!("  mov rax, [rax]"),
-                                            }
-                                        }
-                                        // TODO:
-                                        _ => println!("  mov rax, [rax]"),
-                                    }
-                                }
-                                Type::Array { .. } => println!("  mov rax, [rax]"),
-                                _ => panic!("invalid dereference"),
-                            }
-                        }
-                        _ => panic!("invalid dereference"),
-                    }
+                    gen_arithmetic(op, &lhs.type_, &rhs.type_);
+                    gen_extend(&node.type_);
                     println!("  push rax");
+                    Ok(())
                 }
             },
-            Expression::LocalVariable { type_, .. } => match type_ {
-                // cast to pointer
-                Type::Array { .. } => self.gen_lval(node),
-                _ => {
-                    self.gen_lval(node);
+        }
+    }
+
+    /// pushes the value of `lhs = rhs`, stored in `lhs`.
+    fn gen_assignment(
+        &mut self,
+        lhs: &TypedExpression,
+        rhs: &TypedExpression,
+    ) -> Result<(), Diagnostic> {
+        println!("  # --start assignment");
+        println!("  # --left");
+        self.gen_address(lhs)?;
+        println!("  # --right");
+        self.gen_expr(rhs)?;
+        println!("  # --assignment");
+        println!("  pop rdi");
+        println!("  pop rax");
+        gen_store(&lhs.type_);
+        println!("  push rdi");
+        println!("  # --end assignment");
+        Ok(())
+    }
+
+    /// pushes the address of the object `lhs` designates.
+    fn gen_address(&mut self, lhs: &TypedExpression) -> Result<(), Diagnostic> {
+        match &lhs.kind {
+            ExpressionKind::Unary {
+                expr,
+                op: UnaryOperator::Dereference,
+            } => self.gen_expr(expr),
+            ExpressionKind::Index { expr, index } => {
+                self.gen_expr(expr)?; // pointer
+                self.gen_expr(index)?;
+                println!("  pop rdi");
+                println!("  pop rax");
+                println!("  imul rdi, {}", lhs.type_.size());
+                println!("  add rax, rdi");
+                println!("  push rax");
+                Ok(())
+            }
+            ExpressionKind::LocalVariable { .. } | ExpressionKind::GlobalVariable { .. } => {
+                self.gen_lval(lhs)
+            }
+            ExpressionKind::Function { name } => {
+                println!("  lea rax, {}[rip]", name);
+                println!("  push rax");
+                Ok(())
+            }
+            ExpressionKind::String(bytes) => {
+                let label = self.add_str_lit(bytes);
+                println!("  lea rax, {}[rip]", label);
+                println!("  push rax");
+                Ok(())
+            }
+            ExpressionKind::Member { expr, offset, .. } => {
+                self.gen_address(expr)?;
+                if *offset > 0 {
                     println!("  pop rax");
-                    println!("  mov rax, [rax]");
+                    println!("  add rax, {}", offset);
                     println!("  push rax");
                 }
-            },
-            Expression::Call {
-                callee_name,
-                arguments,
-            } => {
-                if callee_name == "sizeof" {
-                    match &arguments[0] {
-                        Expression::LocalVariable { type_, .. } => {
-                            println!("  push {}", type_.size());
-                        }
-                        Expression::Integer(_) | Expression::Binary { .. } => {
-                            println!("  push 8");
-                        }
-                        Expression::Unary { expr, op } => match op {
-                            UnaryOperator::Reference => {
-                                println!("  push 8");
-                            }
-                            UnaryOperator::Dereference => match expr.as_ref() {
-                                Expression::LocalVariable { type_, .. } => {
-                                    println!("  push {}", type_.size());
-                                }
-                                _ => panic!("invalid sizeof"),
-                            },
-                            // TODO: judge type
-                            _ => panic!("invalid sizeof"),
-                        },
-                        _ => panic!("invalid sizeof"),
-                    }
-                    return;
-                }
+                Ok(())
+            }
+            _ => Err(assignment_error(lhs)),
+        }
+    }
 
-                let registers = ["rdi", "rsi", "rdx", "rcx", "r8d", "r9d"];
-                if arguments.len() > registers.len() {
-                    panic!("too many arguments");
+    /// `lhs op= rhs`, or `++`/`--` on `lhs` when `rhs` is `None`.
+    /// a postfix update leaves the old value of `lhs`, everything else the new one.
+    fn gen_compound_assignment(
+        &mut self,
+        lhs: &TypedExpression,
+        op: &BinaryOperator,
+        rhs: Option<&TypedExpression>,
+        postfix: bool,
+    ) -> Result<(), Diagnostic> {
+        println!("  # --start compound assignment");
+        self.gen_address(lhs)?;
+        println!("  mov rax, [rsp]");
+        gen_load(&lhs.type_);
+        // `lhs` is converted to the type `rhs` is, but for pointer arithmetic and shifts. `++`
+        // and `--` add 1 of the type of `lhs`, or an element to a pointer.
+        let int = Type::Primitive(TypeEnum::Int);
+        let (lhs_type, rhs_type) = match rhs {
+            Some(rhs) => match (&lhs.type_, op) {
+                (Type::Pointer(_), _) | (_, BinaryOperator::LShift | BinaryOperator::RShift) => {
+                    (&lhs.type_, &rhs.type_)
                 }
-                for (i, arg) in arguments.iter().enumerate() {
-                    self.gen_expr(arg);
-                    println!("  pop {}", registers[i]);
-                }
-                println!("  mov rax, 0x0");
-                println!("  call {}", callee_name);
+                _ => (&rhs.type_, &rhs.type_),
+            },
+            None if is_floating(&lhs.type_) => (&lhs.type_, &lhs.type_),
+            None => (&lhs.type_, &int),
+        };
+        gen_convert(&lhs.type_, lhs_type);
+        println!("  push rax");
+        match rhs {
+            Some(rhs) => self.gen_expr(rhs)?,
+            None => {
+                println!("  mov eax, 1");
+                gen_convert(&int, rhs_type);
                 println!("  push rax");
             }
-            Expression::Index { expr, index } => {
-                match expr.as_ref() {
-                    Expression::LocalVariable { type_, .. } => match type_ {
-                        Type::Array { type_, .. } => {
-                            self.gen_expr(expr); // pointer
-                            self.gen_expr(index);
-                            println!("  pop rdi");
-                            println!("  pop rax"); // pointer
+        }
+        println!("  pop rdi");
+        println!("  pop rax");
+        println!("  mov rsi, rax");
+        gen_arithmetic(op, lhs_type, rhs_type);
+        gen_convert(lhs_type, &lhs.type_);
+        println!("  mov rdi, rax");
+        println!("  pop rax");
+        gen_store(&lhs.type_);
+        if postfix {
+            println!("  push rsi");
+        } else {
+            println!("  push rdi");
+        }
+        println!("  # --end compound assignment");
+        Ok(())
+    }
 
-                            // calc offset
-                            println!("  imul rdi, {}", type_.size());
-                            println!("  sub rax, rdi");
+    /// `&&` and `||`, `rhs` is only evaluated when `lhs` does not decide the result.
+    fn gen_logical(
+        &mut self,
+        lhs: &TypedExpression,
+        op: &BinaryOperator,
+        rhs: &TypedExpression,
+    ) -> Result<(), Diagnostic> {
+        let label_short = format!(".Lshort{}", rand());
+        let label_end = format!(".Lend{}", rand());
+        // `&&` stops at the first 0, `||` at the first non-zero.
+        let (jump, short_value) = match op {
+            BinaryOperator::And => ("je", 0),
+            _ => ("jne", 1),
+        };
+        for operand in [lhs, rhs] {
+            self.gen_expr(operand)?;
+            println!("  pop rax");
+            println!("  cmp rax, 0");
+            println!("  {jump} {label_short}");
+        }
+        println!("  push {}", 1 - short_value);
+        println!("  jmp {label_end}");
+        println!("{label_short}:");
+        println!("  push {short_value}");
+        println!("{label_end}:");
+        Ok(())
+    }
+}
 
-                            // load value from offset
-                            println!("  mov rax, [rax]");
-                            println!("  push rax");
-                        }
-                        Type::Pointer(_) => {
-                            self.gen_expr(expr); // pointer
-                            self.gen_expr(index);
-                            println!("  pop rdi");
-                            println!("  pop rax"); // pointer
-                            println!("  add rax, rdi");
+/// replaces the operand on top of the stack with `op` applied to it, a value of `type_`.
+fn gen_unary_operation(op: &UnaryOperator, type_: &Type) {
+    println!("  pop rax");
+    match op {
+        UnaryOperator::Minus if is_floating(type_) => gen_float_negate(type_),
+        UnaryOperator::Minus => {
+            println!("  neg rax");
+            gen_extend(type_);
+        }
+        UnaryOperator::Not => {
+            println!("  cmp rax, 0");
+            println!("  sete al");
+            println!("  movzb rax, al");
+        }
+        UnaryOperator::BitNot => {
+            println!("  not rax");
+            gen_extend(type_);
+        }
+        UnaryOperator::Dereference => gen_load(type_),
+        _ => unreachable!("`{}` is not applied to a value", op),
+    }
+    println!("  push rax");
+}
 
-                            // TODO: judge type
-                            // load value from offset
-                            println!("  movzx eax, byte ptr [rax]");
-                            println!("  push rax");
-                        }
-                        _ => panic!(
-                            "Invalid node: {:?}.\nleft node is not array on index expression.",
-                            expr
-                        ),
-                    },
-                    _ => panic!(
-                        "Invalid node: {:?}.\nleft node is not array on index expression.",
-                        expr
-                    ),
-                }
+/// `rax = rax op rdi` for operands of type `lhs` and `rhs`. an integer added to or subtracted
+/// from a pointer counts elements, and so does the difference of two pointers. the type of
+/// `lhs` decides whether division, `>>` and comparisons are signed, or floating.
+fn gen_arithmetic(op: &BinaryOperator, lhs: &Type, rhs: &Type) {
+    if is_floating(lhs) {
+        return gen_float_arithmetic(op, lhs);
+    }
+    let signed = lhs.is_signed();
+    match op {
+        BinaryOperator::Plus | BinaryOperator::Minus => match (lhs, rhs) {
+            (Type::Pointer(pointee), Type::Pointer(_)) => {
+                println!("  sub rax, rdi");
+                println!("  mov rdi, {}", element_size(pointee));
+                println!("  cqo");
+                println!("  idiv rdi");
             }
-            Expression::Binary { lhs, op, rhs } => {
+            _ => {
+                if let Type::Pointer(pointee) = lhs {
+                    println!("  imul rdi, {}", element_size(pointee));
+                }
                 match op {
-                    BinaryOperator::Plus => match lhs.as_ref() {
-                        Expression::LocalVariable { type_, .. } => match type_ {
-                            Type::Pointer(_) => {
-                                self.gen_expr(lhs);
-                                self.gen_expr(rhs);
-                                println!("  pop rdi");
-                                println!("  pop rax");
-                                println!("  imul rdi, {}", type_.size());
-                                println!("  sub rax, rdi");
-                            }
-                            _ => {
-                                self.gen_expr(lhs);
-                                self.gen_expr(rhs);
-                                println!("  pop rdi");
-                                println!("  pop rax");
-                                println!("  add rax, rdi");
-                            }
-                        },
-                        _ => {
-                            self.gen_expr(lhs);
-                            self.gen_expr(rhs);
-                            println!("  pop rdi");
-                            println!("  pop rax");
-                            println!("  add rax, rdi");
-                        }
-                    },
-                    BinaryOperator::Minus => match lhs.as_ref() {
-                        Expression::LocalVariable { type_, .. } => match type_ {
-                            Type::Pointer(_) => {
-                                self.gen_expr(lhs);
-                                self.gen_expr(rhs);
-                                println!("  pop rdi");
-                                println!("  pop rax");
-                                println!("  imul rdi, {}", type_.size());
-                                println!("  add rax, rdi");
-                            }
-                            _ => {
-                                self.gen_expr(lhs);
-                                self.gen_expr(rhs);
-                                println!("  pop rdi");
-                                println!("  pop rax");
-                                println!("  sub rax, rdi");
-                            }
-                        },
-                        _ => {
-                            self.gen_expr(lhs);
-                            self.gen_expr(rhs);
-                            println!("  pop rdi");
-                            println!("  pop rax");
-                            println!("  sub rax, rdi");
-                        }
-                    },
-                    BinaryOperator::Asterisk => {
-                        self.gen_expr(lhs);
-                        self.gen_expr(rhs);
-                        println!("  pop rdi");
-                        println!("  pop rax");
-                        println!("  imul rax, rdi");
-                    }
-                    BinaryOperator::Slash => {
-                        self.gen_expr(lhs);
-                        self.gen_expr(rhs);
-                        println!("  pop rdi");
-                        println!("  pop rax");
-                        println!("  cqo");
-                        println!("  idiv rdi");
-                    }
-                    BinaryOperator::Lt => {
-                        self.gen_expr(lhs);
-                        self.gen_expr(rhs);
-                        println!("  pop rdi");
-                        println!("  pop rax");
-                        println!("  cmp rax, rdi");
-                        println!("  setl al");
-                        println!("  movzb rax, al");
-                    }
-                    BinaryOperator::LtEq => {
-                        self.gen_expr(lhs);
-                        self.gen_expr(rhs);
-                        println!("  pop rdi");
-                        println!("  pop rax");
-                        println!("  cmp rax, rdi");
-                        println!("  setle al");
-                        println!("  movzb rax, al");
-                    }
-                    BinaryOperator::Eq => {
-                        self.gen_expr(lhs);
-                        self.gen_expr(rhs);
-                        println!("  pop rdi");
-                        println!("  pop rax");
-                        println!("  cmp rax, rdi");
-                        println!("  sete al");
-                        println!("  movzb rax, al");
-                    }
-                    BinaryOperator::NotEq => {
-                        self.gen_expr(lhs);
-                        self.gen_expr(rhs);
-                        println!("  pop rdi");
-                        println!("  pop rax");
-                        println!("  cmp rax, rdi");
-                        println!("  setne al");
-                        println!("  movzb rax, al");
-                    }
-                    BinaryOperator::Assignment => {
-                        println!("  # --start assignment");
+                    BinaryOperator::Plus => println!("  add rax, rdi"),
+                    _ => println!("  sub rax, rdi"),
+                }
+            }
+        },
+        BinaryOperator::Asterisk => println!("  imul rax, rdi"),
+        BinaryOperator::Slash | BinaryOperator::Percent => {
+            if signed {
+                println!("  cqo");
+                println!("  idiv rdi");
+            } else {
+                println!("  xor edx, edx");
+                println!("  div rdi");
+            }
+            if *op == BinaryOperator::Percent {
+                println!("  mov rax, rdx");
+            }
+        }
+        BinaryOperator::LShift => {
+            println!("  mov rcx, rdi");
+            println!("  shl rax, cl");
+        }
+        BinaryOperator::RShift => {
+            println!("  mov rcx, rdi");
+            match signed {
+                true => println!("  sar rax, cl"),
+                false => println!("  shr rax, cl"),
+            }
+        }
+        BinaryOperator::BitAnd => println!("  and rax, rdi"),
+        BinaryOperator::BitOr => println!("  or rax, rdi"),
+        BinaryOperator::BitXor => println!("  xor rax, rdi"),
+        BinaryOperator::Lt | BinaryOperator::LtEq | BinaryOperator::Eq | BinaryOperator::NotEq => {
+            let set = match (op, signed) {
+                (BinaryOperator::Lt, true) => "setl",
+                (BinaryOperator::Lt, false) => "setb",
+                (BinaryOperator::LtEq, true) => "setle",
+                (BinaryOperator::LtEq, false) => "setbe",
+                (BinaryOperator::Eq, _) => "sete",
+                _ => "setne",
+            };
+            println!("  cmp rax, rdi");
+            println!("  {} al", set);
+            println!("  movzb rax, al");
+        }
+        _ => unreachable!("`{:?}` is not an arithmetic operator", op),
+    }
+}
 
-                        println!("  # --left");
-                        match lhs.as_ref() {
-                            Expression::Unary { expr, op } => match op {
-                                UnaryOperator::Dereference => {
-                                    self.gen_expr(expr);
-                                }
-                                _ => {
-                                    panic!("Invalid node: {:?}.\nleft node is not var on assignment expression.", expr);
-                                }
-                            },
-                            Expression::Index { expr, index } => {
-                                self.gen_lval(expr);
-                                self.gen_expr(index);
-                                println!("  pop rdi");
-                                println!("  pop rax");
+/// general purpose registers of the integer and pointer arguments, in order.
+pub(super) const INTEGER_REGISTERS: [&str; 6] = ["rdi", "rsi", "rdx", "rcx", "r8", "r9"];
 
-                                // calc offset
-                                let element_type = match expr.as_ref() {
-                                    Expression::LocalVariable { type_, .. } => match type_ {
-                                        Type::Array { type_, .. } => type_.as_ref(),
-                                        _ => panic!("Invalid node: {:?}.\nleft node is not var on assignment expression.", expr),
-                                    },
-                                    _ => panic!("Invalid node: {:?}.\nleft node is not var on assignment expression.", expr),
-                                };
-                                println!("  add rdi, 1");
-                                println!("  imul rdi, {}", element_type.size());
-                                println!("  add rax, rdi");
-                            }
-                            Expression::LocalVariable { .. } => {
-                                self.gen_lval(lhs);
-                            }
-                            _ => {
-                                panic!("Invalid node: {:?}.\nleft node is not var on assignment expression.", lhs);
-                            }
-                        }
+/// count of the SSE registers holding floating arguments, `xmm0` to `xmm7`.
+pub(super) const FLOATING_REGISTERS: usize = 8;
 
-                        println!("  # --right");
-                        self.gen_expr(rhs);
-                        println!("  # --assignment");
-                        println!("  pop rdi");
-                        println!("  pop rax");
-                        println!("  mov [rax], rdi");
-                        println!("  push rdi");
-                        println!("  # --end assignment");
-                    }
-                }
-                println!("  push rax");
+/// register an argument is passed in.
+pub(super) enum Register {
+    Integer(&'static str),
+    /// `xmm` register of this index.
+    Floating(usize),
+}
+
+/// registers of arguments of `types`, each class taking the next of its own registers. the
+/// index of the first argument left without a register when either class runs out.
+pub(super) fn argument_registers<'a>(
+    types: impl Iterator<Item = &'a Type>,
+) -> Result<Vec<Register>, usize> {
+    let mut integers = INTEGER_REGISTERS.into_iter();
+    let mut floating = 0..FLOATING_REGISTERS;
+    types
+        .enumerate()
+        .map(|(i, type_)| match is_floating(type_) {
+            true => floating.next().map(Register::Floating).ok_or(i),
+            false => integers.next().map(Register::Integer).ok_or(i),
+        })
+        .collect()
+}
+
+/// size of the elements a pointer to `type_` steps over, 1 for `void` and functions like GCC.
+pub(super) fn element_size(type_: &Type) -> usize {
+    type_.size().max(1)
+}
+
+/// converts the value in `rax` from `from` to `to`. integers are truncated to the size of `to`
+/// and extended back to 64 bits, `_Bool` is 1 for any value but 0, pointers keep their value.
+fn gen_convert(from: &Type, to: &Type) {
+    match (is_floating(from), is_floating(to)) {
+        (true, true) => gen_float_to_float(from, to),
+        (false, true) => gen_integer_to_float(from, to),
+        (true, false) => gen_float_to_integer(from, to),
+        (false, false) => match to.unqualified() {
+            Type::Primitive(TypeEnum::Bool) => {
+                println!("  cmp rax, 0");
+                println!("  setne al");
+                println!("  movzx eax, al");
+            }
+            to => gen_extend(to),
+        },
+    }
+}
+
+/// extends the integer of `type_` in the low bytes of `rax` to 64 bits, with its sign or with
+/// zeros. values of the other types are left as they are.
+pub(super) fn gen_extend(type_: &Type) {
+    if !is_integer(type_) {
+        return;
+    }
+    match (type_.size(), type_.is_signed()) {
+        (1, true) => println!("  movsx rax, al"),
+        (1, false) => println!("  movzx eax, al"),
+        (2, true) => println!("  movsx rax, ax"),
+        (2, false) => println!("  movzx eax, ax"),
+        (4, true) => println!("  movsxd rax, eax"),
+        (4, false) => println!("  mov eax, eax"),
+        _ => {}
+    }
+}
+
+/// `rax = *rax` for a value of `type_`, extended to 64 bits. arrays, structs and functions are
+/// used through their address, which is left as it is.
+fn gen_load(type_: &Type) {
+    match type_.unqualified() {
+        Type::Array { .. } | Type::Struct(_) | Type::Function { .. } => {}
+        type_ => match (type_.size(), type_.is_signed()) {
+            (1, true) => println!("  movsx rax, byte ptr [rax]"),
+            (1, false) => println!("  movzx eax, byte ptr [rax]"),
+            (2, true) => println!("  movsx rax, word ptr [rax]"),
+            (2, false) => println!("  movzx eax, word ptr [rax]"),
+            (4, true) => println!("  movsxd rax, dword ptr [rax]"),
+            (4, false) => println!("  mov eax, dword ptr [rax]"),
+            _ => println!("  mov rax, qword ptr [rax]"),
+        },
+    }
+}
+
+/// whether `type_` is an integer type, held in the general purpose registers.
+fn is_integer(type_: &Type) -> bool {
+    match type_.unqualified() {
+        Type::Primitive(TypeEnum::Void | TypeEnum::Float | TypeEnum::Double) => false,
+        Type::Primitive(_) => true,
+        _ => false,
+    }
+}
+
+/// `*rax = rdi` for a value of `type_`, only writing its size. the value of a struct is its
+/// address, the struct is copied from there.
+pub(super) fn gen_store(type_: &Type) {
+    match type_.unqualified() {
+        type_ @ Type::Struct(_) => {
+            for i in 0..type_.size() {
+                println!("  mov r8b, [rdi+{}]", i);
+                println!("  mov [rax+{}], r8b", i);
             }
-            _ => unreachable!(),
         }
+        type_ => println!(
+            "  mov {} ptr [rax], {}",
+            width(type_.size()),
+            register("rdi", type_.size())
+        ),
+    }
+}
+
+/// operand size keyword for a memory access of `size` bytes.
+fn width(size: usize) -> &'static str {
+    match size {
+        1 => "byte",
+        2 => "word",
+        4 => "dword",
+        _ => "qword",
     }
 }
+
+/// the low `size` bytes of the 64-bit `register`.
+pub(super) fn register(register: &str, size: usize) -> &'static str {
+    let names = match register {
+        "rdi" => ["dil", "di", "edi", "rdi"],
+        "rsi" => ["sil", "si", "esi", "rsi"],
+        "rdx" => ["dl", "dx", "edx", "rdx"],
+        "rcx" => ["cl", "cx", "ecx", "rcx"],
+        "r8" => ["r8b", "r8w", "r8d", "r8"],
+        "r9" => ["r9b", "r9w", "r9d", "r9"],
+        _ => unreachable!("no sub-registers for `{}`", register),
+    };
+    match size {
+        1 => names[0],
+        2 => names[1],
+        4 => names[2],
+        _ => names[3],
+    }
+}
+
+fn assignment_error(lhs: &TypedExpression) -> Diagnostic {
+    unsupported(format!("cannot take the address of `{}`", lhs))
+        .with_primary_label(lhs.span, "not an object")
+}
@@ -0,0 +1,196 @@
+use ast::{BinaryOperator, Type, TypeEnum};
+use helper::rand::rand;
+
+use crate::expression::gen_extend;
+use crate::CodeGenerator;
+
+/// floating constant emitted in `.rodata`, where SSE instructions can load it from.
+pub(crate) struct AsmFloatLiteral {
+    label: String,
+    value: f64,
+    type_: Type,
+}
+
+impl CodeGenerator {
+    /// label of a new floating constant holding `value` as a `type_`.
+    pub(super) fn add_float_lit(&mut self, value: f64, type_: &Type) -> String {
+        let label = format!(".LF{}", self.float_lits.len());
+        self.float_lits.push(AsmFloatLiteral {
+            label: label.clone(),
+            value,
+            type_: type_.clone(),
+        });
+        label
+    }
+
+    pub(super) fn gen_float_lits(&self) {
+        println!("  .section .rodata");
+        for lit in self.float_lits.iter() {
+            println!("  .align {}", lit.type_.align());
+            println!("{}: {}", lit.label, float_directive(lit.value, &lit.type_));
+            println!();
+        }
+    }
+}
+
+/// data directive holding the bits of `value` as a `type_`.
+pub(super) fn float_directive(value: f64, type_: &Type) -> String {
+    match type_.unqualified() {
+        Type::Primitive(TypeEnum::Float) => format!(".long {}", (value as f32).to_bits()),
+        _ => format!(".quad {}", value.to_bits()),
+    }
+}
+
+/// whether `type_` is `float` or `double`, held in the SSE registers.
+pub(super) fn is_floating(type_: &Type) -> bool {
+    matches!(
+        type_.unqualified(),
+        Type::Primitive(TypeEnum::Float | TypeEnum::Double)
+    )
+}
+
+/// suffix of the scalar SSE instructions for `type_`.
+fn suffix(type_: &Type) -> &'static str {
+    match type_.unqualified() {
+        Type::Primitive(TypeEnum::Float) => "ss",
+        _ => "sd",
+    }
+}
+
+/// `rax = xmm0` for a value of the floating `type_`. a `float` is zero extended, like the
+/// unsigned integers of its size.
+pub(super) fn gen_from_xmm(type_: &Type) {
+    match type_.unqualified() {
+        Type::Primitive(TypeEnum::Float) => println!("  movd eax, xmm0"),
+        _ => println!("  movq rax, xmm0"),
+    }
+}
+
+/// `rax = rax op rdi` for floating operands of `type_`. comparisons are false when either
+/// operand is a NaN, but for `!=`.
+pub(super) fn gen_float_arithmetic(op: &BinaryOperator, type_: &Type) {
+    let s = suffix(type_);
+    println!("  movq xmm0, rax");
+    println!("  movq xmm1, rdi");
+    match op {
+        BinaryOperator::Plus => println!("  add{s} xmm0, xmm1"),
+        BinaryOperator::Minus => println!("  sub{s} xmm0, xmm1"),
+        BinaryOperator::Asterisk => println!("  mul{s} xmm0, xmm1"),
+        BinaryOperator::Slash => println!("  div{s} xmm0, xmm1"),
+        // `ucomis` sets the carry and the parity flag for unordered operands, `rhs` is compared
+        // with `lhs` so that `seta` and `setae` leave 0 for them.
+        BinaryOperator::Lt | BinaryOperator::LtEq => {
+            println!("  ucomi{s} xmm1, xmm0");
+            match op {
+                BinaryOperator::Lt => println!("  seta al"),
+                _ => println!("  setae al"),
+            }
+            println!("  movzx eax, al");
+            return;
+        }
+        BinaryOperator::Eq | BinaryOperator::NotEq => {
+            println!("  ucomi{s} xmm0, xmm1");
+            match op {
+                BinaryOperator::Eq => {
+                    println!("  sete al");
+                    println!("  setnp dl");
+                    println!("  and al, dl");
+                }
+                _ => {
+                    println!("  setne al");
+                    println!("  setp dl");
+                    println!("  or al, dl");
+                }
+            }
+            println!("  movzx eax, al");
+            return;
+        }
+        _ => unreachable!("`{:?}` is not a floating operator", op),
+    }
+    gen_from_xmm(type_);
+}
+
+/// `rax = -rax` for a value of the floating `type_`, flipping its sign bit.
+pub(super) fn gen_float_negate(type_: &Type) {
+    match type_.unqualified() {
+        Type::Primitive(TypeEnum::Float) => println!("  btc eax, 31"),
+        _ => println!("  btc rax, 63"),
+    }
+}
+
+/// converts the floating value in `rax` from `from` to the floating `to`.
+pub(super) fn gen_float_to_float(from: &Type, to: &Type) {
+    if suffix(from) == suffix(to) {
+        return;
+    }
+    println!("  movq xmm0, rax");
+    println!("  cvt{}2{} xmm0, xmm0", suffix(from), suffix(to));
+    gen_from_xmm(to);
+}
+
+/// converts the integer in `rax`, of type `from`, to the floating `to`.
+pub(super) fn gen_integer_to_float(from: &Type, to: &Type) {
+    let s = suffix(to);
+    if from.size() < 8 || from.is_signed() {
+        println!("  cvtsi2{s} xmm0, rax");
+        gen_from_xmm(to);
+        return;
+    }
+    // the conversion is signed: from 2^63 on, half the value is converted and doubled. its
+    // lowest bit is kept, so that it is still rounded like the whole value.
+    let label_big = format!(".Lbig{}", rand());
+    let label_end = format!(".Lend{}", rand());
+    println!("  test rax, rax");
+    println!("  js {label_big}");
+    println!("  cvtsi2{s} xmm0, rax");
+    println!("  jmp {label_end}");
+    println!("{label_big}:");
+    println!("  mov rdi, rax");
+    println!("  and edi, 1");
+    println!("  shr rax, 1");
+    println!("  or rax, rdi");
+    println!("  cvtsi2{s} xmm0, rax");
+    println!("  add{s} xmm0, xmm0");
+    println!("{label_end}:");
+    gen_from_xmm(to);
+}
+
+/// converts the floating value in `rax`, of type `from`, to the integer `to`, truncating it
+/// toward zero. `_Bool` is 1 for any value but zeros, NaNs included. the result is extended
+/// like `gen_extend` does.
+pub(super) fn gen_float_to_integer(from: &Type, to: &Type) {
+    println!("  movq xmm0, rax");
+    // every `float` is exactly a `double`.
+    if suffix(from) == "ss" {
+        println!("  cvtss2sd xmm0, xmm0");
+    }
+    if let Type::Primitive(TypeEnum::Bool) = to.unqualified() {
+        println!("  xorpd xmm1, xmm1");
+        println!("  ucomisd xmm0, xmm1");
+        println!("  setne al");
+        println!("  setp dl");
+        println!("  or al, dl");
+        println!("  movzx eax, al");
+        return;
+    }
+    if to.size() < 8 || to.is_signed() {
+        println!("  cvttsd2si rax, xmm0");
+        gen_extend(to);
+        return;
+    }
+    // the conversion is signed: from 2^63 on, the value is converted less 2^63, which is added
+    // back by setting the top bit.
+    let label_big = format!(".Lbig{}", rand());
+    let label_end = format!(".Lend{}", rand());
+    println!("  mov rax, {:#x}", ((1u64 << 63) as f64).to_bits());
+    println!("  movq xmm1, rax");
+    println!("  ucomisd xmm0, xmm1");
+    println!("  jae {label_big}");
+    println!("  cvttsd2si rax, xmm0");
+    println!("  jmp {label_end}");
+    println!("{label_big}:");
+    println!("  subsd xmm0, xmm1");
+    println!("  cvttsd2si rax, xmm0");
+    println!("  btc rax, 63");
+    println!("{label_end}:");
+}
@@ -1,46 +1,114 @@
-use ast::{Expression, Statement};
+use ast::Type;
+use diagnostic::Diagnostic;
+use sema::{ExpressionKind, TypedExpression};
 
-use crate::CodeGenerator;
+use crate::expression::{
+    argument_registers, register, Register, FLOATING_REGISTERS, INTEGER_REGISTERS,
+};
+use crate::float::is_floating;
+use crate::{unsupported, CodeGenerator, Statement};
 
 impl CodeGenerator {
     pub(super) fn gen_function_definition(
         &mut self,
         name: &String,
-        arguments: &Vec<Expression>, // Expression::LocalVariable
-        body: &Vec<Statement>,
-    ) {
+        return_type: &Type,
+        arguments: &Vec<TypedExpression>, // ExpressionKind::LocalVariable
+        body: &[Statement],
+        stack_size: usize,
+        static_: bool,
+    ) -> Result<(), Diagnostic> {
+        if let Type::Struct(_) = return_type.unqualified() {
+            return Err(unsupported(format!(
+                "returning `{}` from `{}` is not supported yet",
+                return_type, name
+            )));
+        }
         if name != "main" {
             println!("# ====== function definition ======");
         }
+        if !static_ {
+            println!("  .global {}", name);
+        }
         println!("{}:", name);
         println!("  # prologue");
         println!("  push rbp");
         println!("  mov rbp, rsp");
+        self.stack_size = stack_size;
+        self.function = name.clone();
+        if stack_size > 0 {
+            println!("  sub rsp, {}", stack_size);
+        }
         println!("  # arguments");
-        let registers = ["rdi", "rsi", "rdx", "rcx", "r8d", "r9d"];
-        for (i, arg) in arguments.iter().enumerate() {
-            let offset = match arg {
-                Expression::LocalVariable { offset, .. } => offset,
-                _ => panic!("invalid argument"),
+        let registers =
+            argument_registers(arguments.iter().map(|arg| &arg.type_)).map_err(|i| {
+                unsupported(format!(
+                    "`{}` takes {} parameters, at most {} integer and {} floating ones are \
+                     supported",
+                    name,
+                    arguments.len(),
+                    INTEGER_REGISTERS.len(),
+                    FLOATING_REGISTERS
+                ))
+                .with_primary_label(arguments[i].span, "no register left for this")
+            })?;
+        for (arg, destination) in arguments.iter().zip(registers) {
+            let offset = match &arg.kind {
+                ExpressionKind::LocalVariable { .. } if matches!(arg.type_, Type::Struct(_)) => {
+                    return Err(unsupported(format!(
+                        "parameter `{}` of type `{}` is not supported yet",
+                        arg, arg.type_
+                    ))
+                    .with_primary_label(arg.span, "passed by value"))
+                }
+                ExpressionKind::LocalVariable { offset, .. } => offset,
+                _ => {
+                    return Err(unsupported(format!("invalid parameter `{}`", arg))
+                        .with_primary_label(arg.span, "not a parameter"))
+                }
             };
-            println!("  mov [rbp-{}], {}", offset, registers[i]);
+            let address = self.local_address(*offset);
+            match (destination, arg.type_.size()) {
+                (Register::Integer(name), size) => {
+                    println!("  mov [rbp-{}], {}", address, register(name, size))
+                }
+                (Register::Floating(i), 4) => println!("  movss [rbp-{}], xmm{}", address, i),
+                (Register::Floating(i), _) => println!("  movsd [rbp-{}], xmm{}", address, i),
+            }
         }
-        if arguments.len() == 0 {
+        if arguments.is_empty() {
             println!("    # --");
         }
 
         println!("  # body");
-        self.gen_stmts(body);
+        self.gen_stmts(body)?;
+        // the end of the body returns too, with 0 from `main`.
+        if name == "main" {
+            println!("  mov rax, 0");
+        }
+        gen_epilogue();
+        Ok(())
     }
 
-    pub(super) fn gen_return(&mut self, node: &Expression) {
+    pub(super) fn gen_return(&mut self, node: Option<&TypedExpression>) -> Result<(), Diagnostic> {
         println!("  # -- return");
-        self.gen_expr(node);
-        println!("  # epilogue");
-        println!("  pop rax");
-        println!("  mov rsp, rbp");
-        println!("  pop rbp");
-        println!("  ret");
-        println!("");
+        if let Some(node) = node {
+            self.gen_expr(node)?;
+            println!("  pop rax");
+            if is_floating(&node.type_) {
+                println!("  movq xmm0, rax");
+            }
+        }
+        gen_epilogue();
+        Ok(())
     }
 }
+
+/// restores the frame of the caller and returns to it.
+fn gen_epilogue() {
+    println!("  # epilogue");
+    println!("  mov rsp, rbp");
+    println!("  pop rbp");
+    println!("  ret");
+    println!();
+}
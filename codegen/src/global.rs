@@ -0,0 +1,292 @@
+use ast::{fold, fold_integer, BinaryOperator, Type, TypeEnum, UnaryOperator, Value};
+use diagnostic::Diagnostic;
+use sema::{ExpressionKind, TypedExpression};
+
+use crate::expression::element_size;
+use crate::float::float_directive;
+use crate::variable::escape;
+use crate::CodeGenerator;
+
+/// variable with static storage, emitted after the functions.
+pub(crate) struct Global {
+    name: String,
+    type_: Type,
+    init: Option<TypedExpression>,
+    /// declared `static`, not visible outside the file.
+    static_: bool,
+}
+
+/// value of a constant expression, known when assembling or linking.
+enum Constant {
+    Integer(i64),
+    /// value of a `float` or a `double`, rounded to its type.
+    Float(f64),
+    /// `label + offset`, e.g. `&a[2]` or a string literal.
+    Address {
+        label: String,
+        offset: i64,
+    },
+}
+
+fn constant_error(message: String) -> Diagnostic {
+    Diagnostic::error(message).with_code("E0301")
+}
+
+fn not_constant(expr: &TypedExpression) -> Diagnostic {
+    constant_error(format!(
+        "initializer element `{}` is not a compile-time constant",
+        expr
+    ))
+    .with_primary_label(expr.span, "not a constant")
+}
+
+impl CodeGenerator {
+    /// records a file scope declaration. tentative ones merge with the declaration holding the
+    /// initializer.
+    pub(super) fn add_global(
+        &mut self,
+        name: &str,
+        type_: &Type,
+        init: &Option<TypedExpression>,
+        static_: bool,
+    ) {
+        match self.globals.iter_mut().find(|g| g.name == name) {
+            Some(global) => {
+                if init.is_some() {
+                    global.init = init.clone();
+                }
+            }
+            None => self.globals.push(Global {
+                name: name.to_string(),
+                type_: type_.clone(),
+                init: init.clone(),
+                static_,
+            }),
+        }
+    }
+
+    /// initialized globals go to `.data`, the others are zero filled in `.bss`.
+    pub(super) fn gen_globals(&mut self) -> Result<(), Diagnostic> {
+        for global in std::mem::take(&mut self.globals) {
+            let symbol = |section: &str| {
+                println!("  {}", section);
+                if !global.static_ {
+                    println!("  .global {}", global.name);
+                }
+                println!("  .align {}", global.type_.align());
+                println!("{}:", global.name);
+            };
+            match global.init {
+                Some(ref init) => {
+                    symbol(".data");
+                    self.gen_initializer(init, &global.type_)?;
+                }
+                None => {
+                    symbol(".bss");
+                    println!("  .zero {}", global.type_.size());
+                }
+            }
+            println!();
+        }
+        Ok(())
+    }
+
+    /// data directives for `init` as a value of `type_`.
+    fn gen_initializer(&mut self, init: &TypedExpression, type_: &Type) -> Result<(), Diagnostic> {
+        match (type_.unqualified(), &init.kind) {
+            (Type::Array { type_, size }, ExpressionKind::Array { elements }) => {
+                let size = *size as usize;
+                if elements.len() > size {
+                    return Err(constant_error(format!(
+                        "excess elements in initializer of `{}`",
+                        Type::Array {
+                            type_: type_.clone(),
+                            size: size as i32,
+                        }
+                    ))
+                    .with_primary_label(elements[size].span, "excess element"));
+                }
+                for element in elements {
+                    self.gen_initializer(element, type_)?;
+                }
+                gen_zero((size - elements.len()) * type_.size());
+            }
+            (Type::Array { type_, size }, ExpressionKind::String(bytes))
+                if *type_.unqualified() == Type::Primitive(TypeEnum::Char) =>
+            {
+                // the terminating null is dropped when the array has no room for it.
+                let size = *size as usize;
+                if bytes.len() > size {
+                    return Err(constant_error(format!(
+                        "initializer string is too long for `char[{}]`",
+                        size
+                    ))
+                    .with_primary_label(init.span, "too long"));
+                }
+                println!("  .ascii \"{}\"", escape(bytes));
+                gen_zero(size - bytes.len());
+            }
+            (Type::Struct(struct_), ExpressionKind::Array { elements }) => {
+                // a union is initialized through its first member.
+                let members = struct_.members.as_deref().unwrap_or_default();
+                let count = if struct_.union { 1 } else { members.len() };
+                if elements.len() > count {
+                    return Err(constant_error(format!(
+                        "excess elements in initializer of `{}`",
+                        type_
+                    ))
+                    .with_primary_label(elements[count].span, "excess element"));
+                }
+                let mut end = 0;
+                for (member, element) in members.iter().zip(elements) {
+                    gen_zero(member.offset - end);
+                    self.gen_initializer(element, &member.type_)?;
+                    end = member.offset + member.type_.size();
+                }
+                gen_zero(type_.size() - end);
+            }
+            (Type::Array { .. } | Type::Struct(_), _) => {
+                return Err(constant_error(format!(
+                    "cannot initialize a variable of type `{}` with `{}`",
+                    type_, init
+                ))
+                .with_primary_label(init.span, format!("expected `{}`", type_)))
+            }
+            _ => {
+                let directive = match type_.size() {
+                    1 => ".byte",
+                    2 => ".short",
+                    4 => ".long",
+                    _ => ".quad",
+                };
+                match self.eval_constant(init)? {
+                    Constant::Integer(value) => println!("  {} {}", directive, value),
+                    Constant::Float(value) => println!("  {}", float_directive(value, type_)),
+                    Constant::Address { label, offset } if type_.size() == 8 => {
+                        println!("  .quad {}{:+}", label, offset)
+                    }
+                    Constant::Address { .. } => return Err(not_constant(init)),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn eval_constant(&mut self, expr: &TypedExpression) -> Result<Constant, Diagnostic> {
+        // arithmetic constants fold like the ones of the parser and of sema, the addresses are
+        // left to the assembler.
+        match fold(expr) {
+            Some(Value::Integer(value)) => return Ok(Constant::Integer(value)),
+            Some(Value::Float(value)) => return Ok(Constant::Float(value)),
+            None => {}
+        }
+        let constant = match &expr.kind {
+            ExpressionKind::Unary {
+                expr: operand,
+                op: UnaryOperator::Reference,
+            } => self.eval_address(operand)?,
+            ExpressionKind::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => match fold(condition.as_ref()) {
+                Some(Value::Integer(0)) => self.eval_constant(otherwise)?,
+                Some(Value::Integer(_)) => self.eval_constant(then)?,
+                _ => return Err(not_constant(expr)),
+            },
+            // arrays and functions are used through their address.
+            ExpressionKind::Cast { expr: operand, .. }
+                if matches!(operand.type_, Type::Array { .. } | Type::Function { .. }) =>
+            {
+                self.eval_address(operand)?
+            }
+            // an address only fits in 8 bytes.
+            ExpressionKind::Cast { expr: operand, .. } if expr.type_.size() == 8 => {
+                match self.eval_constant(operand)? {
+                    address @ Constant::Address { .. } => address,
+                    _ => return Err(not_constant(expr)),
+                }
+            }
+            ExpressionKind::Binary { op, rhs, .. }
+                if matches!(op, BinaryOperator::Slash | BinaryOperator::Percent)
+                    && fold_integer(rhs.as_ref()) == Some(0) =>
+            {
+                return Err(constant_error(format!(
+                    "division by zero in constant expression `{}`",
+                    expr
+                ))
+                .with_primary_label(rhs.span, "division by zero"));
+            }
+            ExpressionKind::Binary { lhs, op, rhs }
+                if matches!(op, BinaryOperator::Plus | BinaryOperator::Minus) =>
+            {
+                match (self.eval_constant(lhs)?, fold_integer(rhs.as_ref())) {
+                    (Constant::Address { label, offset }, Some(n)) => {
+                        let n = match &lhs.type_ {
+                            Type::Pointer(pointee) => n * element_size(pointee) as i64,
+                            _ => n,
+                        };
+                        let offset = match op {
+                            BinaryOperator::Plus => offset + n,
+                            _ => offset - n,
+                        };
+                        Constant::Address { label, offset }
+                    }
+                    _ => return Err(not_constant(expr)),
+                }
+            }
+            _ => return Err(not_constant(expr)),
+        };
+        Ok(constant)
+    }
+
+    /// address of a global, of a string literal, or of an element or a member of a global.
+    fn eval_address(&mut self, expr: &TypedExpression) -> Result<Constant, Diagnostic> {
+        match &expr.kind {
+            ExpressionKind::GlobalVariable { name } | ExpressionKind::Function { name } => {
+                Ok(Constant::Address {
+                    label: name.clone(),
+                    offset: 0,
+                })
+            }
+            ExpressionKind::String(bytes) => Ok(Constant::Address {
+                label: self.add_str_lit(bytes),
+                offset: 0,
+            }),
+            ExpressionKind::Index { expr: array, index } => {
+                match (self.eval_constant(array)?, self.eval_constant(index)?) {
+                    (Constant::Address { label, offset }, Constant::Integer(index)) => {
+                        Ok(Constant::Address {
+                            label,
+                            offset: offset + index * expr.type_.size() as i64,
+                        })
+                    }
+                    _ => Err(not_constant(expr)),
+                }
+            }
+            ExpressionKind::Member {
+                expr: base, offset, ..
+            } => match self.eval_address(base)? {
+                Constant::Address {
+                    label,
+                    offset: start,
+                } => Ok(Constant::Address {
+                    label,
+                    offset: start + *offset as i64,
+                }),
+                Constant::Integer(_) | Constant::Float(_) => Err(not_constant(expr)),
+            },
+            ExpressionKind::Unary {
+                expr,
+                op: UnaryOperator::Dereference,
+            } => self.eval_constant(expr),
+            _ => Err(not_constant(expr)),
+        }
+    }
+}
+
+fn gen_zero(size: usize) {
+    if size > 0 {
+        println!("  .zero {}", size);
+    }
+}
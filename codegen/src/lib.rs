@@ -1,57 +1,91 @@
-use ast::{Program, Statement};
+use ast::Program;
+use diagnostic::Diagnostic;
+use float::AsmFloatLiteral;
+use global::Global;
+use sema::TypedExpression;
 
 mod branch;
 mod expression;
+mod float;
 mod function;
+mod global;
 mod loop_;
 mod variable;
 
+/// statement whose expressions are checked.
+type Statement = ast::Statement<TypedExpression>;
+
 // entry
-pub fn codegen(ast: Program) {
+pub fn codegen(ast: Program<TypedExpression>) -> Result<(), Diagnostic> {
     let mut generator = CodeGenerator::new(ast);
-    generator.codegen();
+    generator.codegen()
+}
+
+/// error for a construct the code generator cannot lower.
+fn unsupported(message: String) -> Diagnostic {
+    Diagnostic::error(message).with_code("E0300")
 }
 
 struct AsmStringLiteral {
     label: String,
-    value: String,
+    value: Vec<u8>,
 }
 
 struct CodeGenerator {
-    ast: Program,
+    ast: Program<TypedExpression>,
     str_lits: Vec<AsmStringLiteral>,
+    float_lits: Vec<AsmFloatLiteral>,
+    globals: Vec<Global>,
+    /// frame size of the function being generated.
+    stack_size: usize,
+    /// name of the function being generated.
+    function: String,
+    /// where `break` and `continue` jump to, innermost last.
+    break_labels: Vec<String>,
+    continue_labels: Vec<String>,
+    /// labels of the switches around the statement being generated, innermost last.
+    switch_labels: Vec<String>,
 }
 
 impl CodeGenerator {
-    fn new(ast: Program) -> Self {
+    fn new(ast: Program<TypedExpression>) -> Self {
         Self {
             ast,
             str_lits: vec![],
+            float_lits: vec![],
+            globals: vec![],
+            stack_size: 0,
+            function: String::new(),
+            break_labels: vec![],
+            continue_labels: vec![],
+            switch_labels: vec![],
         }
     }
 }
 
 impl CodeGenerator {
-    fn codegen(&mut self) {
+    fn codegen(&mut self) -> Result<(), Diagnostic> {
         println!("  .intel_syntax noprefix");
-        println!("  .global main");
-        println!("");
+        println!();
         println!("  .text");
         for stmt in self.ast.statements.clone().iter() {
-            self.gen_stmt(stmt);
+            self.gen_stmt(stmt)?;
         }
 
-        println!("  .data");
+        self.gen_globals()?;
         self.gen_str_lits();
+        self.gen_float_lits();
+        Ok(())
     }
 
-    fn gen_stmts(&mut self, stmts: &[Statement]) {
+    fn gen_stmts(&mut self, stmts: &[Statement]) -> Result<(), Diagnostic> {
         for stmt in stmts.iter() {
-            self.gen_stmt(stmt);
+            self.gen_stmt(stmt)?;
         }
+        Ok(())
     }
 
-    fn gen_stmt(&mut self, node: &Statement) {
+    fn gen_stmt(&mut self, node: &Statement) -> Result<(), Diagnostic> {
         match node {
             Statement::If {
                 condition,
@@ -65,20 +99,55 @@ impl CodeGenerator {
                 post,
                 body,
             } => self.gen_for(init, condition, post, body),
+            Statement::DoWhile { body, condition } => self.gen_do_while(body, condition),
+            Statement::Switch {
+                condition,
+                body,
+                cases,
+                default,
+            } => self.gen_switch(condition, body, cases, *default),
+            Statement::Case { value, body } => self.gen_case(*value, body),
+            Statement::Break => self.gen_break(),
+            Statement::Continue => self.gen_continue(),
+            Statement::Label { name, body } => self.gen_label(name, body),
+            Statement::Goto(name) => {
+                self.gen_goto(name);
+                Ok(())
+            }
             Statement::Block(stmts) => self.gen_stmts(stmts),
             Statement::Expression(expr) => self.gen_expr(expr),
-            Statement::Return(expr) => self.gen_return(expr),
+            Statement::Return(expr) => self.gen_return(expr.as_ref()),
             Statement::FunctionDefinition {
                 name,
+                return_type,
                 arguments,
                 body,
-            } => self.gen_function_definition(name, arguments, body),
+                stack_size,
+                static_,
+            } => self.gen_function_definition(
+                name,
+                return_type,
+                arguments,
+                body,
+                *stack_size,
+                *static_,
+            ),
             Statement::InitDeclaration {
                 name,
                 offset,
                 type_,
                 init,
             } => self.gen_init_declaration(name, offset, type_, init),
+            Statement::GlobalDeclaration {
+                name,
+                type_,
+                init,
+                static_,
+            } => {
+                self.add_global(name, type_, init, *static_);
+                Ok(())
+            }
+            Statement::Declaration { .. } => Ok(()),
         }
     }
 }
@@ -1,23 +1,25 @@
-use ast::{Expression, Statement};
+use diagnostic::Diagnostic;
 use helper::rand::rand;
+use sema::TypedExpression;
 
-use crate::CodeGenerator;
+use crate::{unsupported, CodeGenerator, Statement};
 
 impl CodeGenerator {
     pub(super) fn gen_for(
         &mut self,
         init: &Option<Box<Statement>>,
-        condition: &Option<Expression>,
+        condition: &Option<TypedExpression>,
         post: &Option<Box<Statement>>,
-        body: &Box<Statement>,
-    ) {
+        body: &Statement,
+    ) -> Result<(), Diagnostic> {
         println!("# -- start for");
         let label_begin = format!(".Lbegin{}", rand());
+        let label_continue = format!(".Lcontinue{}", rand());
         let label_end = format!(".Lend{}", rand());
 
         // init
         match init {
-            Some(init) => self.gen_stmt(init),
+            Some(init) => self.gen_stmt(init)?,
             None => {}
         }
         println!("{label_begin}:");
@@ -25,7 +27,7 @@ impl CodeGenerator {
         // condition and jump
         match condition {
             Some(ref condition) => {
-                self.gen_expr(condition);
+                self.gen_expr(condition)?;
                 println!("  pop rax");
                 println!("  cmp rax, 0");
                 println!("  je {label_end}");
@@ -34,18 +36,74 @@ impl CodeGenerator {
         }
 
         // body
-        self.gen_stmt(body);
+        self.gen_loop_body(body, &label_end, &label_continue)?;
+        println!("{label_continue}:");
 
         // update
         match post {
-            Some(update) => self.gen_stmt(update),
+            Some(update) => self.gen_stmt(update)?,
             None => {}
         }
 
         println!("  jmp {label_begin}");
         println!("{label_end}:");
         println!("# -- end for");
+        Ok(())
     }
 
-    // TODO: while
+    pub(super) fn gen_do_while(
+        &mut self,
+        body: &Statement,
+        condition: &TypedExpression,
+    ) -> Result<(), Diagnostic> {
+        println!("# -- start do while");
+        let label_begin = format!(".Lbegin{}", rand());
+        let label_continue = format!(".Lcontinue{}", rand());
+        let label_end = format!(".Lend{}", rand());
+        println!("{label_begin}:");
+        self.gen_loop_body(body, &label_end, &label_continue)?;
+        println!("{label_continue}:");
+        self.gen_expr(condition)?;
+        println!("  pop rax");
+        println!("  cmp rax, 0");
+        println!("  jne {label_begin}");
+        println!("{label_end}:");
+        println!("# -- end do while");
+        Ok(())
+    }
+
+    /// body of a loop, where `break` jumps to `label_break` and `continue` to `label_continue`.
+    pub(super) fn gen_loop_body(
+        &mut self,
+        body: &Statement,
+        label_break: &str,
+        label_continue: &str,
+    ) -> Result<(), Diagnostic> {
+        self.break_labels.push(label_break.to_string());
+        self.continue_labels.push(label_continue.to_string());
+        let result = self.gen_stmt(body);
+        self.break_labels.pop();
+        self.continue_labels.pop();
+        result
+    }
+
+    pub(super) fn gen_break(&self) -> Result<(), Diagnostic> {
+        match self.break_labels.last() {
+            Some(label) => println!("  jmp {label}"),
+            None => {
+                return Err(unsupported(String::from(
+                    "`break` outside of a loop or switch",
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn gen_continue(&self) -> Result<(), Diagnostic> {
+        match self.continue_labels.last() {
+            Some(label) => println!("  jmp {label}"),
+            None => return Err(unsupported(String::from("`continue` outside of a loop"))),
+        }
+        Ok(())
+    }
 }
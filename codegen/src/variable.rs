@@ -1,6 +1,9 @@
-use ast::{Expression, Type, TypeEnum};
+use ast::{Type, TypeEnum};
+use diagnostic::Diagnostic;
+use sema::{ExpressionKind, TypedExpression};
 
-use crate::{AsmStringLiteral, CodeGenerator};
+use crate::expression::gen_store;
+use crate::{unsupported, AsmStringLiteral, CodeGenerator};
 
 impl CodeGenerator {
     pub(super) fn gen_init_declaration(
@@ -8,126 +11,156 @@ impl CodeGenerator {
         name: &String,
         offset: &usize,
         type_: &Type,
-        init: &Option<Expression>,
-    ) {
+        init: &Option<TypedExpression>,
+    ) -> Result<(), Diagnostic> {
         println!("  # -- init declaration {}", name);
-        self.gen_init_lval(*offset);
         match init {
-            Some(ref init) => self.gen_init_expr(init, offset, type_),
-            None => {}
+            Some(
+                init @ TypedExpression {
+                    kind: ExpressionKind::Array { .. } | ExpressionKind::String(_),
+                    ..
+                },
+            ) => {
+                // what the initializer list or the string leaves out is zero.
+                println!("  lea rdi, [rbp-{}]", self.local_address(*offset));
+                println!("  mov rcx, {}", type_.size());
+                println!("  xor eax, eax");
+                println!("  rep stosb");
+                self.gen_init_expr(init, *offset, type_)
+            }
+            Some(init) => self.gen_init_expr(init, *offset, type_),
+            None => Ok(()),
         }
     }
 
-    pub(super) fn gen_lval(&self, node: &Expression) {
-        match node {
-            Expression::LocalVariable { offset, type_, .. } => {
-                match type_ {
-                    // cast to pointer
-                    Type::Array { type_, size, .. } => {
-                        println!("  mov rax, rbp");
-                        println!(
-                            "  sub rax, {}",
-                            (*offset) - (*size as usize - 1) * (type_.size())
-                        );
-                        println!("  push rax");
-                    }
-                    _ => {
-                        println!("  mov rax, rbp");
-                        println!("  sub rax, {offset}");
-                        println!("  push rax");
-                    }
-                }
+    pub(super) fn gen_lval(&self, node: &TypedExpression) -> Result<(), Diagnostic> {
+        match &node.kind {
+            // arrays are used through a pointer to their first element.
+            ExpressionKind::LocalVariable { offset, .. } => self.gen_init_lval(*offset),
+            ExpressionKind::GlobalVariable { name } => {
+                println!("  lea rax, {}[rip]", name);
+                println!("  push rax");
             }
             _ => {
-                panic!(
-                    "Invalid node: {:?}.\nleft node is not var on assignment expression.",
-                    node
-                );
+                return Err(
+                    unsupported(format!("`{}` is not an assignable variable", node))
+                        .with_primary_label(node.span, "not a variable"),
+                )
             }
         }
+        Ok(())
     }
 
+    /// pushes the address of the local at `offset` from the bottom of the frame.
     pub(super) fn gen_init_lval(&self, offset: usize) {
-        println!("  mov rax, rbp");
-        println!("  sub rax, {offset}");
+        println!("  lea rax, [rbp-{}]", self.local_address(offset));
         println!("  push rax");
     }
 
-    pub(super) fn gen_init_expr(&mut self, expr: &Expression, offset: &usize, type_: &Type) {
-        match expr {
-            Expression::Array { elements, .. } => {
-                println!("  # -- init array start");
-                let element_type = match type_ {
-                    Type::Array { type_, .. } => type_,
-                    _ => panic!("Invalid type: {:?}.", type_),
-                };
+    /// distance below `rbp` of the local at `offset` from the bottom of the frame.
+    pub(super) fn local_address(&self, offset: usize) -> usize {
+        self.stack_size - offset
+    }
 
-                for (i, element) in elements.iter().enumerate() {
-                    self.gen_init_lval(
-                        ((*offset) - (type_.size() as usize)) + (i + 1) * element_type.size(),
-                    );
-                    self.gen_expr(element);
-                    println!("  pop rdi");
-                    println!("  pop rax");
-                    println!("  mov [rax], rdi");
-                    println!("  push rdi");
+    /// initializes the local of `type_` at `offset` from the bottom of the frame with `expr`.
+    pub(super) fn gen_init_expr(
+        &mut self,
+        expr: &TypedExpression,
+        offset: usize,
+        type_: &Type,
+    ) -> Result<(), Diagnostic> {
+        match &expr.kind {
+            ExpressionKind::Array { elements } => match type_.unqualified() {
+                Type::Array { type_, .. } => {
+                    for (i, element) in elements.iter().enumerate() {
+                        self.gen_init_expr(element, offset + i * type_.size(), type_)?;
+                    }
                 }
-                println!("  # -- init array end");
-            }
-            Expression::String(string) => {
-                println!("  # -- init string start");
-                match type_ {
-                    Type::Array { type_, .. } => match type_.as_ref() {
-                        Type::Primitive(t) => match t {
-                            TypeEnum::Char => {
-                                // TODO:
-                                // let asciis = string
-                                //     .chars()
-                                //     .map(|c| format!("0{:b}", c as u8))
-                                //     .collect::<Vec<String>>();
-                                // let reversed = asciis.into_iter().rev().collect::<Vec<String>>();
-                                // let i = isize::from_str_radix(&reversed.join(""), 2).unwrap();
-                                // self.gen_init_lval(*offset + 1);
-                                // println!("  pop rax");
-                                // println!("  mov [rax], {i}");
-                            }
-                            _ => panic!("Invalid type: {:?}.", type_),
-                        },
-                        _ => panic!("Invalid type: {:?}.", type_),
-                    },
-                    Type::Pointer(t) => match t.as_ref() {
-                        Type::Primitive(t) => match t {
-                            TypeEnum::Char => {
-                                let label = format!(".LC{}", self.str_lits.len());
-                                self.str_lits.push(AsmStringLiteral {
-                                    label: label.clone(),
-                                    value: string.clone(),
-                                });
-                                self.gen_init_lval(*offset);
-                                println!("  pop rax");
-                                println!("  mov qword ptr [rax], offset flat:{label}");
-                            }
-                            _ => panic!("Invalid type: {:?}.", type_),
-                        },
-                        _ => panic!("Invalid type: {:?}.", type_),
-                    },
-                    _ => panic!("Invalid type: {:?}.", type_),
+                Type::Struct(struct_) => {
+                    // a union is initialized through its first member.
+                    let members = struct_.members.as_deref().unwrap_or_default();
+                    let count = if struct_.union { 1 } else { members.len() };
+                    if elements.len() > count {
+                        return Err(unsupported(format!(
+                            "excess elements in initializer of `{}`",
+                            type_
+                        ))
+                        .with_primary_label(elements[count].span, "excess element"));
+                    }
+                    for (member, element) in members.iter().zip(elements) {
+                        self.gen_init_expr(element, offset + member.offset, &member.type_)?;
+                    }
                 }
-            }
+                _ => return Err(initializer_error(expr, type_)),
+            },
+            ExpressionKind::String(bytes) => match type_.unqualified() {
+                Type::Array {
+                    type_: element,
+                    size,
+                } if *element.unqualified() == Type::Primitive(TypeEnum::Char) => {
+                    // the array is zero filled already, which gives the terminating null if
+                    // there is room for it.
+                    if bytes.len() > *size as usize {
+                        return Err(unsupported(format!(
+                            "initializer string is too long for `{}`",
+                            type_
+                        ))
+                        .with_primary_label(expr.span, "too long"));
+                    }
+                    for (i, byte) in bytes.iter().enumerate() {
+                        let address = self.local_address(offset + i);
+                        println!("  mov BYTE PTR [rbp-{}], {}", address, byte);
+                    }
+                }
+                _ => return Err(initializer_error(expr, type_)),
+            },
             _ => {
-                self.gen_expr(expr);
+                self.gen_init_lval(offset);
+                self.gen_expr(expr)?;
                 println!("  pop rdi");
                 println!("  pop rax");
-                println!("  mov [rax], rdi");
-                println!("  push rdi");
+                gen_store(type_);
             }
         }
+        Ok(())
+    }
+
+    /// label of a new string literal holding `value`.
+    pub(super) fn add_str_lit(&mut self, value: &[u8]) -> String {
+        let label = format!(".LC{}", self.str_lits.len());
+        self.str_lits.push(AsmStringLiteral {
+            label: label.clone(),
+            value: value.to_vec(),
+        });
+        label
     }
 
     pub(super) fn gen_str_lits(&self) {
+        println!("  .section .rodata");
         for lit in self.str_lits.iter() {
-            println!("{}: .string \"{}\"", lit.label, lit.value);
-            println!("");
+            println!("{}: .string \"{}\"", lit.label, escape(&lit.value));
+            println!();
         }
     }
 }
+
+/// `bytes` written as the contents of an assembler string, anything but printable ASCII as an
+/// octal escape.
+pub(super) fn escape(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| match b {
+            b'"' | b'\\' => format!("\\{}", *b as char),
+            b' '..=b'~' => (*b as char).to_string(),
+            _ => format!("\\{:03o}", b),
+        })
+        .collect()
+}
+
+fn initializer_error(init: &TypedExpression, type_: &Type) -> Diagnostic {
+    unsupported(format!(
+        "cannot initialize a variable of type `{}` with `{}`",
+        type_, init
+    ))
+    .with_primary_label(init.span, format!("expected `{}`", type_))
+}
@@ -1,17 +1,104 @@
-fn main() -> Result<(), String> {
-    let argv = std::env::args().collect::<Vec<_>>();
-    if argv.len() != 2 {
-        panic!("Invalid number of arguments");
+use diagnostic::Diagnostic;
+use parse::ParseOptions;
+use preprocess::PreprocessOptions;
+use span::SourceMap;
+
+const USAGE: &str = "usage: ubcc [-E] [-I <dir>] [-ferror-limit=N] <file>";
+
+fn main() {
+    let mut source_map = SourceMap::new();
+    if let Err(diagnostics) = compile(&mut source_map) {
+        emit(&diagnostics, &source_map);
+        std::process::exit(1);
     }
+}
+
+struct Args {
+    file_path: String,
+    /// `-E`, stop after preprocessing and print the result.
+    preprocess_only: bool,
+    preprocess_options: PreprocessOptions,
+    parse_options: ParseOptions,
+}
 
-    let file_path = argv[1].clone();
-    let input = match std::fs::read_to_string(file_path) {
-        Ok(input) => input,
-        Err(e) => panic!("Failed to read file: {}", e),
-    };
-    let lexer = lex::Lexer::new(input);
-    let ast = parse::parse(lexer)?;
-    codegen::codegen(ast);
+fn parse_args() -> Result<Args, Diagnostic> {
+    let mut file_path = None;
+    let mut preprocess_only = false;
+    let mut preprocess_options = PreprocessOptions::default();
+    let mut parse_options = ParseOptions::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "-E" {
+            preprocess_only = true;
+        } else if let Some(dir) = arg.strip_prefix("-I") {
+            let dir = match dir {
+                "" => args.next().ok_or_else(|| {
+                    Diagnostic::error("missing directory after `-I`").with_note(USAGE)
+                })?,
+                dir => dir.to_string(),
+            };
+            preprocess_options.include_paths.push(dir.into());
+        } else if let Some(limit) = arg.strip_prefix("-ferror-limit=") {
+            parse_options.error_limit = limit.parse().map_err(|_| {
+                Diagnostic::error(format!("invalid error limit `{}`", limit)).with_note(USAGE)
+            })?;
+        } else if arg.starts_with('-') {
+            return Err(Diagnostic::error(format!("unknown option `{}`", arg)).with_note(USAGE));
+        } else if file_path.replace(arg).is_some() {
+            return Err(Diagnostic::error("multiple input files").with_note(USAGE));
+        }
+    }
+
+    match file_path {
+        Some(file_path) => Ok(Args {
+            file_path,
+            preprocess_only,
+            preprocess_options,
+            parse_options,
+        }),
+        None => Err(Diagnostic::error("no input file").with_note(USAGE)),
+    }
+}
 
-    Ok(())
+fn compile(source_map: &mut SourceMap) -> Result<(), Vec<Diagnostic>> {
+    let Args {
+        file_path,
+        preprocess_only,
+        preprocess_options,
+        parse_options,
+    } = parse_args().map_err(|e| vec![e])?;
+
+    let input = std::fs::read_to_string(&file_path).map_err(|e| {
+        vec![Diagnostic::error(format!(
+            "failed to read `{}`: {}",
+            file_path, e
+        ))]
+    })?;
+    let file_id = source_map.add_file(file_path, input);
+
+    let input = preprocess::preprocess(source_map, file_id, &preprocess_options)?;
+    if preprocess_only {
+        print!("{}", input);
+        return Ok(());
+    }
+
+    let lexer = lex::Lexer::with_file_id(input, file_id).with_file_ids(source_map.file_ids());
+    let (ast, warnings) = parse::parse(lexer, &parse_options)?;
+    emit(&warnings, source_map);
+    let ast = sema::analyze(&ast)?;
+    codegen::codegen(ast).map_err(|e| vec![e])
+}
+
+fn emit(diagnostics: &[Diagnostic], source_map: &SourceMap) {
+    for diagnostic in diagnostics.iter() {
+        eprintln!("{}", diagnostic.render(source_map));
+    }
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    if errors > 0 {
+        eprintln!(
+            "{} error{} generated.",
+            errors,
+            if errors == 1 { "" } else { "s" }
+        );
+    }
 }
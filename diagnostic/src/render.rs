@@ -0,0 +1,195 @@
+use std::fmt::Write;
+
+use span::{FileId, SourceMap};
+
+use crate::{Diagnostic, Label};
+
+const TAB_WIDTH: usize = 4;
+
+impl Diagnostic {
+    /// renders the diagnostic rustc-style with source snippets and carets.
+    ///
+    /// ```text
+    /// error[E0100]: expected `;`, found `}`
+    ///  --> main.c:3:1
+    ///   |
+    /// 3 | }
+    ///   | ^ expected `;`
+    /// ```
+    pub fn render(&self, sources: &SourceMap) -> String {
+        let mut out = String::new();
+        writeln!(out, "{}", self).unwrap();
+
+        let width = self
+            .labels
+            .iter()
+            .map(|l| l.span.line.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let pad = " ".repeat(width);
+
+        // the file of the primary label comes first, then the others in order of appearance.
+        let mut files: Vec<FileId> = vec![];
+        if let Some(span) = self.primary_span() {
+            files.push(span.file_id);
+        }
+        for label in self.labels.iter() {
+            if !files.contains(&label.span.file_id) {
+                files.push(label.span.file_id);
+            }
+        }
+
+        for (i, file_id) in files.iter().enumerate() {
+            let mut labels = self
+                .labels
+                .iter()
+                .filter(|l| l.span.file_id == *file_id)
+                .collect::<Vec<_>>();
+            labels.sort_by_key(|l| (l.span.line, l.span.column));
+
+            let anchor = self
+                .labels
+                .iter()
+                .find(|l| l.primary && l.span.file_id == *file_id)
+                .unwrap_or(labels[0]);
+            let arrow = if i == 0 { "-->" } else { ":::" };
+            writeln!(out, "{pad}{arrow} {}", sources.location(anchor.span)).unwrap();
+            writeln!(out, "{pad} |").unwrap();
+
+            let mut previous_line: Option<usize> = None;
+            for label in labels.iter() {
+                let line = label.span.line;
+                let text = sources
+                    .get(*file_id)
+                    .and_then(|f| f.line(line))
+                    .unwrap_or("");
+                if previous_line != Some(line) {
+                    if previous_line.map_or(false, |p| line > p + 1) {
+                        writeln!(out, "...").unwrap();
+                    }
+                    writeln!(out, "{line:>width$} | {}", expand_tabs(text)).unwrap();
+                    previous_line = Some(line);
+                }
+                writeln!(out, "{pad} | {}", underline(label, text).trim_end()).unwrap();
+            }
+        }
+
+        if !self.labels.is_empty() && !self.notes.is_empty() {
+            writeln!(out, "{pad} |").unwrap();
+        }
+        for note in self.notes.iter() {
+            writeln!(out, "{pad} = note: {note}").unwrap();
+        }
+
+        out
+    }
+}
+
+fn expand_tabs(text: &str) -> String {
+    text.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+/// start of the character of `text` at byte `index`, clamped to the end of `text`.
+fn byte_index(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// marker line for a label, e.g. `    ^^^ message`. the column and the length of the span are
+/// in bytes, the marker is placed by characters.
+fn underline(label: &Label, text: &str) -> String {
+    let start = byte_index(text, label.span.column.saturating_sub(1));
+    let indent = expand_tabs(&text[..start]).chars().count();
+    // spans reaching past the end of the line are cut at the line end.
+    let end = byte_index(text, start + label.span.len());
+    let length = text[start..end].chars().count().max(1);
+    let marker = if label.primary { "^" } else { "-" };
+    format!(
+        "{}{} {}",
+        " ".repeat(indent),
+        marker.repeat(length),
+        label.message
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use span::{SourceMap, Span};
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let mut sources = SourceMap::new();
+        let id = sources.add_file(
+            String::from("main.c"),
+            String::from("int foo() {\n    return 0;\n}\nint foo() {\n\treturn 1\n}\n"),
+        );
+        let utf8 = sources.add_file(
+            String::from("utf8.c"),
+            String::from("char *s = \"ééé\"; return x;\n"),
+        );
+
+        let cases = vec![
+            (
+                Diagnostic::error("expected `;`, found `}`")
+                    .with_code("E0100")
+                    .with_primary_label(Span::new(id, 50, 51, 6, 1), "expected `;`"),
+                r#"error[E0100]: expected `;`, found `}`
+ --> main.c:6:1
+  |
+6 | }
+  | ^ expected `;`
+"#,
+            ),
+            (
+                Diagnostic::error("redefinition of `foo`")
+                    .with_primary_label(Span::new(id, 32, 35, 4, 5), "redefined here")
+                    .with_secondary_label(Span::new(id, 4, 7, 1, 5), "previously defined here")
+                    .with_note("functions may only be defined once"),
+                r#"error: redefinition of `foo`
+ --> main.c:4:5
+  |
+1 | int foo() {
+  |     --- previously defined here
+...
+4 | int foo() {
+  |     ^^^ redefined here
+  |
+  = note: functions may only be defined once
+"#,
+            ),
+            (
+                Diagnostic::warning("missing `;`")
+                    .with_primary_label(Span::new(id, 41, 49, 5, 2), ""),
+                r#"warning: missing `;`
+ --> main.c:5:2
+  |
+5 |     return 1
+  |     ^^^^^^^^
+"#,
+            ),
+            (
+                Diagnostic::error("use of undeclared identifier `x`")
+                    .with_primary_label(Span::new(utf8, 27, 28, 1, 28), "not found"),
+                r#"error: use of undeclared identifier `x`
+ --> utf8.c:1:25
+  |
+1 | char *s = "ééé"; return x;
+  |                         ^ not found
+"#,
+            ),
+            (
+                Diagnostic::error("no input files"),
+                "error: no input files\n",
+            ),
+        ];
+
+        for (diagnostic, expected) in cases {
+            assert_eq!(diagnostic.render(&sources), expected);
+        }
+    }
+}
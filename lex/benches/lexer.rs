@@ -0,0 +1,58 @@
+//! throughput of the lexer on a generated multi-megabyte file.
+//!
+//! run with `cargo bench -p lex`.
+use std::time::Instant;
+
+use lex::tokens::Token;
+use lex::Lexer;
+
+/// size of the generated input in bytes.
+const SIZE: usize = 8 * 1024 * 1024;
+const RUNS: usize = 5;
+
+fn generate() -> String {
+    let function = r#"
+/* computes a checksum, ünïcödé in comments is fine */
+int checksum_NAME(char *data, long length) {
+    int sum = 0x1F;
+    for (long i = 0; i < length; i++) {
+        sum = (sum << 5) ^ data[i] + 'a';  // mix
+        if (sum >= 1000000 && sum != 42) sum %= 7;
+    }
+    char *message = "done\n\t\"quoted\"";
+    return sum + 1.5e3f;
+}
+"#;
+    let mut input = String::with_capacity(SIZE + function.len());
+    let mut i = 0;
+    while input.len() < SIZE {
+        input.push_str(&function.replace("NAME", &i.to_string()));
+        i += 1;
+    }
+    input
+}
+
+fn main() {
+    let input = generate();
+    let megabytes = input.len() as f64 / (1024.0 * 1024.0);
+
+    let mut best = f64::MAX;
+    let mut tokens = 0;
+    for _ in 0..RUNS {
+        let mut lexer = Lexer::new(input.clone());
+        let start = Instant::now();
+        tokens = 0;
+        while lexer.next().unwrap().token != Token::Eof {
+            tokens += 1;
+        }
+        best = best.min(start.elapsed().as_secs_f64());
+    }
+
+    println!(
+        "lexed {:.1} MiB ({} tokens) in {:.3}s: {:.1} MiB/s",
+        megabytes,
+        tokens,
+        best,
+        megabytes / best
+    );
+}
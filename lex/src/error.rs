@@ -0,0 +1,92 @@
+use std::fmt;
+
+use diagnostic::Diagnostic;
+use span::Span;
+
+/// error found while reading a token, see `Lexer::next`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum LexErrorKind {
+    InvalidCharacter(char),
+    /// `/*` without `*/`, the span is the opening `/*`.
+    UnterminatedComment,
+    /// string or character literal without its closing quote, the span is the opening one.
+    UnterminatedLiteral(char),
+    EmptyCharacterConstant,
+    MultiCharacterConstant,
+    UnknownEscape(char),
+    OctalEscapeOutOfRange,
+    HexEscapeOutOfRange,
+    MissingHexEscapeDigits,
+    InvalidDigit {
+        digit: char,
+        base: &'static str,
+    },
+    MissingDigits,
+    InvalidIntegerSuffix(String),
+    IntegerTooLarge,
+    InvalidFloat(String),
+}
+
+impl fmt::Display for LexErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LexErrorKind::InvalidCharacter(c) => write!(f, "invalid character `{}`", c),
+            LexErrorKind::UnterminatedComment => write!(f, "unterminated comment"),
+            LexErrorKind::UnterminatedLiteral(quote) => {
+                write!(f, "missing terminating {} character", quote)
+            }
+            LexErrorKind::EmptyCharacterConstant => write!(f, "empty character constant"),
+            LexErrorKind::MultiCharacterConstant => {
+                write!(f, "multi-character character constant")
+            }
+            LexErrorKind::UnknownEscape(c) => write!(f, "unknown escape sequence `\\{}`", c),
+            LexErrorKind::OctalEscapeOutOfRange => write!(f, "octal escape sequence out of range"),
+            LexErrorKind::HexEscapeOutOfRange => write!(f, "hex escape sequence out of range"),
+            LexErrorKind::MissingHexEscapeDigits => {
+                write!(f, "`\\x` used with no following hex digits")
+            }
+            LexErrorKind::InvalidDigit { digit, base } => {
+                write!(f, "invalid digit `{}` in {} constant", digit, base)
+            }
+            LexErrorKind::MissingDigits => write!(f, "expected digits after the base prefix"),
+            LexErrorKind::InvalidIntegerSuffix(suffix) => {
+                write!(f, "invalid suffix `{}` on integer constant", suffix)
+            }
+            LexErrorKind::IntegerTooLarge => {
+                write!(f, "integer constant is too large for its type")
+            }
+            LexErrorKind::InvalidFloat(text) => write!(f, "invalid floating constant `{}`", text),
+        }
+    }
+}
+
+impl From<LexError> for Diagnostic {
+    fn from(error: LexError) -> Self {
+        let (code, label) = match error.kind {
+            LexErrorKind::InvalidCharacter(_) => ("E0001", "invalid token"),
+            LexErrorKind::UnterminatedComment => ("E0002", "comment starts here"),
+            LexErrorKind::UnterminatedLiteral(_) => ("E0002", "literal starts here"),
+            LexErrorKind::EmptyCharacterConstant | LexErrorKind::MultiCharacterConstant => {
+                ("E0003", "invalid character constant")
+            }
+            LexErrorKind::UnknownEscape(_)
+            | LexErrorKind::OctalEscapeOutOfRange
+            | LexErrorKind::HexEscapeOutOfRange
+            | LexErrorKind::MissingHexEscapeDigits => ("E0003", "invalid escape sequence"),
+            LexErrorKind::InvalidDigit { .. }
+            | LexErrorKind::MissingDigits
+            | LexErrorKind::InvalidIntegerSuffix(_)
+            | LexErrorKind::IntegerTooLarge
+            | LexErrorKind::InvalidFloat(_) => ("E0004", "invalid number"),
+        };
+        Diagnostic::error(error.kind.to_string())
+            .with_code(code)
+            .with_primary_label(error.span, label)
+    }
+}
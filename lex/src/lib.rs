@@ -1,59 +1,149 @@
-use tokens::Token;
+use std::collections::HashMap;
 
+use span::{FileId, Span};
+use tokens::{SpannedToken, Token};
+
+pub use error::{LexError, LexErrorKind};
+
+mod error;
+mod number;
 pub mod tokens;
 
 pub struct Lexer {
     input: String,
+    /// byte offset of `ch`.
     position: usize,
-    consume_position: usize,
+    /// current character, `'\0'` at the end of the input.
     ch: char,
+    file_id: FileId,
+    /// files named by line markers.
+    file_ids: HashMap<String, FileId>,
+    line: usize,
+    column: usize,
+    /// first error in the token being read.
+    error: Option<LexError>,
+    /// token read with an error, returned after it so that the parser does not report it again.
+    recovered: Option<SpannedToken>,
 }
 
 impl Lexer {
     pub fn new(input: String) -> Self {
+        Self::with_file_id(input, FileId::default())
+    }
+
+    pub fn with_file_id(input: String, file_id: FileId) -> Self {
         let mut lexer = Self {
             input,
             position: 0,
-            consume_position: 0,
             ch: '\0',
+            file_id,
+            file_ids: HashMap::new(),
+            line: 1,
+            column: 1,
+            error: None,
+            recovered: None,
         };
-        lexer.consume_char();
+        lexer.ch = lexer.char_at(0);
         lexer
     }
 
-    pub fn next(&mut self) -> Token {
+    /// files which `# <line> "<file>"` markers of preprocessed input may refer to.
+    pub fn with_file_ids(mut self, file_ids: HashMap<String, FileId>) -> Self {
+        self.file_ids = file_ids;
+        self
+    }
+
+    /// the next token. after an error, lexing goes on after the offending token or character.
+    /// a malformed literal is still returned after its error, e.g. as `0` for `0x`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<SpannedToken, LexError> {
+        if let Some(token) = self.recovered.take() {
+            return Ok(token);
+        }
         self.skip_whitespace();
-        match self.ch {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        let (start, line, column) = (self.position, self.line, self.column);
+        let Some(token) = self.read_token() else {
+            let span = Span::new(self.file_id, start, start + 1, line, column);
+            let kind = LexErrorKind::InvalidCharacter(self.ch);
+            self.consume_char();
+            return Err(LexError { kind, span });
+        };
+        let token = SpannedToken {
+            token,
+            span: Span::new(self.file_id, start, self.position, line, column),
+        };
+        match self.error.take() {
+            Some(error) => {
+                self.recovered = Some(token);
+                Err(error)
+            }
+            None => Ok(token),
+        }
+    }
+
+    /// reads the token starting at the current character, `None` if no token starts with it.
+    fn read_token(&mut self) -> Option<Token> {
+        let token = match self.ch {
             '+' => {
                 self.consume_char();
-                Token::Plus
+                match self.ch {
+                    '+' => {
+                        self.consume_char();
+                        Token::Increment
+                    }
+                    '=' => {
+                        self.consume_char();
+                        Token::PlusAssignment
+                    }
+                    _ => Token::Plus,
+                }
             }
             '-' => {
                 self.consume_char();
-                Token::Minus
-            }
-            '*' => {
-                self.consume_char();
-                Token::Asterisk
-            }
-            '/' => {
-                match self.peek_char() {
-                    '/' => {
+                match self.ch {
+                    '-' => {
                         self.consume_char();
-                        self.consume_char(); // skip '/'
-                        self.consume_inline_comment();
-                        return self.next();
+                        Token::Decrement
                     }
-                    '*' => {
+                    '=' => {
                         self.consume_char();
-                        self.consume_char(); // skip '*'
-                        self.consume_block_comment();
-                        return self.next();
+                        Token::MinusAssignment
                     }
-                    _ => {
+                    '>' => {
                         self.consume_char();
-                        Token::Slash
+                        Token::Arrow
                     }
+                    _ => Token::Minus,
+                }
+            }
+            '*' => {
+                self.consume_char();
+                if self.ch == '=' {
+                    self.consume_char();
+                    Token::AsteriskAssignment
+                } else {
+                    Token::Asterisk
+                }
+            }
+            '/' => {
+                self.consume_char();
+                if self.ch == '=' {
+                    self.consume_char();
+                    Token::SlashAssignment
+                } else {
+                    Token::Slash
+                }
+            }
+            '%' => {
+                self.consume_char();
+                if self.ch == '=' {
+                    self.consume_char();
+                    Token::PercentAssignment
+                } else {
+                    Token::Percent
                 }
             }
             '(' => {
@@ -80,10 +170,7 @@ impl Lexer {
                 self.consume_char();
                 Token::RBracket
             }
-            '\0' => {
-                self.consume_char();
-                Token::Eof
-            }
+            _ if self.is_eof() => Token::Eof,
             '!' => {
                 if self.peek_char() == '=' {
                     self.consume_char();
@@ -105,33 +192,117 @@ impl Lexer {
                 }
             }
             '<' => {
-                if self.peek_char() == '=' {
-                    self.consume_char();
-                    self.consume_char();
-                    Token::LtEq
-                } else {
-                    self.consume_char();
-                    Token::Lt
+                self.consume_char();
+                match (self.ch, self.peek_char()) {
+                    ('<', '=') => {
+                        self.consume_char();
+                        self.consume_char();
+                        Token::LShiftAssignment
+                    }
+                    ('<', _) => {
+                        self.consume_char();
+                        Token::LShift
+                    }
+                    ('=', _) => {
+                        self.consume_char();
+                        Token::LtEq
+                    }
+                    _ => Token::Lt,
                 }
             }
             '>' => {
-                if self.peek_char() == '=' {
-                    self.consume_char();
+                self.consume_char();
+                match (self.ch, self.peek_char()) {
+                    ('>', '=') => {
+                        self.consume_char();
+                        self.consume_char();
+                        Token::RShiftAssignment
+                    }
+                    ('>', _) => {
+                        self.consume_char();
+                        Token::RShift
+                    }
+                    ('=', _) => {
+                        self.consume_char();
+                        Token::GtEq
+                    }
+                    _ => Token::Gt,
+                }
+            }
+            '&' => {
+                self.consume_char();
+                match self.ch {
+                    '&' => {
+                        self.consume_char();
+                        Token::And
+                    }
+                    '=' => {
+                        self.consume_char();
+                        Token::AmpersandAssignment
+                    }
+                    _ => Token::Ampersand,
+                }
+            }
+            '|' => {
+                self.consume_char();
+                match self.ch {
+                    '|' => {
+                        self.consume_char();
+                        Token::Or
+                    }
+                    '=' => {
+                        self.consume_char();
+                        Token::PipeAssignment
+                    }
+                    _ => Token::Pipe,
+                }
+            }
+            '^' => {
+                self.consume_char();
+                if self.ch == '=' {
                     self.consume_char();
-                    Token::GtEq
+                    Token::CaretAssignment
                 } else {
-                    self.consume_char();
-                    Token::Gt
+                    Token::Caret
                 }
             }
-            '&' => {
+            '~' => {
+                self.consume_char();
+                Token::Tilde
+            }
+            '?' => {
                 self.consume_char();
-                Token::Ampersand
+                Token::Question
             }
-            '"' => {
+            ':' => {
                 self.consume_char();
-                let s = self.consume_string();
-                Token::String(s)
+                Token::Colon
+            }
+            '.' if self.peek_char().is_ascii_digit() => self.consume_number(),
+            '.' if self.peek_char() == '.' && self.char_at(self.position + 2) == '.' => {
+                for _ in 0..3 {
+                    self.consume_char();
+                }
+                Token::Ellipsis
+            }
+            '.' => {
+                self.consume_char();
+                Token::Dot
+            }
+            '"' => Token::String(self.consume_quoted('"')),
+            '\'' => {
+                let (start, line, column) = (self.position, self.line, self.column);
+                let bytes = self.consume_quoted('\'');
+                if bytes.len() != 1 {
+                    let span = Span::new(self.file_id, start, self.position, line, column);
+                    let kind = if bytes.is_empty() {
+                        LexErrorKind::EmptyCharacterConstant
+                    } else {
+                        LexErrorKind::MultiCharacterConstant
+                    };
+                    self.report_error(span, kind);
+                }
+                Token::Character(bytes.first().copied().unwrap_or(0))
             }
             ';' => {
                 self.consume_char();
@@ -142,41 +313,53 @@ impl Lexer {
                 Token::Comma
             }
             _ => {
-                if self.ch.is_numeric() {
-                    let num = self.consume_number();
-                    Token::Integer(num)
-                } else if self.ch >= 'a' && self.ch <= 'z' {
+                if self.ch.is_ascii_digit() {
+                    self.consume_number()
+                } else if self.ch.is_ascii_alphabetic() || self.ch == '_' {
                     let w = self.consume_word();
                     self.word_into_token(w)
                 } else {
-                    self.report_error("invalid token");
-                    panic!();
+                    return None;
                 }
             }
-        }
+        };
+        Some(token)
     }
 
     fn consume_char(&mut self) {
-        if self.consume_position >= self.input.len() {
-            self.ch = '\0';
+        if self.is_eof() {
+            return;
+        }
+        if self.ch == '\n' {
+            self.line += 1;
+            self.column = 1;
         } else {
-            self.ch = self.input.chars().nth(self.consume_position).unwrap();
+            // in bytes like the preprocessor's, the renderer turns them into characters.
+            self.column += self.ch.len_utf8();
         }
-        self.position = self.consume_position;
-        self.consume_position += 1;
+        self.position += self.ch.len_utf8();
+        self.ch = self.char_at(self.position);
     }
 
-    fn consume_number(&mut self) -> i32 {
-        let position = self.position;
-        while self.ch.is_numeric() {
-            self.consume_char();
+    fn peek_char(&self) -> char {
+        if self.is_eof() {
+            return '\0';
         }
-        self.input[position..self.position].parse().unwrap()
+        self.char_at(self.position + self.ch.len_utf8())
     }
 
+    fn char_at(&self, position: usize) -> char {
+        self.input[position..].chars().next().unwrap_or('\0')
+    }
+
+    fn is_eof(&self) -> bool {
+        self.position >= self.input.len()
+    }
+
+    /// identifier or keyword, `[A-Za-z_][A-Za-z0-9_]*`.
     fn consume_word(&mut self) -> String {
         let position = self.position;
-        while self.ch.is_alphabetic() {
+        while self.ch.is_ascii_alphanumeric() || self.ch == '_' {
             self.consume_char();
         }
         self.input[position..self.position].to_string()
@@ -196,131 +379,335 @@ impl Lexer {
             "long" => Token::Long,
             "float" => Token::Float,
             "double" => Token::Double,
+            "auto" => Token::Auto,
+            "break" => Token::Break,
+            "case" => Token::Case,
+            "const" => Token::Const,
+            "continue" => Token::Continue,
+            "default" => Token::Default,
+            "do" => Token::Do,
+            "enum" => Token::Enum,
+            "extern" => Token::Extern,
+            "goto" => Token::Goto,
+            "inline" => Token::Inline,
+            "register" => Token::Register,
+            "restrict" => Token::Restrict,
+            "signed" => Token::Signed,
+            "sizeof" => Token::Sizeof,
+            "static" => Token::Static,
+            "struct" => Token::Struct,
+            "switch" => Token::Switch,
+            "typedef" => Token::Typedef,
+            "union" => Token::Union,
+            "unsigned" => Token::Unsigned,
+            "volatile" => Token::Volatile,
+            // the C23 spelling of `_Alignof`.
+            "alignof" => Token::Alignof,
+            "_Alignas" => Token::Alignas,
+            "_Alignof" => Token::Alignof,
+            "_Atomic" => Token::Atomic,
+            "_Bool" => Token::Bool,
+            "_Complex" => Token::Complex,
+            "_Generic" => Token::Generic,
+            "_Imaginary" => Token::Imaginary,
+            "_Noreturn" => Token::Noreturn,
+            "_Static_assert" => Token::StaticAssert,
+            "_Thread_local" => Token::ThreadLocal,
             _ => Token::Identifier(word),
         }
     }
 
-    fn consume_string(&mut self) -> String {
-        let position = self.position;
-        while self.ch != '"' {
-            self.consume_char();
+    /// literal from the opening `quote` to the closing one, escape sequences decoded.
+    fn consume_quoted(&mut self, quote: char) -> Vec<u8> {
+        let (start, line, column) = (self.position, self.line, self.column);
+        self.consume_char(); // consume the opening quote
+        let mut bytes = vec![];
+        while self.ch != quote {
+            if self.ch == '\n' || self.is_eof() {
+                let span = Span::new(self.file_id, start, start + 1, line, column);
+                self.report_error(span, LexErrorKind::UnterminatedLiteral(quote));
+                return bytes;
+            }
+            if self.ch == '\\' {
+                bytes.push(self.consume_escape());
+            } else {
+                let mut buf = [0; 4];
+                bytes.extend_from_slice(self.ch.encode_utf8(&mut buf).as_bytes());
+                self.consume_char();
+            }
         }
-        let s = self.input[position..self.position].to_string();
-        self.consume_char(); // consume '"'
-        s
+        self.consume_char(); // consume the closing quote
+        bytes
+    }
+
+    /// `\n`, `\101`, `\x41` and the like, starting at the backslash.
+    fn consume_escape(&mut self) -> u8 {
+        let (start, line, column) = (self.position, self.line, self.column);
+        self.consume_char(); // consume '\\'
+        let c = self.ch;
+        if c == '\n' || self.is_eof() {
+            return b'\\';
+        }
+        self.consume_char();
+        let (value, error) = match c {
+            'n' => (b'\n' as u32, None),
+            't' => (b'\t' as u32, None),
+            'r' => (b'\r' as u32, None),
+            'a' => (0x07, None),
+            'b' => (0x08, None),
+            'f' => (0x0c, None),
+            'v' => (0x0b, None),
+            '\\' | '\'' | '"' | '?' => (c as u32, None),
+            '0'..='7' => {
+                let mut value = c as u32 - '0' as u32;
+                for _ in 0..2 {
+                    match self.ch.to_digit(8) {
+                        Some(d) => value = value * 8 + d,
+                        None => break,
+                    }
+                    self.consume_char();
+                }
+                let error = (value > 0xff).then_some(LexErrorKind::OctalEscapeOutOfRange);
+                (value, error)
+            }
+            'x' => {
+                let position = self.position;
+                let mut value: u32 = 0;
+                while let Some(d) = self.ch.to_digit(16) {
+                    value = value.saturating_mul(16).saturating_add(d);
+                    self.consume_char();
+                }
+                let error = if self.position == position {
+                    Some(LexErrorKind::MissingHexEscapeDigits)
+                } else if value > 0xff {
+                    Some(LexErrorKind::HexEscapeOutOfRange)
+                } else {
+                    None
+                };
+                (value, error)
+            }
+            _ => {
+                let span = Span::new(self.file_id, start, self.position, line, column);
+                self.report_error(span, LexErrorKind::UnknownEscape(c));
+                return c as u8;
+            }
+        };
+        if let Some(kind) = error {
+            let span = Span::new(self.file_id, start, self.position, line, column);
+            self.report_error(span, kind);
+        }
+        value as u8
     }
 
     fn consume_inline_comment(&mut self) {
-        while self.ch != '\n' {
+        while self.ch != '\n' && !self.is_eof() {
             self.consume_char();
         }
     }
 
     fn consume_block_comment(&mut self) {
+        let (start, line, column) = (self.position, self.line, self.column);
+        self.consume_char(); // consume '/'
+        self.consume_char(); // consume '*'
         while self.ch != '*' || self.peek_char() != '/' {
+            if self.is_eof() {
+                let span = Span::new(self.file_id, start, start + 2, line, column);
+                self.report_error(span, LexErrorKind::UnterminatedComment);
+                return;
+            }
             self.consume_char();
         }
         self.consume_char(); // consume '*'
         self.consume_char(); // consume '/'
     }
 
+    /// skips whitespace, comments and line markers.
     fn skip_whitespace(&mut self) {
-        while self.ch.is_whitespace() {
+        loop {
+            match (self.ch, self.peek_char()) {
+                (c, _) if c.is_whitespace() => self.consume_char(),
+                ('#', _) if self.column == 1 && self.consume_line_marker() => {}
+                ('/', '/') => {
+                    self.consume_char();
+                    self.consume_char(); // skip '/'
+                    self.consume_inline_comment();
+                }
+                ('/', '*') => self.consume_block_comment(),
+                _ => break,
+            }
+        }
+    }
+
+    /// `# <line> "<file>"` left by the preprocessor, telling where the next line comes from.
+    /// nothing is consumed unless the whole marker is there.
+    fn consume_line_marker(&mut self) -> bool {
+        let saved = (self.position, self.ch, self.line, self.column);
+        self.consume_char(); // consume '#'
+        while self.ch == ' ' {
+            self.consume_char();
+        }
+        let position = self.position;
+        while self.ch.is_ascii_digit() {
             self.consume_char();
         }
+        let line = self.input[position..self.position].parse::<usize>().ok();
+        while self.ch == ' ' {
+            self.consume_char();
+        }
+        let name = self.consume_quoted_name();
+
+        match (line, name) {
+            (Some(line), Some(name)) => {
+                while self.ch != '\n' && !self.is_eof() {
+                    self.consume_char();
+                }
+                if let Some(file_id) = self.file_ids.get(&name) {
+                    self.file_id = *file_id;
+                }
+                // the line count goes up when the newline is consumed.
+                self.line = line.saturating_sub(1);
+                true
+            }
+            _ => {
+                (self.position, self.ch, self.line, self.column) = saved;
+                false
+            }
+        }
     }
 
-    fn peek_char(&self) -> char {
-        if self.consume_position >= self.input.len() {
-            '\0'
-        } else {
-            self.input.chars().nth(self.consume_position).unwrap()
+    /// `"..."` with `\\` and `\"` escaped, as written in line markers.
+    fn consume_quoted_name(&mut self) -> Option<String> {
+        if self.ch != '"' {
+            return None;
         }
+        self.consume_char();
+        let mut name = String::new();
+        while self.ch != '"' {
+            if self.ch == '\\' {
+                self.consume_char();
+            }
+            if self.ch == '\n' || self.is_eof() {
+                return None;
+            }
+            name.push(self.ch);
+            self.consume_char();
+        }
+        self.consume_char(); // consume '"'
+        Some(name)
     }
 
-    fn report_error(&self, message: &str) {
-        let mut error = String::new();
-        error.push_str("\x1b[31merror\x1b[0m: ");
-        error.push_str(message);
-        error.push_str("\n");
-        error.push_str(&self.input);
-        error.push_str("\n");
-        error.push_str(&" ".repeat(self.position));
-        error.push_str("\x1b[33m^\x1b[0m\n");
-        println!("{}", error);
+    /// records an error in the current token, only the first one is kept.
+    fn report_error(&mut self, span: Span, kind: LexErrorKind) {
+        if self.error.is_none() {
+            self.error = Some(LexError { kind, span });
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use ast::IntegerType;
 
     #[test]
     fn test_next_token() {
         {
             let input = String::from(
-                "1 + - / * ( ) { } = ! == != < > <= >= & ; , a b foo bar return if else while for void char short int long float double \"Hello World!\n\"",
+                "1 + - / * ( ) { } = ! == != < > <= >= & ; , a b foo bar return if else while for void char short int long float double \"Hello World!\\n\"",
             );
             let mut lexer = Lexer::new(input);
-            assert_eq!(lexer.next(), Token::Integer(1));
-            assert_eq!(lexer.next(), Token::Plus);
-            assert_eq!(lexer.next(), Token::Minus);
-            assert_eq!(lexer.next(), Token::Slash);
-            assert_eq!(lexer.next(), Token::Asterisk);
-            assert_eq!(lexer.next(), Token::LParen);
-            assert_eq!(lexer.next(), Token::RParen);
-            assert_eq!(lexer.next(), Token::LBrace);
-            assert_eq!(lexer.next(), Token::RBrace);
-            assert_eq!(lexer.next(), Token::Assignment);
-            assert_eq!(lexer.next(), Token::Not);
-            assert_eq!(lexer.next(), Token::Eq);
-            assert_eq!(lexer.next(), Token::NotEq);
-            assert_eq!(lexer.next(), Token::Lt);
-            assert_eq!(lexer.next(), Token::Gt);
-            assert_eq!(lexer.next(), Token::LtEq);
-            assert_eq!(lexer.next(), Token::GtEq);
-            assert_eq!(lexer.next(), Token::Ampersand);
-            assert_eq!(lexer.next(), Token::SemiColon);
-            assert_eq!(lexer.next(), Token::Comma);
-            assert_eq!(lexer.next(), Token::Identifier(String::from("a")));
-            assert_eq!(lexer.next(), Token::Identifier(String::from("b")));
-            assert_eq!(lexer.next(), Token::Identifier(String::from("foo")));
-            assert_eq!(lexer.next(), Token::Identifier(String::from("bar")));
-            assert_eq!(lexer.next(), Token::Return);
-            assert_eq!(lexer.next(), Token::If);
-            assert_eq!(lexer.next(), Token::Else);
-            assert_eq!(lexer.next(), Token::While);
-            assert_eq!(lexer.next(), Token::For);
-            assert_eq!(lexer.next(), Token::Void);
-            assert_eq!(lexer.next(), Token::Char);
-            assert_eq!(lexer.next(), Token::Short);
-            assert_eq!(lexer.next(), Token::Int);
-            assert_eq!(lexer.next(), Token::Long);
-            assert_eq!(lexer.next(), Token::Float);
-            assert_eq!(lexer.next(), Token::Double);
-            assert_eq!(lexer.next(), Token::String(String::from("Hello World!\n")));
-            assert_eq!(lexer.next(), Token::Eof);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(1, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::Plus);
+            assert_eq!(lexer.next().unwrap().token, Token::Minus);
+            assert_eq!(lexer.next().unwrap().token, Token::Slash);
+            assert_eq!(lexer.next().unwrap().token, Token::Asterisk);
+            assert_eq!(lexer.next().unwrap().token, Token::LParen);
+            assert_eq!(lexer.next().unwrap().token, Token::RParen);
+            assert_eq!(lexer.next().unwrap().token, Token::LBrace);
+            assert_eq!(lexer.next().unwrap().token, Token::RBrace);
+            assert_eq!(lexer.next().unwrap().token, Token::Assignment);
+            assert_eq!(lexer.next().unwrap().token, Token::Not);
+            assert_eq!(lexer.next().unwrap().token, Token::Eq);
+            assert_eq!(lexer.next().unwrap().token, Token::NotEq);
+            assert_eq!(lexer.next().unwrap().token, Token::Lt);
+            assert_eq!(lexer.next().unwrap().token, Token::Gt);
+            assert_eq!(lexer.next().unwrap().token, Token::LtEq);
+            assert_eq!(lexer.next().unwrap().token, Token::GtEq);
+            assert_eq!(lexer.next().unwrap().token, Token::Ampersand);
+            assert_eq!(lexer.next().unwrap().token, Token::SemiColon);
+            assert_eq!(lexer.next().unwrap().token, Token::Comma);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Identifier(String::from("a"))
+            );
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Identifier(String::from("b"))
+            );
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Identifier(String::from("foo"))
+            );
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Identifier(String::from("bar"))
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::Return);
+            assert_eq!(lexer.next().unwrap().token, Token::If);
+            assert_eq!(lexer.next().unwrap().token, Token::Else);
+            assert_eq!(lexer.next().unwrap().token, Token::While);
+            assert_eq!(lexer.next().unwrap().token, Token::For);
+            assert_eq!(lexer.next().unwrap().token, Token::Void);
+            assert_eq!(lexer.next().unwrap().token, Token::Char);
+            assert_eq!(lexer.next().unwrap().token, Token::Short);
+            assert_eq!(lexer.next().unwrap().token, Token::Int);
+            assert_eq!(lexer.next().unwrap().token, Token::Long);
+            assert_eq!(lexer.next().unwrap().token, Token::Float);
+            assert_eq!(lexer.next().unwrap().token, Token::Double);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::String(b"Hello World!\n".to_vec())
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::Eof);
         }
         {
             let input = String::from("1 + 2");
             let mut lexer = Lexer::new(input);
 
-            assert_eq!(lexer.next(), Token::Integer(1));
-            assert_eq!(lexer.next(), Token::Plus);
-            assert_eq!(lexer.next(), Token::Integer(2));
-            assert_eq!(lexer.next(), Token::Eof);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(1, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::Plus);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(2, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::Eof);
         }
 
         {
             let input = String::from("5+20-4");
             let mut lexer = Lexer::new(input);
 
-            assert_eq!(lexer.next(), Token::Integer(5));
-            assert_eq!(lexer.next(), Token::Plus);
-            assert_eq!(lexer.next(), Token::Integer(20));
-            assert_eq!(lexer.next(), Token::Minus);
-            assert_eq!(lexer.next(), Token::Integer(4));
-            assert_eq!(lexer.next(), Token::Eof);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(5, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::Plus);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(20, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::Minus);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(4, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::Eof);
         }
 
         {
@@ -339,34 +726,336 @@ mod test {
             );
             let mut lexer = Lexer::new(input);
 
-            assert_eq!(lexer.next(), Token::Integer(5));
-            assert_eq!(lexer.next(), Token::Plus);
-            assert_eq!(lexer.next(), Token::Integer(20));
-            assert_eq!(lexer.next(), Token::Minus);
-            assert_eq!(lexer.next(), Token::Integer(4));
-            assert_eq!(lexer.next(), Token::SemiColon);
-
-            assert_eq!(lexer.next(), Token::Integer(5));
-            assert_eq!(lexer.next(), Token::Plus);
-            assert_eq!(lexer.next(), Token::Integer(20));
-            assert_eq!(lexer.next(), Token::Minus);
-            assert_eq!(lexer.next(), Token::Integer(4));
-            assert_eq!(lexer.next(), Token::SemiColon);
-
-            assert_eq!(lexer.next(), Token::Integer(5));
-            assert_eq!(lexer.next(), Token::Plus);
-            assert_eq!(lexer.next(), Token::Integer(20));
-            assert_eq!(lexer.next(), Token::Minus);
-            assert_eq!(lexer.next(), Token::Integer(4));
-            assert_eq!(lexer.next(), Token::SemiColon);
-
-            assert_eq!(lexer.next(), Token::Integer(5));
-            assert_eq!(lexer.next(), Token::Plus);
-            assert_eq!(lexer.next(), Token::Integer(20));
-            assert_eq!(lexer.next(), Token::Minus);
-            assert_eq!(lexer.next(), Token::Integer(4));
-            assert_eq!(lexer.next(), Token::SemiColon);
-            assert_eq!(lexer.next(), Token::Eof);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(5, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::Plus);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(20, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::Minus);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(4, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::SemiColon);
+
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(5, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::Plus);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(20, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::Minus);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(4, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::SemiColon);
+
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(5, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::Plus);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(20, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::Minus);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(4, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::SemiColon);
+
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(5, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::Plus);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(20, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::Minus);
+            assert_eq!(
+                lexer.next().unwrap().token,
+                Token::Integer(4, IntegerType::Int)
+            );
+            assert_eq!(lexer.next().unwrap().token, Token::SemiColon);
+            assert_eq!(lexer.next().unwrap().token, Token::Eof);
+        }
+    }
+
+    #[test]
+    fn test_operators() {
+        let input = String::from(
+            "% ++ -- += -= *= /= %= && || << >> <<= >>= | ^ ~ ? : -> . ... &= |= ^= a+++b a-->b x<<=1",
+        );
+        let mut lexer = Lexer::new(input);
+        let cases = vec![
+            Token::Percent,
+            Token::Increment,
+            Token::Decrement,
+            Token::PlusAssignment,
+            Token::MinusAssignment,
+            Token::AsteriskAssignment,
+            Token::SlashAssignment,
+            Token::PercentAssignment,
+            Token::And,
+            Token::Or,
+            Token::LShift,
+            Token::RShift,
+            Token::LShiftAssignment,
+            Token::RShiftAssignment,
+            Token::Pipe,
+            Token::Caret,
+            Token::Tilde,
+            Token::Question,
+            Token::Colon,
+            Token::Arrow,
+            Token::Dot,
+            Token::Ellipsis,
+            Token::AmpersandAssignment,
+            Token::PipeAssignment,
+            Token::CaretAssignment,
+            // longest match: `a ++ + b`, `a -- > b`
+            Token::Identifier(String::from("a")),
+            Token::Increment,
+            Token::Plus,
+            Token::Identifier(String::from("b")),
+            Token::Identifier(String::from("a")),
+            Token::Decrement,
+            Token::Gt,
+            Token::Identifier(String::from("b")),
+            Token::Identifier(String::from("x")),
+            Token::LShiftAssignment,
+            Token::Integer(1, IntegerType::Int),
+            Token::Eof,
+        ];
+        for token in cases {
+            assert_eq!(lexer.next().unwrap().token, token);
+        }
+    }
+
+    #[test]
+    fn test_literals() {
+        let input =
+            String::from(r#"'a' '\n' '\'' '\0' '\x41' '\101' '"' "a\tb\\\"\'\?\0\12\x7fz" "" "é""#);
+        let mut lexer = Lexer::new(input);
+        let cases = vec![
+            Token::Character(b'a'),
+            Token::Character(b'\n'),
+            Token::Character(b'\''),
+            Token::Character(0),
+            Token::Character(b'A'),
+            Token::Character(b'A'),
+            Token::Character(b'"'),
+            Token::String(b"a\tb\\\"'?\0\n\x7fz".to_vec()),
+            Token::String(vec![]),
+            Token::String("é".as_bytes().to_vec()),
+            Token::Eof,
+        ];
+        for token in cases {
+            assert_eq!(lexer.next().unwrap().token, token);
+        }
+
+        let cases = vec![
+            ("'ab'", "multi-character character constant"),
+            ("''", "empty character constant"),
+            ("\"\\q\"", "unknown escape sequence `\\q`"),
+            ("\"\\x\"", "`\\x` used with no following hex digits"),
+            ("'\\x100'", "hex escape sequence out of range"),
+            ("'\\777'", "octal escape sequence out of range"),
+            ("\"abc\n", "missing terminating \" character"),
+        ];
+        for (input, message) in cases {
+            let error = Lexer::new(String::from(input)).next().unwrap_err();
+            assert_eq!(error.kind.to_string(), message, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_identifiers_and_keywords() {
+        let input = String::from(
+            "my_var x1 MAX _ __func__ a1b2 \
+             auto break case const continue default do enum extern goto inline register \
+             restrict signed sizeof static struct switch typedef union unsigned volatile \
+             alignof _Alignas _Alignof _Atomic _Bool _Complex _Generic _Imaginary _Noreturn \
+             _Static_assert _Thread_local Int _bool",
+        );
+        let mut lexer = Lexer::new(input);
+        let identifier = |name: &str| Token::Identifier(String::from(name));
+        let cases = vec![
+            identifier("my_var"),
+            identifier("x1"),
+            identifier("MAX"),
+            identifier("_"),
+            identifier("__func__"),
+            identifier("a1b2"),
+            Token::Auto,
+            Token::Break,
+            Token::Case,
+            Token::Const,
+            Token::Continue,
+            Token::Default,
+            Token::Do,
+            Token::Enum,
+            Token::Extern,
+            Token::Goto,
+            Token::Inline,
+            Token::Register,
+            Token::Restrict,
+            Token::Signed,
+            Token::Sizeof,
+            Token::Static,
+            Token::Struct,
+            Token::Switch,
+            Token::Typedef,
+            Token::Union,
+            Token::Unsigned,
+            Token::Volatile,
+            Token::Alignof,
+            Token::Alignas,
+            Token::Alignof,
+            Token::Atomic,
+            Token::Bool,
+            Token::Complex,
+            Token::Generic,
+            Token::Imaginary,
+            Token::Noreturn,
+            Token::StaticAssert,
+            Token::ThreadLocal,
+            identifier("Int"),
+            identifier("_bool"),
+            Token::Eof,
+        ];
+        for token in cases {
+            assert_eq!(lexer.next().unwrap().token, token);
+        }
+    }
+
+    #[test]
+    fn test_span() {
+        let input = String::from("int main() {\n    // comment\n    return 42;\n}");
+        let mut lexer = Lexer::with_file_id(input, FileId(3));
+        let cases = vec![
+            (Token::Int, Span::new(FileId(3), 0, 3, 1, 1)),
+            (
+                Token::Identifier(String::from("main")),
+                Span::new(FileId(3), 4, 8, 1, 5),
+            ),
+            (Token::LParen, Span::new(FileId(3), 8, 9, 1, 9)),
+            (Token::RParen, Span::new(FileId(3), 9, 10, 1, 10)),
+            (Token::LBrace, Span::new(FileId(3), 11, 12, 1, 12)),
+            (Token::Return, Span::new(FileId(3), 32, 38, 3, 5)),
+            (
+                Token::Integer(42, IntegerType::Int),
+                Span::new(FileId(3), 39, 41, 3, 12),
+            ),
+            (Token::SemiColon, Span::new(FileId(3), 41, 42, 3, 14)),
+            (Token::RBrace, Span::new(FileId(3), 43, 44, 4, 1)),
+        ];
+        for (token, span) in cases {
+            assert_eq!(lexer.next().unwrap(), SpannedToken { token, span });
+        }
+        assert_eq!(lexer.next().unwrap().token, Token::Eof);
+    }
+
+    #[test]
+    fn test_utf8() {
+        let input = String::from("/* 日本語 */ a // ü\n\"é\" b // end");
+        let mut lexer = Lexer::with_file_id(input, FileId(0));
+        let cases = vec![
+            (
+                Token::Identifier(String::from("a")),
+                Span::new(FileId(0), 16, 17, 1, 17),
+            ),
+            (
+                Token::String("é".as_bytes().to_vec()),
+                Span::new(FileId(0), 24, 28, 2, 1),
+            ),
+            (
+                Token::Identifier(String::from("b")),
+                Span::new(FileId(0), 29, 30, 2, 6),
+            ),
+        ];
+        for (token, span) in cases {
+            assert_eq!(lexer.next().unwrap(), SpannedToken { token, span });
+        }
+        assert_eq!(lexer.next().unwrap().token, Token::Eof);
+    }
+
+    #[test]
+    fn test_errors() {
+        // lexing goes on after each error, with the malformed literals.
+        let input = String::from("a @ 'bc' \"d\\q\" e\n\"f\ng /* h");
+        let mut lexer = Lexer::with_file_id(input, FileId(0));
+        let cases = vec![
+            Ok(Token::Identifier(String::from("a"))),
+            Err(LexError {
+                kind: LexErrorKind::InvalidCharacter('@'),
+                span: Span::new(FileId(0), 2, 3, 1, 3),
+            }),
+            Err(LexError {
+                kind: LexErrorKind::MultiCharacterConstant,
+                span: Span::new(FileId(0), 4, 8, 1, 5),
+            }),
+            Ok(Token::Character(b'b')),
+            Err(LexError {
+                kind: LexErrorKind::UnknownEscape('q'),
+                span: Span::new(FileId(0), 11, 13, 1, 12),
+            }),
+            Ok(Token::String(b"dq".to_vec())),
+            Ok(Token::Identifier(String::from("e"))),
+            Err(LexError {
+                kind: LexErrorKind::UnterminatedLiteral('"'),
+                span: Span::new(FileId(0), 17, 18, 2, 1),
+            }),
+            Ok(Token::String(b"f".to_vec())),
+            Ok(Token::Identifier(String::from("g"))),
+            Err(LexError {
+                kind: LexErrorKind::UnterminatedComment,
+                span: Span::new(FileId(0), 22, 24, 3, 3),
+            }),
+            Ok(Token::Eof),
+        ];
+        for expected in cases {
+            assert_eq!(lexer.next().map(|t| t.token), expected);
+        }
+    }
+
+    #[test]
+    fn test_line_marker() {
+        let input = String::from("# 1 \"main.c\"\nint\n# 7 \"foo.h\"\n  a;\n# 3 \"main.c\"\n#");
+        let file_ids = HashMap::from([
+            (String::from("main.c"), FileId(0)),
+            (String::from("foo.h"), FileId(1)),
+        ]);
+        let mut lexer = Lexer::new(input).with_file_ids(file_ids);
+        let cases = vec![
+            (Token::Int, FileId(0), 1, 1),
+            (Token::Identifier(String::from("a")), FileId(1), 7, 3),
+            (Token::SemiColon, FileId(1), 7, 4),
+        ];
+        for (token, file_id, line, column) in cases {
+            let t = lexer.next().unwrap();
+            assert_eq!(t.token, token);
+            assert_eq!(
+                (t.span.file_id, t.span.line, t.span.column),
+                (file_id, line, column)
+            );
         }
+        // a `#` which does not start a marker is still an error.
+        let error = lexer.next().unwrap_err();
+        assert_eq!(error.kind, LexErrorKind::InvalidCharacter('#'));
+        assert_eq!(error.span.line, 3);
+        assert_eq!(lexer.next().unwrap().token, Token::Eof);
     }
 }
@@ -0,0 +1,216 @@
+use ast::{FloatType, IntegerType};
+use span::Span;
+
+use crate::tokens::Token;
+use crate::{LexErrorKind, Lexer};
+
+impl Lexer {
+    /// integer or floating constant, see C11 6.4.4.1 and 6.4.4.2.
+    pub(crate) fn consume_number(&mut self) -> Token {
+        let (start, line, column) = (self.position, self.line, self.column);
+        // a preprocessing number: digits, letters, `.`, and signs after an exponent.
+        while self.ch.is_ascii_alphanumeric() || self.ch == '_' || self.ch == '.' {
+            let exponent = matches!(self.ch, 'e' | 'E' | 'p' | 'P');
+            self.consume_char();
+            if exponent && (self.ch == '+' || self.ch == '-') {
+                self.consume_char();
+            }
+        }
+        let text = self.input[start..self.position].to_string();
+        let span = Span::new(self.file_id, start, self.position, line, column);
+
+        let result = match text.get(..2) {
+            Some("0x" | "0X") if text.contains(['.', 'p', 'P']) => parse_hex_float(&text[2..]),
+            Some("0x" | "0X") => parse_integer(&text[2..], 16),
+            Some("0b" | "0B") => parse_integer(&text[2..], 2),
+            _ if text.contains(['.', 'e', 'E']) => parse_decimal_float(&text),
+            _ if text.starts_with('0') => parse_integer(&text, 8),
+            _ => parse_integer(&text, 10),
+        };
+        match result {
+            Ok(token) => token,
+            Err(kind) => {
+                self.report_error(span, kind);
+                Token::Integer(0, IntegerType::Int)
+            }
+        }
+    }
+}
+
+/// `digits` in `radix` followed by an optional `u`, `l` or `ll` suffix.
+fn parse_integer(text: &str, radix: u32) -> Result<Token, LexErrorKind> {
+    let end = text
+        .find(|c: char| !c.is_ascii_hexdigit() || (radix != 16 && c.is_ascii_alphabetic()))
+        .unwrap_or(text.len());
+    let (digits, suffix) = text.split_at(end);
+    if let Some(c) = digits.chars().find(|c| c.to_digit(radix).is_none()) {
+        let base = match radix {
+            2 => "binary",
+            8 => "octal",
+            _ => "decimal",
+        };
+        return Err(LexErrorKind::InvalidDigit { digit: c, base });
+    }
+    if digits.is_empty() {
+        return Err(LexErrorKind::MissingDigits);
+    }
+    let Some((unsigned, long)) = integer_suffix(suffix) else {
+        return Err(LexErrorKind::InvalidIntegerSuffix(suffix.to_string()));
+    };
+    let value = u64::from_str_radix(digits, radix).map_err(|_| LexErrorKind::IntegerTooLarge)?;
+    Ok(Token::Integer(
+        value,
+        integer_type(value, radix == 10, unsigned, long),
+    ))
+}
+
+/// `(unsigned, number of l)` of an integer suffix such as `ul` or `LLU`.
+fn integer_suffix(suffix: &str) -> Option<(bool, u8)> {
+    let (unsigned, rest) = match suffix
+        .strip_prefix(['u', 'U'])
+        .or_else(|| suffix.strip_suffix(['u', 'U']))
+    {
+        Some(rest) => (true, rest),
+        None => (false, suffix),
+    };
+    let long = match rest {
+        "" => 0,
+        "l" | "L" => 1,
+        "ll" | "LL" => 2,
+        _ => return None,
+    };
+    Some((unsigned, long))
+}
+
+/// first type in the list of C11 6.4.4.1p5 which can represent `value`.
+fn integer_type(value: u64, decimal: bool, unsigned: bool, long: u8) -> IntegerType {
+    use IntegerType::*;
+    let candidates: &[IntegerType] = match (unsigned, long) {
+        (false, 0) if decimal => &[Int, Long, LongLong],
+        (false, 0) => &[
+            Int,
+            UnsignedInt,
+            Long,
+            UnsignedLong,
+            LongLong,
+            UnsignedLongLong,
+        ],
+        (false, 1) if decimal => &[Long, LongLong],
+        (false, 1) => &[Long, UnsignedLong, LongLong, UnsignedLongLong],
+        (false, _) if decimal => &[LongLong],
+        (false, _) => &[LongLong, UnsignedLongLong],
+        (true, 0) => &[UnsignedInt, UnsignedLong, UnsignedLongLong],
+        (true, 1) => &[UnsignedLong, UnsignedLongLong],
+        (true, _) => &[UnsignedLongLong],
+    };
+    // a decimal constant too large for every signed type is taken as unsigned, like GCC does.
+    candidates
+        .iter()
+        .copied()
+        .find(|t| value <= t.max())
+        .unwrap_or(UnsignedLongLong)
+}
+
+/// `f`/`F` for `float`, `l`/`L` for `long double`, nothing for `double`.
+fn float_suffix(text: &str) -> (&str, FloatType) {
+    match text.strip_suffix(['f', 'F']) {
+        Some(rest) => (rest, FloatType::Float),
+        None => match text.strip_suffix(['l', 'L']) {
+            Some(rest) => (rest, FloatType::LongDouble),
+            None => (text, FloatType::Double),
+        },
+    }
+}
+
+/// `1.5`, `.5`, `1e-3`, `2.f` and the like.
+fn parse_decimal_float(text: &str) -> Result<Token, LexErrorKind> {
+    let (number, type_) = float_suffix(text);
+    let valid = number
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'));
+    match number.parse::<f64>() {
+        Ok(value) if valid => Ok(Token::Floating(value, type_)),
+        _ => Err(LexErrorKind::InvalidFloat(text.to_string())),
+    }
+}
+
+/// `1.8p4` after the `0x`, the exponent is a power of 2 and cannot be left out.
+fn parse_hex_float(text: &str) -> Result<Token, LexErrorKind> {
+    let invalid = || LexErrorKind::InvalidFloat(format!("0x{}", text));
+    let (number, type_) = float_suffix(text);
+    let Some((mantissa, exponent)) = number.split_once(['p', 'P']) else {
+        return Err(invalid());
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if integer.is_empty() && fraction.is_empty() {
+        return Err(invalid());
+    }
+    let mut value = 0.0;
+    for c in integer.chars().chain(fraction.chars()) {
+        value = value * 16.0 + c.to_digit(16).ok_or_else(invalid)? as f64;
+    }
+    let exponent = exponent.parse::<i32>().map_err(|_| invalid())?;
+    let value = value * 2f64.powi(exponent - 4 * fraction.len() as i32);
+    Ok(Token::Floating(value, type_))
+}
+
+#[cfg(test)]
+mod test {
+    use ast::{FloatType, IntegerType};
+
+    use crate::tokens::Token;
+    use crate::Lexer;
+
+    #[test]
+    fn test_numbers() {
+        let input = String::from(
+            "0 42 0x1F 0XffU 017 0b101 10l 10UL 10llu 2147483648 0x80000000 \
+             4294967296u 18446744073709551615u 1.5 .5 1e-3 2.f 1.5E+3L 0x1p4 0x1.8p-1f",
+        );
+        let mut lexer = Lexer::new(input);
+        let cases = vec![
+            Token::Integer(0, IntegerType::Int),
+            Token::Integer(42, IntegerType::Int),
+            Token::Integer(31, IntegerType::Int),
+            Token::Integer(255, IntegerType::UnsignedInt),
+            Token::Integer(15, IntegerType::Int),
+            Token::Integer(5, IntegerType::Int),
+            Token::Integer(10, IntegerType::Long),
+            Token::Integer(10, IntegerType::UnsignedLong),
+            Token::Integer(10, IntegerType::UnsignedLongLong),
+            Token::Integer(2147483648, IntegerType::Long),
+            Token::Integer(0x80000000, IntegerType::UnsignedInt),
+            Token::Integer(4294967296, IntegerType::UnsignedLong),
+            Token::Integer(u64::MAX, IntegerType::UnsignedLong),
+            Token::Floating(1.5, FloatType::Double),
+            Token::Floating(0.5, FloatType::Double),
+            Token::Floating(0.001, FloatType::Double),
+            Token::Floating(2.0, FloatType::Float),
+            Token::Floating(1500.0, FloatType::LongDouble),
+            Token::Floating(16.0, FloatType::Double),
+            Token::Floating(0.75, FloatType::Float),
+            Token::Eof,
+        ];
+        for token in cases {
+            assert_eq!(lexer.next().unwrap().token, token);
+        }
+
+        let cases = vec![
+            ("09", "invalid digit `9` in octal constant"),
+            ("0b12", "invalid digit `2` in binary constant"),
+            ("0x", "expected digits after the base prefix"),
+            ("10lul", "invalid suffix `lul` on integer constant"),
+            ("1a", "invalid suffix `a` on integer constant"),
+            (
+                "18446744073709551616",
+                "integer constant is too large for its type",
+            ),
+            ("1.5.2", "invalid floating constant `1.5.2`"),
+            ("0x1.8", "invalid floating constant `0x1.8`"),
+        ];
+        for (input, message) in cases {
+            let error = Lexer::new(String::from(input)).next().unwrap_err();
+            assert_eq!(error.kind.to_string(), message, "{}", input);
+        }
+    }
+}
@@ -1,3 +1,8 @@
+use std::fmt;
+
+use ast::{FloatType, IntegerType};
+use span::Span;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Token {
     Plus,
@@ -21,10 +26,40 @@ pub enum Token {
     Assignment,
     SemiColon,
     Comma,
+    Percent,
+    Increment,
+    Decrement,
+    PlusAssignment,
+    MinusAssignment,
+    AsteriskAssignment,
+    SlashAssignment,
+    PercentAssignment,
+    LShiftAssignment,
+    RShiftAssignment,
+    AmpersandAssignment,
+    PipeAssignment,
+    CaretAssignment,
+    And,
+    Or,
+    LShift,
+    RShift,
+    Pipe,
+    Caret,
+    Tilde,
+    Question,
+    Colon,
+    Arrow,
+    Dot,
+    Ellipsis,
     Eof,
 
-    Integer(i32),
-    String(String),
+    /// value and type of an integer constant, e.g. `10ul`.
+    Integer(u64, IntegerType),
+    Floating(f64, FloatType),
+    /// `'a'`, the value of its single byte.
+    Character(u8),
+    /// contents of a string literal with escape sequences decoded.
+    String(Vec<u8>),
     Identifier(String),
     Return,
     If,
@@ -38,4 +73,156 @@ pub enum Token {
     Long,
     Float,
     Double,
+    Auto,
+    Break,
+    Case,
+    Const,
+    Continue,
+    Default,
+    Do,
+    Enum,
+    Extern,
+    Goto,
+    Inline,
+    Register,
+    Restrict,
+    Signed,
+    Sizeof,
+    Static,
+    Struct,
+    Switch,
+    Typedef,
+    Union,
+    Unsigned,
+    Volatile,
+    Alignas,
+    Alignof,
+    Atomic,
+    Bool,
+    Complex,
+    Generic,
+    Imaginary,
+    Noreturn,
+    StaticAssert,
+    ThreadLocal,
+}
+
+impl fmt::Display for Token {
+    /// source spelling of the token, as used in diagnostics.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Token::Plus => "+",
+            Token::Minus => "-",
+            Token::Slash => "/",
+            Token::Asterisk => "*",
+            Token::LParen => "(",
+            Token::RParen => ")",
+            Token::LBrace => "{",
+            Token::RBrace => "}",
+            Token::RBracket => "]",
+            Token::LBracket => "[",
+            Token::Gt => ">",
+            Token::Lt => "<",
+            Token::GtEq => ">=",
+            Token::LtEq => "<=",
+            Token::Eq => "==",
+            Token::NotEq => "!=",
+            Token::Not => "!",
+            Token::Ampersand => "&",
+            Token::Assignment => "=",
+            Token::SemiColon => ";",
+            Token::Comma => ",",
+            Token::Percent => "%",
+            Token::Increment => "++",
+            Token::Decrement => "--",
+            Token::PlusAssignment => "+=",
+            Token::MinusAssignment => "-=",
+            Token::AsteriskAssignment => "*=",
+            Token::SlashAssignment => "/=",
+            Token::PercentAssignment => "%=",
+            Token::LShiftAssignment => "<<=",
+            Token::RShiftAssignment => ">>=",
+            Token::AmpersandAssignment => "&=",
+            Token::PipeAssignment => "|=",
+            Token::CaretAssignment => "^=",
+            Token::And => "&&",
+            Token::Or => "||",
+            Token::LShift => "<<",
+            Token::RShift => ">>",
+            Token::Pipe => "|",
+            Token::Caret => "^",
+            Token::Tilde => "~",
+            Token::Question => "?",
+            Token::Colon => ":",
+            Token::Arrow => "->",
+            Token::Dot => ".",
+            Token::Ellipsis => "...",
+            Token::Eof => return write!(f, "end of file"),
+            Token::Integer(n, type_) => return write!(f, "{}{}", n, type_.suffix()),
+            Token::Floating(n, type_) => {
+                let suffix = type_.suffix();
+                return write!(f, "{:?}{}", n, suffix);
+            }
+            Token::Character(c) => {
+                return write!(f, "'{}'", std::ascii::escape_default(*c));
+            }
+            Token::String(s) => {
+                let s = s.iter().flat_map(|b| std::ascii::escape_default(*b));
+                return write!(f, "\"{}\"", s.map(char::from).collect::<String>());
+            }
+            Token::Identifier(name) => name,
+            Token::Return => "return",
+            Token::If => "if",
+            Token::Else => "else",
+            Token::While => "while",
+            Token::For => "for",
+            Token::Void => "void",
+            Token::Char => "char",
+            Token::Short => "short",
+            Token::Int => "int",
+            Token::Long => "long",
+            Token::Float => "float",
+            Token::Double => "double",
+            Token::Auto => "auto",
+            Token::Break => "break",
+            Token::Case => "case",
+            Token::Const => "const",
+            Token::Continue => "continue",
+            Token::Default => "default",
+            Token::Do => "do",
+            Token::Enum => "enum",
+            Token::Extern => "extern",
+            Token::Goto => "goto",
+            Token::Inline => "inline",
+            Token::Register => "register",
+            Token::Restrict => "restrict",
+            Token::Signed => "signed",
+            Token::Sizeof => "sizeof",
+            Token::Static => "static",
+            Token::Struct => "struct",
+            Token::Switch => "switch",
+            Token::Typedef => "typedef",
+            Token::Union => "union",
+            Token::Unsigned => "unsigned",
+            Token::Volatile => "volatile",
+            Token::Alignas => "_Alignas",
+            Token::Alignof => "_Alignof",
+            Token::Atomic => "_Atomic",
+            Token::Bool => "_Bool",
+            Token::Complex => "_Complex",
+            Token::Generic => "_Generic",
+            Token::Imaginary => "_Imaginary",
+            Token::Noreturn => "_Noreturn",
+            Token::StaticAssert => "_Static_assert",
+            Token::ThreadLocal => "_Thread_local",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// token together with the location it was read from.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
 }
@@ -1,19 +1,18 @@
-use ast::Statement;
+use ast::{fold_integer, Statement};
+use diagnostic::Diagnostic;
 use lex::tokens::Token;
 
+use crate::jump::{misplaced, Switch};
 use crate::{Parser, Precedence};
 
 impl Parser {
-    pub(super) fn parse_if_statement(&mut self) -> Result<Statement, String> {
+    pub(super) fn parse_if_statement(&mut self) -> Result<Statement, Diagnostic> {
         self.next_token(); // skip 'if'
 
         if self.current_token == Token::LParen {
             self.next_token();
         } else {
-            return Err(format!(
-                "expected token '(' but got {:?}",
-                self.current_token
-            ));
+            return Err(self.unexpected_current("`(`"));
         }
 
         let condition = self.parse_expression(Precedence::Lowest)?;
@@ -22,10 +21,7 @@ impl Parser {
             self.next_token(); // skip current
             self.next_token(); // skip ')'
         } else {
-            return Err(format!(
-                "expected token ')' but got {:?}",
-                self.peeked_token
-            ));
+            return Err(self.unexpected_peeked("`)`"));
         }
 
         let consequence = self.parse_statement()?;
@@ -44,12 +40,100 @@ impl Parser {
             alternative: alternative.map(Box::new),
         })
     }
+
+    pub(super) fn parse_switch_statement(&mut self) -> Result<Statement, Diagnostic> {
+        self.next_token(); // skip 'switch'
+
+        if self.current_token == Token::LParen {
+            self.next_token();
+        } else {
+            return Err(self.unexpected_current("`(`"));
+        }
+
+        let condition = self.parse_expression(Precedence::Lowest)?;
+
+        if self.peeked_token == Token::RParen {
+            self.next_token(); // skip current
+            self.next_token(); // skip ')'
+        } else {
+            return Err(self.unexpected_peeked("`)`"));
+        }
+
+        self.jumps.switches.push(Switch::default());
+        let body = self.parse_statement();
+        let switch = self.jumps.switches.pop().unwrap_or_default();
+        Ok(Statement::Switch {
+            condition,
+            body: Box::new(body?),
+            cases: switch.cases.into_iter().map(|(value, _)| value).collect(),
+            default: switch.default.is_some(),
+        })
+    }
+
+    /// `case value: statement` or `default: statement` in the innermost switch.
+    pub(super) fn parse_case_statement(&mut self) -> Result<Statement, Diagnostic> {
+        let span = self.current_span;
+        let value = match self.current_token {
+            Token::Case => {
+                self.next_token(); // skip 'case'
+                let expr = self.parse_expression(Precedence::Assignment)?;
+                let Some(value) = fold_integer(&expr) else {
+                    return Err(Diagnostic::error(format!(
+                        "case value `{}` is not an integer constant expression",
+                        expr
+                    ))
+                    .with_code("E0109")
+                    .with_primary_label(span, "in this case label"));
+                };
+                Some(value)
+            }
+            _ => None,
+        };
+        let Some(switch) = self.jumps.switches.last_mut() else {
+            let message = match value {
+                Some(_) => "`case` statement not in switch statement",
+                None => "`default` statement not in switch statement",
+            };
+            return Err(misplaced(message, span));
+        };
+
+        let previous = match value {
+            Some(value) => switch
+                .cases
+                .iter()
+                .find(|(v, _)| *v == value)
+                .map(|(_, s)| *s),
+            None => switch.default,
+        };
+        if let Some(previous) = previous {
+            let message = match value {
+                Some(value) => format!("duplicate case value `{}`", value),
+                None => String::from("multiple default labels in one switch"),
+            };
+            return Err(Diagnostic::error(message)
+                .with_code("E0112")
+                .with_primary_label(span, "duplicate label here")
+                .with_secondary_label(previous, "previous label here"));
+        }
+        match value {
+            Some(value) => switch.cases.push((value, span)),
+            None => switch.default = Some(span),
+        }
+
+        if self.peeked_token != Token::Colon {
+            return Err(self.unexpected_peeked("`:`"));
+        }
+        self.next_token();
+        self.next_token(); // skip ':'
+        let body = Box::new(self.parse_statement()?);
+        Ok(Statement::Case { value, body })
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use ast::{BinaryOperator, Expression, Statement, Type, TypeEnum};
+    use ast::{BinaryOperator, ExpressionKind, IntegerType, Statement, Type, TypeEnum};
     use lex::Lexer;
 
     #[test]
@@ -60,21 +144,27 @@ mod test {
                 vec![
                     Statement::InitDeclaration {
                         name: String::from("a"),
-                        offset: 8,
+                        offset: 0,
                         type_: Type::Primitive(TypeEnum::Int),
-                        init: Some(Expression::Integer(0)),
+                        init: Some(ExpressionKind::Integer(0, IntegerType::Int).into()),
                     },
                     Statement::If {
-                        condition: Expression::Binary {
-                            lhs: Box::new(Expression::LocalVariable {
-                                name: String::from("a"),
-                                offset: 8,
-                                type_: Type::Primitive(TypeEnum::Int),
-                            }),
+                        condition: ExpressionKind::Binary {
+                            lhs: Box::new(
+                                ExpressionKind::LocalVariable {
+                                    name: String::from("a"),
+                                    offset: 0,
+                                    type_: Type::Primitive(TypeEnum::Int),
+                                }
+                                .into(),
+                            ),
                             op: BinaryOperator::Eq,
-                            rhs: Box::new(Expression::Integer(0)),
-                        },
-                        consequence: Box::new(Statement::Return(Expression::Integer(0))),
+                            rhs: Box::new(ExpressionKind::Integer(0, IntegerType::Int).into()),
+                        }
+                        .into(),
+                        consequence: Box::new(Statement::Return(Some(
+                            ExpressionKind::Integer(0, IntegerType::Int).into(),
+                        ))),
                         alternative: None,
                     },
                 ],
@@ -84,22 +174,30 @@ mod test {
                 vec![
                     Statement::InitDeclaration {
                         name: String::from("a"),
-                        offset: 8,
+                        offset: 0,
                         type_: Type::Primitive(TypeEnum::Int),
-                        init: Some(Expression::Integer(0)),
+                        init: Some(ExpressionKind::Integer(0, IntegerType::Int).into()),
                     },
                     Statement::If {
-                        condition: Expression::Binary {
-                            lhs: Box::new(Expression::LocalVariable {
-                                name: String::from("a"),
-                                offset: 8,
-                                type_: Type::Primitive(TypeEnum::Int),
-                            }),
+                        condition: ExpressionKind::Binary {
+                            lhs: Box::new(
+                                ExpressionKind::LocalVariable {
+                                    name: String::from("a"),
+                                    offset: 0,
+                                    type_: Type::Primitive(TypeEnum::Int),
+                                }
+                                .into(),
+                            ),
                             op: BinaryOperator::Eq,
-                            rhs: Box::new(Expression::Integer(0)),
-                        },
-                        consequence: Box::new(Statement::Return(Expression::Integer(0))),
-                        alternative: Some(Box::new(Statement::Return(Expression::Integer(1)))),
+                            rhs: Box::new(ExpressionKind::Integer(0, IntegerType::Int).into()),
+                        }
+                        .into(),
+                        consequence: Box::new(Statement::Return(Some(
+                            ExpressionKind::Integer(0, IntegerType::Int).into(),
+                        ))),
+                        alternative: Some(Box::new(Statement::Return(Some(
+                            ExpressionKind::Integer(1, IntegerType::Int).into(),
+                        )))),
                     },
                 ],
             ),
@@ -108,7 +206,61 @@ mod test {
         for (input, expected) in cases {
             let lexer = Lexer::new(input);
             let mut parser = Parser::new(lexer);
-            assert_eq!(parser.parse().unwrap().statements, expected);
+            parser.enter_scope();
+            assert_eq!(parser.parse().unwrap().0.statements, expected);
         }
     }
+
+    #[test]
+    fn test_parse_switch_statement() {
+        let input = String::from(
+            "int f(int x) { switch (x) { case 1: case 2 + 1: break; default: switch (x) { case 1: return 0; } } return 1; }",
+        );
+        let statements = Parser::new(Lexer::new(input)).parse().unwrap().0.statements;
+        let Statement::FunctionDefinition { body, .. } = &statements[0] else {
+            panic!("expected a function definition");
+        };
+        let Statement::Switch {
+            body,
+            cases,
+            default,
+            ..
+        } = &body[0]
+        else {
+            panic!("expected a switch");
+        };
+        // the labels of the inner switch are its own.
+        assert_eq!((&cases[..], *default), (&[1, 3][..], true));
+        assert_eq!(
+            **body,
+            Statement::Block(vec![
+                Statement::Case {
+                    value: Some(1),
+                    body: Box::new(Statement::Case {
+                        value: Some(3),
+                        body: Box::new(Statement::Break),
+                    }),
+                },
+                Statement::Case {
+                    value: None,
+                    body: Box::new(Statement::Switch {
+                        condition: ExpressionKind::LocalVariable {
+                            name: String::from("x"),
+                            offset: 0,
+                            type_: Type::Primitive(TypeEnum::Int),
+                        }
+                        .into(),
+                        body: Box::new(Statement::Block(vec![Statement::Case {
+                            value: Some(1),
+                            body: Box::new(Statement::Return(Some(
+                                ExpressionKind::Integer(0, IntegerType::Int).into()
+                            ))),
+                        }])),
+                        cases: vec![1],
+                        default: false,
+                    }),
+                },
+            ])
+        );
+    }
 }
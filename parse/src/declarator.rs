@@ -0,0 +1,568 @@
+use ast::{fold_integer, Qualifiers, Type, TypeEnum};
+use diagnostic::Diagnostic;
+use lex::tokens::Token;
+use span::Span;
+
+use crate::{Parser, Precedence};
+
+/// identifier declared by a declarator, with its span.
+pub(crate) type Name = (String, Span);
+
+/// parameter of a function declarator, named in a definition.
+pub(crate) struct Parameter {
+    pub(crate) type_: Type,
+    pub(crate) name: Option<Name>,
+}
+
+/// declarator applied to its base type.
+pub(crate) struct Declarator {
+    pub(crate) type_: Type,
+    pub(crate) name: Option<Name>,
+    /// parameters of the function the declarator declares, if it declares one.
+    pub(crate) parameters: Option<Vec<Parameter>>,
+    /// span of the `[` of an array declared without a size, which its initializer gives. the
+    /// size of `type_` is 0 until then.
+    pub(crate) size_missing: Option<Span>,
+}
+
+/// what a declarator derives from the type it is applied to.
+enum Derivation {
+    Pointer(Qualifiers),
+    /// `None` for `[]`, with the span of the `[`.
+    Array(Option<i32>, Span),
+    /// `prototyped` is false for `()`, which says nothing of the parameters.
+    Function {
+        parameters: Vec<Parameter>,
+        variadic: bool,
+        prototyped: bool,
+    },
+}
+
+impl Parser {
+    /// type named by the qualifiers and the type specifier starting at the current token, e.g.
+    /// `const int` or `struct s { int a; }`. the last token of them is left as the current one.
+    pub(crate) fn parse_base_type(&mut self) -> Result<Type, Diagnostic> {
+        let mut qualifiers = Qualifiers::default();
+        while let Some(qualifier) = qualifier(&self.current_token) {
+            qualifiers = qualifiers.union(qualifier);
+            self.next_token();
+        }
+        let type_ = match self.current_token {
+            ref token if is_arithmetic_specifier(token) => {
+                Type::Primitive(self.parse_arithmetic_type(&mut qualifiers)?)
+            }
+            Token::Struct | Token::Union => self.parse_struct_type()?,
+            Token::Enum => self.parse_enum_type()?,
+            _ => match self.typedef_type(&self.current_token) {
+                Some(type_) => type_,
+                None => return Err(self.unexpected_current("type")),
+            },
+        };
+        Ok(type_.qualified(qualifiers.union(self.parse_qualifiers())))
+    }
+
+    /// arithmetic type or `void` named by the specifiers from the current token on, in any
+    /// order, e.g. `long unsigned int`. the qualifiers between them are added to `qualifiers`.
+    fn parse_arithmetic_type(
+        &mut self,
+        qualifiers: &mut Qualifiers,
+    ) -> Result<TypeEnum, Diagnostic> {
+        let mut specifiers = vec![self.current_token.clone()];
+        let mut type_ = arithmetic_type(&specifiers);
+        loop {
+            if let Some(qualifier) = qualifier(&self.peeked_token) {
+                *qualifiers = qualifiers.union(qualifier);
+            } else if is_arithmetic_specifier(&self.peeked_token) {
+                specifiers.push(self.peeked_token.clone());
+                type_ = arithmetic_type(&specifiers);
+                if type_.is_none() {
+                    let previous = specifiers[..specifiers.len() - 1]
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    return Err(Diagnostic::error(format!(
+                        "cannot combine `{}` with `{}`",
+                        self.peeked_token, previous
+                    ))
+                    .with_code("E0114")
+                    .with_primary_label(self.peeked_span, "invalid type specifier"));
+                }
+            } else {
+                break;
+            }
+            self.next_token();
+        }
+        // a single specifier always names a type.
+        Ok(type_.unwrap())
+    }
+
+    /// type name of a cast, e.g. `char *` or `int (*)[4]`, from its first token as the current
+    /// one to its last one.
+    pub(crate) fn parse_type_name(&mut self) -> Result<Type, Diagnostic> {
+        let base = self.parse_base_type()?;
+        match self.parse_declarator(base)? {
+            Declarator {
+                name: Some((name, span)),
+                ..
+            } => Err(Self::unexpected_token(
+                "`)`",
+                &Token::Identifier(name),
+                span,
+            )),
+            Declarator { type_, .. } => Ok(type_),
+        }
+    }
+
+    /// whether `token` starts a type name: a qualifier, a type specifier or a typedef name.
+    pub(crate) fn starts_type_name(&self, token: &Token) -> bool {
+        match token {
+            Token::Const
+            | Token::Volatile
+            | Token::Restrict
+            | Token::Struct
+            | Token::Union
+            | Token::Enum => true,
+            token if is_arithmetic_specifier(token) => true,
+            _ => self.typedef_type(token).is_some(),
+        }
+    }
+
+    /// type and name of a declarator of `base`, with the span of the name.
+    pub(crate) fn parse_type_declaration(
+        &mut self,
+        base: Type,
+    ) -> Result<(Type, String, Span), Diagnostic> {
+        match self.parse_declarator(base)? {
+            Declarator {
+                type_,
+                name: Some((name, span)),
+                ..
+            } => Ok((type_, name, span)),
+            _ => Err(self.unexpected_peeked("identifier")),
+        }
+    }
+
+    /// declarator of `base` with or without a name, e.g. `*p[3]`, `(*f)(int)` or the `*` of
+    /// `char *` in a prototype. it starts after the current token, which is left on the last
+    /// token of the declarator.
+    pub(crate) fn parse_declarator(&mut self, base: Type) -> Result<Declarator, Diagnostic> {
+        let declarator = self.parse_declarator_of(base, false)?;
+        match declarator.size_missing {
+            Some(span) => Err(size_missing(span)),
+            None => Ok(declarator),
+        }
+    }
+
+    /// declarator of a declaration, which may leave the size of an array to its initializer,
+    /// e.g. `a[]` of `int a[] = {1, 2, 3};`.
+    pub(crate) fn parse_init_declarator(&mut self, base: Type) -> Result<Declarator, Diagnostic> {
+        self.parse_declarator_of(base, false)
+    }
+
+    /// declarator of a parameter, or of anything else. arrays and functions are adjusted to
+    /// pointers in parameters.
+    fn parse_declarator_of(
+        &mut self,
+        base: Type,
+        parameter: bool,
+    ) -> Result<Declarator, Diagnostic> {
+        let (name, derivations) = self.parse_derivations()?;
+
+        // the derivations are from the name outwards, the type is built from the base inwards.
+        let mut type_ = base;
+        let mut parameters = None;
+        let mut missing = None;
+        for (i, derivation) in derivations.into_iter().enumerate().rev() {
+            let outermost = i == 0;
+            type_ = match derivation {
+                Derivation::Pointer(qualifiers) => {
+                    Type::Pointer(Box::new(type_)).qualified(qualifiers)
+                }
+                Derivation::Array(_, _) if parameter && outermost => Type::Pointer(Box::new(type_)),
+                Derivation::Array(Some(size), _) => Type::Array {
+                    type_: Box::new(type_),
+                    size,
+                },
+                Derivation::Array(None, span) if outermost => {
+                    missing = Some(span);
+                    Type::Array {
+                        type_: Box::new(type_),
+                        size: 0,
+                    }
+                }
+                Derivation::Array(None, span) => return Err(size_missing(span)),
+                Derivation::Function {
+                    parameters: list,
+                    variadic,
+                    prototyped,
+                } => {
+                    // the type of a function ignores the qualifiers of its parameters.
+                    let function = Type::Function {
+                        return_type: Box::new(type_),
+                        parameters: list.iter().map(|p| p.type_.unqualified().clone()).collect(),
+                        variadic,
+                        prototyped,
+                    };
+                    if !outermost {
+                        function
+                    } else if parameter {
+                        Type::Pointer(Box::new(function))
+                    } else {
+                        parameters = Some(list);
+                        function
+                    }
+                }
+            };
+        }
+        Ok(Declarator {
+            type_,
+            name,
+            parameters,
+            size_missing: missing,
+        })
+    }
+
+    /// name and derivations of a declarator, from the name outwards: `*a[3]` is an array of
+    /// pointers, `(*a)[3]` a pointer to an array.
+    fn parse_derivations(&mut self) -> Result<(Option<Name>, Vec<Derivation>), Diagnostic> {
+        let mut pointers = vec![];
+        while self.peeked_token == Token::Asterisk {
+            self.next_token();
+            pointers.push(Derivation::Pointer(self.parse_qualifiers()));
+        }
+
+        let (name, mut derivations) = match self.peeked_token.clone() {
+            Token::Identifier(name) => {
+                self.next_token();
+                (Some((name, self.current_span)), vec![])
+            }
+            Token::LParen => {
+                self.next_token();
+                if self.is_nested_declarator() {
+                    let nested = self.parse_derivations()?;
+                    if self.peeked_token != Token::RParen {
+                        return Err(self.unexpected_peeked("`)`"));
+                    }
+                    self.next_token();
+                    nested
+                } else {
+                    // parameters of an abstract declarator, e.g. `int (int)`.
+                    (None, vec![self.parse_parameters()?])
+                }
+            }
+            _ => (None, vec![]),
+        };
+
+        loop {
+            match self.peeked_token {
+                Token::LBracket => {
+                    self.next_token();
+                    let span = self.current_span;
+                    derivations.push(Derivation::Array(self.parse_array_size()?, span));
+                }
+                Token::LParen => {
+                    self.next_token();
+                    derivations.push(self.parse_parameters()?);
+                }
+                _ => break,
+            }
+        }
+        derivations.extend(pointers.into_iter().rev());
+        Ok((name, derivations))
+    }
+
+    /// whether the `(` just read opens a nested declarator rather than a parameter list.
+    fn is_nested_declarator(&self) -> bool {
+        match self.peeked_token {
+            Token::Asterisk | Token::LParen | Token::LBracket => true,
+            Token::Identifier(_) => !self.starts_type_name(&self.peeked_token),
+            _ => false,
+        }
+    }
+
+    /// size of an array from its `[` as the current token to its `]`, `None` when it is empty.
+    fn parse_array_size(&mut self) -> Result<Option<i32>, Diagnostic> {
+        if self.peeked_token == Token::RBracket {
+            self.next_token();
+            return Ok(None);
+        }
+        self.next_token();
+        let span = self.current_span;
+        let expr = self.parse_expression(Precedence::Assignment)?;
+        let size = match fold_integer(&expr).map(i32::try_from) {
+            Some(Ok(size)) if size >= 0 => size,
+            Some(_) => {
+                return Err(Diagnostic::error(format!("invalid array size `{}`", expr))
+                    .with_code("E0109")
+                    .with_primary_label(span, "the size must be between 0 and 2147483647"))
+            }
+            None => {
+                return Err(Diagnostic::error(format!(
+                    "array size `{}` is not an integer constant expression",
+                    expr
+                ))
+                .with_code("E0109")
+                .with_primary_label(span, "variable length arrays are not supported"))
+            }
+        };
+        if self.peeked_token != Token::RBracket {
+            return Err(self.unexpected_peeked("`]`"));
+        }
+        self.next_token();
+        Ok(Some(size))
+    }
+
+    /// function derivation of a parameter list, from its `(` as the current token to its `)`.
+    fn parse_parameters(&mut self) -> Result<Derivation, Diagnostic> {
+        let mut parameters = vec![];
+        let mut variadic = false;
+        if self.peeked_token == Token::RParen {
+            self.next_token();
+            return Ok(Derivation::Function {
+                parameters,
+                variadic,
+                prototyped: false,
+            });
+        }
+        loop {
+            self.next_token(); // skip '(' or ','
+            if self.current_token == Token::Ellipsis {
+                variadic = true;
+                if self.peeked_token != Token::RParen {
+                    return Err(self.unexpected_peeked("`)`"));
+                }
+                self.next_token();
+                break;
+            }
+            let base = self.parse_base_type()?;
+            let Declarator { type_, name, .. } = self.parse_declarator_of(base, true)?;
+            parameters.push(Parameter { type_, name });
+            self.next_token();
+            match self.current_token {
+                Token::Comma => {}
+                Token::RParen => break,
+                _ => return Err(self.unexpected_current("`,` or `)`")),
+            }
+        }
+
+        // `(void)` is an empty list.
+        if let [Parameter {
+            type_: Type::Primitive(TypeEnum::Void),
+            name: None,
+        }] = parameters[..]
+        {
+            parameters.clear();
+        }
+        Ok(Derivation::Function {
+            parameters,
+            variadic,
+            prototyped: true,
+        })
+    }
+
+    /// qualifiers among the next tokens, e.g. after a `*`.
+    fn parse_qualifiers(&mut self) -> Qualifiers {
+        let mut qualifiers = Qualifiers::default();
+        while let Some(qualifier) = qualifier(&self.peeked_token) {
+            qualifiers = qualifiers.union(qualifier);
+            self.next_token();
+        }
+        qualifiers
+    }
+}
+
+/// whether `token` is one of the specifiers naming an arithmetic type or `void`.
+fn is_arithmetic_specifier(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Void
+            | Token::Bool
+            | Token::Char
+            | Token::Short
+            | Token::Int
+            | Token::Long
+            | Token::Float
+            | Token::Double
+            | Token::Signed
+            | Token::Unsigned
+    )
+}
+
+/// type named by the arithmetic type `specifiers`, in any order. `None` if they do not name
+/// one, e.g. `unsigned double` or `short long`.
+fn arithmetic_type(specifiers: &[Token]) -> Option<TypeEnum> {
+    let count = |token: Token| specifiers.iter().filter(|s| **s == token).count();
+    let (signed, unsigned) = (count(Token::Signed), count(Token::Unsigned));
+    let sign = match (signed, unsigned) {
+        (0, 0) => None,
+        (1, 0) => Some(true),
+        (0, 1) => Some(false),
+        _ => return None,
+    };
+    // `int` is implied by the other integer specifiers, and by a sign alone.
+    let integer = |signed_type, unsigned_type| match sign {
+        Some(false) => Some(unsigned_type),
+        _ => Some(signed_type),
+    };
+    let counts = (
+        count(Token::Void) + count(Token::Bool) + count(Token::Float) + count(Token::Double),
+        count(Token::Char),
+        count(Token::Short),
+        count(Token::Int),
+        count(Token::Long),
+    );
+    match counts {
+        (1, 0, 0, 0, 0) if sign.is_none() => match specifiers.first() {
+            Some(Token::Void) => Some(TypeEnum::Void),
+            Some(Token::Bool) => Some(TypeEnum::Bool),
+            Some(Token::Float) => Some(TypeEnum::Float),
+            _ => Some(TypeEnum::Double),
+        },
+        (1, 0, 0, 0, 1) if sign.is_none() && count(Token::Double) == 1 => {
+            Some(TypeEnum::LongDouble)
+        }
+        (0, 1, 0, 0, 0) => match sign {
+            None => Some(TypeEnum::Char),
+            Some(true) => Some(TypeEnum::SignedChar),
+            Some(false) => Some(TypeEnum::UnsignedChar),
+        },
+        (0, 0, 1, 0 | 1, 0) => integer(TypeEnum::Short, TypeEnum::UnsignedShort),
+        (0, 0, 0, 0 | 1, 0) => integer(TypeEnum::Int, TypeEnum::UnsignedInt),
+        (0, 0, 0, 0 | 1, 1) => integer(TypeEnum::Long, TypeEnum::UnsignedLong),
+        (0, 0, 0, 0 | 1, 2) => integer(TypeEnum::LongLong, TypeEnum::UnsignedLongLong),
+        _ => None,
+    }
+}
+
+/// error for the `[` at `span` of an array whose size nothing gives.
+pub(crate) fn size_missing(span: Span) -> Diagnostic {
+    Diagnostic::error("array size missing")
+        .with_code("E0107")
+        .with_primary_label(span, "an array needs a size here")
+}
+
+/// error if an object or a result of `type_`, declared at `span`, would hold a `long double`.
+pub(crate) fn ensure_supported(type_: &Type, span: Span) -> Result<(), Diagnostic> {
+    match type_.unqualified() {
+        Type::Primitive(TypeEnum::LongDouble) => {
+            Err(Diagnostic::error("`long double` is not supported")
+                .with_code("E0300")
+                .with_primary_label(span, "declared `long double` here"))
+        }
+        Type::Array { type_, .. } => ensure_supported(type_, span),
+        _ => Ok(()),
+    }
+}
+
+fn qualifier(token: &Token) -> Option<Qualifiers> {
+    let mut qualifiers = Qualifiers::default();
+    match token {
+        Token::Const => qualifiers.const_ = true,
+        Token::Volatile => qualifiers.volatile = true,
+        Token::Restrict => qualifiers.restrict = true,
+        _ => return None,
+    }
+    Some(qualifiers)
+}
+
+#[cfg(test)]
+mod test {
+    use ast::Statement;
+    use lex::Lexer;
+
+    use super::*;
+
+    /// declared types of the variables of `input`, as they are displayed.
+    fn declared_types(input: &str) -> Vec<String> {
+        let statements = Parser::new(Lexer::new(input.to_string()))
+            .parse()
+            .unwrap()
+            .0
+            .statements;
+        let mut types = vec![];
+        for statement in statements {
+            let declarations = match statement {
+                Statement::Block(declarations) => declarations,
+                statement => vec![statement],
+            };
+            for declaration in declarations {
+                match declaration {
+                    Statement::GlobalDeclaration { type_, .. }
+                    | Statement::Declaration { type_, .. } => types.push(type_.to_string()),
+                    _ => panic!("expected a declaration"),
+                }
+            }
+        }
+        types
+    }
+
+    #[test]
+    fn test_parse_declarator() {
+        let cases = vec![
+            ("int (*p)[4];", vec!["int (*)[4]"]),
+            ("int *a[3];", vec!["int *[3]"]),
+            ("int a[2][3];", vec!["int[2][3]"]),
+            ("int (*fp)(int);", vec!["int (*)(int)"]),
+            ("int *f(char, ...);", vec!["int *(char, ...)"]),
+            ("const char *const s;", vec!["const char *const"]),
+            ("int a, *b, c[3];", vec!["int", "int *", "int[3]"]),
+            ("enum { N = 2 }; int a[N * 3];", vec!["int[6]"]),
+            (
+                "int f(int a[], int g(void));",
+                vec!["int (int *, int (*)(void))"],
+            ),
+            ("int f(const int a);", vec!["int (int)"]),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(declared_types(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_arithmetic_type() {
+        let cases = vec![
+            ("unsigned u;", "unsigned int"),
+            ("signed s;", "int"),
+            ("short int s;", "short"),
+            ("int unsigned short s;", "unsigned short"),
+            ("signed char c;", "signed char"),
+            ("char unsigned c;", "unsigned char"),
+            ("long int l;", "long"),
+            ("unsigned long ul;", "unsigned long"),
+            ("long long ll;", "long long"),
+            ("long unsigned int long ull;", "unsigned long long"),
+            ("_Bool b;", "_Bool"),
+            ("long const unsigned *p;", "const unsigned long *"),
+            ("extern long double d;", "long double"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(declared_types(input), vec![expected], "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_qualified_type() {
+        let input = String::from("volatile int const *restrict p;");
+        let statements = Parser::new(Lexer::new(input)).parse().unwrap().0.statements;
+        let Statement::GlobalDeclaration { type_, .. } = &statements[0] else {
+            panic!("expected a declaration");
+        };
+        let const_volatile = Qualifiers {
+            const_: true,
+            volatile: true,
+            restrict: false,
+        };
+        let restrict = Qualifiers {
+            restrict: true,
+            ..Qualifiers::default()
+        };
+        assert_eq!(
+            *type_,
+            Type::Pointer(Box::new(
+                Type::Primitive(TypeEnum::Int).qualified(const_volatile)
+            ))
+            .qualified(restrict)
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use ast::{fold_integer, Type, TypeEnum};
+use diagnostic::Diagnostic;
+use lex::tokens::Token;
+
+use crate::scope::Storage;
+use crate::{Parser, Precedence};
+
+impl Parser {
+    /// `enum tag`, `enum tag { A, B = 2 }` or `enum { ... }`, from the keyword as the current
+    /// token to the tag or the `}`. enums are `int`s and their enumerators integer constants.
+    pub(crate) fn parse_enum_type(&mut self) -> Result<Type, Diagnostic> {
+        let tag = match self.peeked_token.clone() {
+            Token::Identifier(tag) => {
+                self.next_token();
+                Some((tag, self.current_span))
+            }
+            _ => None,
+        };
+
+        if self.peeked_token != Token::LBrace {
+            let Some((tag, span)) = tag else {
+                return Err(self.unexpected_peeked("identifier or `{`"));
+            };
+            if !self.find_enum(&tag) {
+                return Err(
+                    Diagnostic::error(format!("use of undeclared enum `{}`", tag))
+                        .with_code("E0101")
+                        .with_primary_label(span, "not found in this scope"),
+                );
+            }
+            return Ok(Type::Primitive(TypeEnum::Int));
+        }
+
+        if let Some((tag, span)) = tag {
+            self.declare_enum(tag, span)?;
+        }
+        self.next_token(); // '{'
+        if let Err(e) = self.parse_enumerators() {
+            self.skip_body();
+            return Err(e);
+        }
+        Ok(Type::Primitive(TypeEnum::Int))
+    }
+
+    /// enumerators up to the `}` closing the list.
+    fn parse_enumerators(&mut self) -> Result<(), Diagnostic> {
+        let mut value = 0i64;
+        loop {
+            self.next_token();
+            let Token::Identifier(name) = self.current_token.clone() else {
+                // `}` after a trailing comma.
+                if self.current_token == Token::RBrace {
+                    return Ok(());
+                }
+                return Err(self.unexpected_current("identifier"));
+            };
+            let span = self.current_span;
+            if self.peeked_token == Token::Assignment {
+                self.next_token();
+                self.next_token();
+                let expr = self.parse_expression(Precedence::Assignment)?;
+                value = fold_integer(&expr).ok_or_else(|| {
+                    Diagnostic::error(format!(
+                        "value `{}` of `{}` is not an integer constant expression",
+                        expr, name
+                    ))
+                    .with_code("E0109")
+                    .with_primary_label(span, "enumerator declared here")
+                })?;
+            }
+            let type_ = Type::Primitive(TypeEnum::Int);
+            self.declare_name(name, type_, Storage::Enumerator(value), span)?;
+            value = value.wrapping_add(1);
+
+            self.next_token();
+            match self.current_token {
+                Token::Comma => {}
+                Token::RBrace => return Ok(()),
+                _ => return Err(self.unexpected_current("`,` or `}`")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ast::{Expression, ExpressionKind, IntegerType, Statement};
+    use lex::Lexer;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_enum_type() {
+        let input = String::from(
+            "enum e { A, B = 5, C, D = -1, E, F = C << 2 }; int f() { return A; return C; return D; return E; return F; }",
+        );
+        let statements = Parser::new(Lexer::new(input)).parse().unwrap().0.statements;
+        let Statement::FunctionDefinition { body, .. } = &statements[1] else {
+            panic!("expected a function definition");
+        };
+        let values = body
+            .iter()
+            .map(|s| match s {
+                Statement::Return(Some(Expression {
+                    kind: ExpressionKind::Integer(value, IntegerType::Int),
+                    ..
+                })) => *value as i64,
+                _ => panic!("expected a constant"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![0, 6, -1, 0, 24]);
+    }
+}
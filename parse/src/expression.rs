@@ -1,164 +1,345 @@
-use ast::{BinaryOperator, Expression, UnaryOperator};
+use ast::{
+    type_of, BinaryOperator, Expression, ExpressionKind, IntegerType, Type, TypeEnum, UnaryOperator,
+};
+use diagnostic::Diagnostic;
 use lex::tokens::Token;
+use span::Span;
 
-use crate::{LVar, Parser, Precedence};
+use crate::scope::{Storage, Variable};
+use crate::struct_::{incomplete, is_complete};
+use crate::{Parser, Precedence};
 
 impl Parser {
+    /// expression from the current token, up to an operator binding no tighter than
+    /// `precedence`. the recursion nests as deep as the expression: its parts are parsed by
+    /// functions of their own, whose results are passed on rather than unwrapped with `?`, so
+    /// that each level takes little of the stack in a debug build.
     pub(super) fn parse_expression(
         &mut self,
         precedence: Precedence,
-    ) -> Result<Expression, String> {
-        let mut expr = match self.current_token.clone() {
-            Token::Integer(n) => Expression::Integer(n),
-            Token::String(s) => Expression::String(s),
-            Token::LParen => self.parse_grouped_expression()?,
-            Token::Minus | Token::Asterisk | Token::Ampersand => self.parse_unary_expression()?,
-            Token::Identifier(name) => match self.peeked_token {
-                Token::LParen => {
-                    self.next_token(); // skip identifier
-                    self.parse_call_expression(name)?
-                }
-                _ => self.parse_identifier_expression(name)?,
-            },
-            Token::LBrace => self.parse_array_expression()?,
-
-            _ => return Err(format!("Invalid token: {:?}", self.current_token)),
-        };
-
-        while self.peeked_token != Token::Eof && precedence < self.peek_precedence() {
-            expr = match self.peeked_token {
-                Token::Assignment
-                | Token::Plus
-                | Token::Minus
-                | Token::Asterisk
-                | Token::Slash
-                | Token::Eq
-                | Token::NotEq
-                | Token::Lt
-                | Token::Gt
-                | Token::LtEq
-                | Token::GtEq => {
-                    self.next_token();
-                    self.parse_binary_expression(expr)?
-                }
-                Token::LBracket => {
-                    self.next_token();
-                    self.perse_index_expression(expr)?
-                }
-                _ => panic!(""), // TODO:
-            }
+    ) -> Result<Expression, Diagnostic> {
+        let mut expr = self.parse_prefix_expression();
+        while expr.is_ok() && self.peeked_token != Token::Eof && precedence < self.peek_precedence()
+        {
+            expr = expr.and_then(|expr| self.parse_infix_expression(expr));
         }
+        expr
+    }
 
-        Ok(expr)
+    /// operand starting at the current token, before any postfix or binary operator.
+    fn parse_prefix_expression(&mut self) -> Result<Expression, Diagnostic> {
+        let start = self.current_span;
+        let kind = match self.current_token.clone() {
+            Token::Integer(n, type_) => ExpressionKind::Integer(n, type_),
+            Token::Floating(n, type_) => ExpressionKind::Float(n, type_),
+            // `char` is signed, `'\xff'` is -1.
+            Token::Character(c) => ExpressionKind::Integer(c as i8 as u64, IntegerType::Int),
+            Token::String(s) => ExpressionKind::String(s),
+            Token::LParen if self.starts_type_name(&self.peeked_token) => {
+                return self.parse_cast_expression()
+            }
+            Token::LParen => return self.parse_grouped_expression(),
+            Token::Plus
+            | Token::Minus
+            | Token::Asterisk
+            | Token::Ampersand
+            | Token::Not
+            | Token::Tilde
+            | Token::Increment
+            | Token::Decrement => return self.parse_unary_expression(),
+            Token::Identifier(name) => return self.parse_identifier_expression(name),
+            Token::Sizeof | Token::Alignof => return self.parse_sizeof_expression(),
+            Token::LBrace => return self.parse_array_expression(),
+
+            _ => return Err(self.unexpected_current("expression")),
+        };
+        Ok(Expression::new(kind, start))
     }
 
-    pub(super) fn parse_unary_expression(&mut self) -> Result<Expression, String> {
-        match self.current_token {
-            Token::Minus => {
+    /// `expr` with the postfix or binary operator of the peeked token applied to it.
+    fn parse_infix_expression(&mut self, expr: Expression) -> Result<Expression, Diagnostic> {
+        match self.peeked_token {
+            Token::LBracket => {
                 self.next_token();
-                let expr = Box::new(self.parse_expression(Precedence::Product)?);
-                Ok(Expression::Unary {
-                    expr,
-                    op: UnaryOperator::Minus,
-                })
+                self.perse_index_expression(expr)
             }
-            Token::Asterisk => {
+            Token::LParen => {
                 self.next_token();
-                let expr = Box::new(self.parse_expression(Precedence::Product)?);
-                Ok(Expression::Unary {
-                    expr,
-                    op: UnaryOperator::Dereference,
-                })
+                self.parse_call_expression(expr)
             }
-            Token::Ampersand => {
+            Token::Dot | Token::Arrow => {
                 self.next_token();
-                let expr = Box::new(self.parse_expression(Precedence::Product)?);
-                Ok(Expression::Unary {
-                    expr,
-                    op: UnaryOperator::Reference,
+                let arrow = self.current_token == Token::Arrow;
+                self.parse_member_expression(expr, arrow)
+            }
+            Token::Question => {
+                self.next_token();
+                self.parse_conditional_expression(expr)
+            }
+            Token::Comma => {
+                self.next_token();
+                self.next_token(); // skip ','
+                let rhs = self.parse_expression(Precedence::Comma);
+                rhs.map(|rhs| {
+                    let span = expr.span.to(rhs.span);
+                    let kind = ExpressionKind::Comma {
+                        lhs: Box::new(expr),
+                        rhs: Box::new(rhs),
+                    };
+                    Expression::new(kind, span)
                 })
             }
-            _ => unreachable!(),
+            Token::Increment | Token::Decrement => {
+                self.next_token();
+                let op = match self.current_token {
+                    Token::Increment => UnaryOperator::PostIncrement,
+                    _ => UnaryOperator::PostDecrement,
+                };
+                let span = expr.span.to(self.current_span);
+                let kind = ExpressionKind::Unary {
+                    expr: Box::new(expr),
+                    op,
+                };
+                Ok(Expression::new(kind, span))
+            }
+            // every other token with a precedence is a binary operator.
+            _ => {
+                self.next_token();
+                self.parse_binary_expression(expr)
+            }
         }
     }
 
+    /// `kind` of an expression from `start` to the current token.
+    pub(crate) fn spanned(&self, kind: ExpressionKind, start: Span) -> Expression {
+        Expression::new(kind, start.to(self.current_span))
+    }
+
+    pub(super) fn parse_unary_expression(&mut self) -> Result<Expression, Diagnostic> {
+        let start = self.current_span;
+        let op = match self.current_token {
+            Token::Plus => UnaryOperator::Plus,
+            Token::Minus => UnaryOperator::Minus,
+            Token::Asterisk => UnaryOperator::Dereference,
+            Token::Ampersand => UnaryOperator::Reference,
+            Token::Not => UnaryOperator::Not,
+            Token::Tilde => UnaryOperator::BitNot,
+            Token::Increment => UnaryOperator::Increment,
+            Token::Decrement => UnaryOperator::Decrement,
+            _ => unreachable!(),
+        };
+        self.next_token();
+        let expr = self.parse_expression(Precedence::Prefix);
+        expr.map(|expr| {
+            let expr = Box::new(expr);
+            self.spanned(ExpressionKind::Unary { expr, op }, start)
+        })
+    }
+
     pub(super) fn parse_binary_expression(
         &mut self,
         lhs: Expression,
-    ) -> Result<Expression, String> {
-        let (op, swap) = match self.current_token {
-            Token::Assignment => (BinaryOperator::Assignment, false),
-            Token::Plus => (BinaryOperator::Plus, false),
-            Token::Minus => (BinaryOperator::Minus, false),
-            Token::Asterisk => (BinaryOperator::Asterisk, false),
-            Token::Slash => (BinaryOperator::Slash, false),
-            Token::Lt => (BinaryOperator::Lt, false),
-            Token::Gt => (BinaryOperator::Lt, true),
-            Token::LtEq => (BinaryOperator::LtEq, false),
-            Token::GtEq => (BinaryOperator::LtEq, true),
-            Token::Eq => (BinaryOperator::Eq, false),
-            Token::NotEq => (BinaryOperator::NotEq, false),
-            _ => {
-                return Err(format!(
-                    "Expected binary operator, but got {:?}",
-                    self.current_token
-                ))
-            }
+    ) -> Result<Expression, Diagnostic> {
+        let Some((op, swap)) = binary_operator(&self.current_token) else {
+            return Err(self.unexpected_current("binary operator"));
+        };
+        let precedence = match self.get_precedence(self.current_token.clone()) {
+            // assignments are right associative, `a = b = c` is `a = (b = c)`.
+            Precedence::Assignment => Precedence::Comma,
+            p => p,
         };
-        let precedence = self.get_precedence(self.current_token.clone());
         self.next_token();
-        let rhs = Box::new(self.parse_expression(precedence)?);
-
-        // when swap is true, swap left and right
-        if swap {
-            Ok(Expression::Binary {
-                rhs: Box::new(lhs),
-                op,
-                lhs: rhs,
-            })
-        } else {
-            Ok(Expression::Binary {
-                rhs,
-                op,
+        let rhs = self.parse_expression(precedence);
+        rhs.map(|rhs| {
+            let start = lhs.span;
+            // when swap is true, swap left and right
+            let (lhs, rhs) = match swap {
+                true => (rhs, lhs),
+                false => (lhs, rhs),
+            };
+            let kind = ExpressionKind::Binary {
                 lhs: Box::new(lhs),
-            })
-        }
+                op,
+                rhs: Box::new(rhs),
+            };
+            self.spanned(kind, start)
+        })
     }
 
-    pub(super) fn parse_grouped_expression(&mut self) -> Result<Expression, String> {
+    /// `? then : otherwise` after `condition`, from the `?` as the current token.
+    /// `a ? b : c ? d : e` is `a ? b : (c ? d : e)`.
+    fn parse_conditional_expression(
+        &mut self,
+        condition: Expression,
+    ) -> Result<Expression, Diagnostic> {
+        self.next_token(); // skip '?'
+        let then = self.parse_expression(Precedence::Lowest)?;
+        if self.peeked_token != Token::Colon {
+            return Err(self.unexpected_peeked("`:`"));
+        }
         self.next_token();
-        let expr = self.parse_expression(Precedence::Lowest)?;
+        self.next_token(); // skip ':'
+        let otherwise = self.parse_expression(Precedence::Assignment)?;
+        let start = condition.span;
+        let kind = ExpressionKind::Conditional {
+            condition: Box::new(condition),
+            then: Box::new(then),
+            otherwise: Box::new(otherwise),
+        };
+        Ok(self.spanned(kind, start))
+    }
+
+    /// `(type) operand`, from the `(` as the current token.
+    fn parse_cast_expression(&mut self) -> Result<Expression, Diagnostic> {
+        let span = self.current_span;
+        self.next_token(); // skip '('
+        let type_ = self.parse_type_name()?;
         if self.peeked_token != Token::RParen {
-            return Err(format!("Expected ')', but got {:?}", self.peeked_token));
+            return Err(self.unexpected_peeked("`)`"));
+        }
+        self.next_token();
+        self.next_token(); // skip ')'
+        let expr = self.parse_expression(Precedence::Prefix)?;
+
+        // the value of a cast has no qualifiers.
+        let kind = ExpressionKind::Cast {
+            expr: Box::new(expr),
+            type_: type_.unqualified().clone(),
+        };
+        Ok(self.spanned(kind, span))
+    }
+
+    /// `sizeof` or `_Alignof` of a type name in parentheses or of an operand, from the operator
+    /// as the current token. it is folded to a `size_t` constant, the operand is not evaluated.
+    fn parse_sizeof_expression(&mut self) -> Result<Expression, Diagnostic> {
+        let operator = self.current_token.clone();
+        let start = self.current_span;
+        self.next_token();
+        let type_ =
+            if self.current_token == Token::LParen && self.starts_type_name(&self.peeked_token) {
+                self.next_token(); // skip '('
+                let type_ = self.parse_type_name()?;
+                if self.peeked_token != Token::RParen {
+                    return Err(self.unexpected_peeked("`)`"));
+                }
+                self.next_token();
+                type_
+            } else {
+                let expr = self.parse_expression(Precedence::Prefix)?;
+                match type_of(&expr) {
+                    Some(type_) => type_,
+                    None => {
+                        return Err(Diagnostic::error(format!(
+                            "invalid operand `{}` to `{}`",
+                            expr, operator
+                        ))
+                        .with_code("E0113")
+                        .with_primary_label(start.to(self.current_span), "its type is not known"))
+                    }
+                }
+            };
+
+        let span = start.to(self.current_span);
+        let type_ = self.complete_type(type_);
+        let context = format!("invalid application of `{}` to an", operator);
+        match type_.unqualified() {
+            Type::Function { .. } => Err(Diagnostic::error(format!(
+                "invalid application of `{}` to a function type `{}`",
+                operator, type_
+            ))
+            .with_code("E0113")
+            .with_primary_label(span, "functions have no size")),
+            Type::Primitive(TypeEnum::Void) => Err(Diagnostic::error(format!(
+                "{} incomplete type `{}`",
+                context, type_
+            ))
+            .with_code("E0107")
+            .with_primary_label(span, "`void` has no size")),
+            type_ if !is_complete(type_) => Err(incomplete(type_, span, &context)),
+            type_ => {
+                let value = match operator {
+                    Token::Sizeof => type_.size(),
+                    _ => type_.align(),
+                };
+                let kind = ExpressionKind::Integer(value as u64, IntegerType::UnsignedLong);
+                Ok(Expression::new(kind, span))
+            }
         }
+    }
+
+    /// expression in parentheses, which are part of its span.
+    pub(super) fn parse_grouped_expression(&mut self) -> Result<Expression, Diagnostic> {
+        let start = self.current_span;
         self.next_token();
-        Ok(expr)
+        let expr = self.parse_expression(Precedence::Lowest);
+        expr.and_then(|mut expr| {
+            if self.peeked_token != Token::RParen {
+                return Err(self.unexpected_peeked("`)`"));
+            }
+            self.next_token();
+            expr.span = start.to(self.current_span);
+            Ok(expr)
+        })
     }
 
     pub(super) fn parse_identifier_expression(
         &mut self,
         name: String,
-    ) -> Result<Expression, String> {
-        let offset = self.find_local_var(&name);
-        match offset {
-            Some(LVar { offset, type_, .. }) => Ok(Expression::LocalVariable {
-                name,
-                offset: *offset,
-                type_: type_.clone(),
-            }),
-            None => Err(format!("Undefined variable: {}", name)),
-        }
+    ) -> Result<Expression, Diagnostic> {
+        let span = self.current_span;
+        let kind = match self.find_variable(&name) {
+            Some(Variable {
+                storage: Storage::Typedef,
+                ..
+            }) => {
+                return Err(
+                    Diagnostic::error(format!("unexpected type name `{}`", name))
+                        .with_code("E0100")
+                        .with_primary_label(span, "expected expression"),
+                )
+            }
+            // a struct declared after the variable may complete its type.
+            Some(variable) => match variable.expression(span).kind {
+                ExpressionKind::LocalVariable {
+                    name,
+                    offset,
+                    type_,
+                } => ExpressionKind::LocalVariable {
+                    name,
+                    offset,
+                    type_: self.complete_type(type_),
+                },
+                ExpressionKind::GlobalVariable { name, type_ } => ExpressionKind::GlobalVariable {
+                    name,
+                    type_: self.complete_type(type_),
+                },
+                kind => kind,
+            },
+            None => match self.function_designator(&name, span) {
+                Some(function) => return Ok(function),
+                None => {
+                    return Err(Diagnostic::error(format!(
+                        "use of undeclared identifier `{}`",
+                        name
+                    ))
+                    .with_code("E0101")
+                    .with_primary_label(span, "not found in this scope"))
+                }
+            },
+        };
+        Ok(Expression::new(kind, span))
     }
 
+    /// arguments of a call through `callee`, from the `(` as the current token to the `)`.
     pub(super) fn parse_call_expression(
         &mut self,
-        callee_name: String,
-    ) -> Result<Expression, String> {
+        callee: Expression,
+    ) -> Result<Expression, Diagnostic> {
         let mut arguments = vec![];
 
         while self.peeked_token != Token::RParen {
             self.next_token();
-            let expr = self.parse_expression(Precedence::Lowest)?;
+            let expr = self.parse_expression(Precedence::Comma)?;
             arguments.push(expr);
             if self.peeked_token == Token::Comma {
                 self.next_token();
@@ -167,36 +348,41 @@ impl Parser {
 
         self.next_token(); // skip ')'
 
-        Ok(Expression::Call {
-            callee_name,
+        let start = callee.span;
+        let kind = ExpressionKind::Call {
+            callee: Box::new(callee),
             arguments,
-        })
+        };
+        Ok(self.spanned(kind, start))
     }
 
     pub(super) fn perse_index_expression(
         &mut self,
         left: Expression,
-    ) -> Result<Expression, String> {
+    ) -> Result<Expression, Diagnostic> {
         self.next_token(); // skip '['
         let index = self.parse_expression(Precedence::Lowest)?;
         if self.peeked_token != Token::RBracket {
-            return Err(format!("Expected ']', but got {:?}", self.peeked_token));
+            return Err(self.unexpected_peeked("`]`"));
         }
         self.next_token(); // skip ']'
 
-        Ok(Expression::Index {
+        let start = left.span;
+        let kind = ExpressionKind::Index {
             expr: Box::new(left),
             index: Box::new(index),
-        })
+        };
+        Ok(self.spanned(kind, start))
     }
 
     // TODO: valid only initial declaration
-    pub(crate) fn parse_array_expression(&mut self) -> Result<Expression, String> {
+    pub(crate) fn parse_array_expression(&mut self) -> Result<Expression, Diagnostic> {
+        let start = self.current_span;
         let mut elements = vec![];
 
         while self.peeked_token != Token::RBrace {
             self.next_token();
-            let expr = self.parse_expression(Precedence::Lowest)?;
+            let expr = self.parse_expression(Precedence::Comma)?;
             elements.push(expr);
 
             if self.peeked_token == Token::Comma {
@@ -206,13 +392,50 @@ impl Parser {
 
         self.next_token(); // skip '}'
 
-        Ok(Expression::Array { elements })
+        Ok(self.spanned(ExpressionKind::Array { elements }, start))
     }
 }
 
+/// operator of the binary `token`, and whether its operands are swapped, `a > b` being `b < a`.
+fn binary_operator(token: &Token) -> Option<(BinaryOperator, bool)> {
+    let operator = match token {
+        Token::Assignment => (BinaryOperator::Assignment, false),
+        Token::Plus => (BinaryOperator::Plus, false),
+        Token::Minus => (BinaryOperator::Minus, false),
+        Token::Asterisk => (BinaryOperator::Asterisk, false),
+        Token::Slash => (BinaryOperator::Slash, false),
+        Token::Lt => (BinaryOperator::Lt, false),
+        Token::Gt => (BinaryOperator::Lt, true),
+        Token::LtEq => (BinaryOperator::LtEq, false),
+        Token::GtEq => (BinaryOperator::LtEq, true),
+        Token::Eq => (BinaryOperator::Eq, false),
+        Token::NotEq => (BinaryOperator::NotEq, false),
+        Token::Percent => (BinaryOperator::Percent, false),
+        Token::LShift => (BinaryOperator::LShift, false),
+        Token::RShift => (BinaryOperator::RShift, false),
+        Token::Ampersand => (BinaryOperator::BitAnd, false),
+        Token::Pipe => (BinaryOperator::BitOr, false),
+        Token::Caret => (BinaryOperator::BitXor, false),
+        Token::And => (BinaryOperator::And, false),
+        Token::Or => (BinaryOperator::Or, false),
+        Token::PlusAssignment => (BinaryOperator::PlusAssignment, false),
+        Token::MinusAssignment => (BinaryOperator::MinusAssignment, false),
+        Token::AsteriskAssignment => (BinaryOperator::AsteriskAssignment, false),
+        Token::SlashAssignment => (BinaryOperator::SlashAssignment, false),
+        Token::PercentAssignment => (BinaryOperator::PercentAssignment, false),
+        Token::LShiftAssignment => (BinaryOperator::LShiftAssignment, false),
+        Token::RShiftAssignment => (BinaryOperator::RShiftAssignment, false),
+        Token::AmpersandAssignment => (BinaryOperator::BitAndAssignment, false),
+        Token::PipeAssignment => (BinaryOperator::BitOrAssignment, false),
+        Token::CaretAssignment => (BinaryOperator::BitXorAssignment, false),
+        _ => return None,
+    };
+    Some(operator)
+}
+
 #[cfg(test)]
 mod test {
-    use ast::{Statement, Type, TypeEnum};
+    use ast::{fold_integer, ExpressionKind, FloatType, IntegerType, Statement, Type, TypeEnum};
     use lex::Lexer;
 
     use super::*;
@@ -220,14 +443,33 @@ mod test {
     #[test]
     fn test_parse_integer() {
         let cases = vec![
-            (String::from("5"), Expression::Integer(5)),
-            (String::from("10"), Expression::Integer(10)),
+            (
+                String::from("5"),
+                ExpressionKind::Integer(5, IntegerType::Int).into(),
+            ),
+            (
+                String::from("10"),
+                ExpressionKind::Integer(10, IntegerType::Int).into(),
+            ),
+            (
+                String::from("0x10UL"),
+                ExpressionKind::Integer(16, IntegerType::UnsignedLong).into(),
+            ),
+            (
+                String::from("'a'"),
+                ExpressionKind::Integer(97, IntegerType::Int).into(),
+            ),
+            (
+                String::from("1.5f"),
+                ExpressionKind::Float(1.5, FloatType::Float).into(),
+            ),
             (
                 String::from("-10"),
-                Expression::Unary {
-                    expr: Box::new(Expression::Integer(10)),
+                ExpressionKind::Unary {
+                    expr: Box::new(ExpressionKind::Integer(10, IntegerType::Int).into()),
                     op: UnaryOperator::Minus,
-                },
+                }
+                .into(),
             ),
         ];
 
@@ -246,17 +488,17 @@ mod test {
         let cases = vec![
             // (
             //     String::from(r#""hello""#),
-            //     Expression::String {
+            //     ExpressionKind::String {
             //         label: String::from(""),
             //         value: String::from("hello"),
-            //     },
+            //     }.into(),
             // ),
             // (
             //     String::from(r#""hello world""#),
-            //     Expression::String {
+            //     ExpressionKind::String {
             //         label: String::from(""),
             //         value: String::from("hello world"),
-            //     },
+            //     }.into(),
             // ),
         ];
 
@@ -275,17 +517,19 @@ mod test {
         let cases = vec![
             (
                 String::from("&5"),
-                Expression::Unary {
-                    expr: Box::new(Expression::Integer(5)),
+                ExpressionKind::Unary {
+                    expr: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
                     op: UnaryOperator::Reference,
-                },
+                }
+                .into(),
             ),
             (
                 String::from("*5"),
-                Expression::Unary {
-                    expr: Box::new(Expression::Integer(5)),
+                ExpressionKind::Unary {
+                    expr: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
                     op: UnaryOperator::Dereference,
-                },
+                }
+                .into(),
             ),
         ];
 
@@ -304,58 +548,70 @@ mod test {
         let case = vec![
             (
                 String::from("5 + 5"),
-                Expression::Binary {
-                    lhs: Box::new(Expression::Integer(5)),
+                ExpressionKind::Binary {
+                    lhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
                     op: BinaryOperator::Plus,
-                    rhs: Box::new(Expression::Integer(5)),
-                },
+                    rhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
+                }
+                .into(),
             ),
             (
                 String::from("5 - 5"),
-                Expression::Binary {
-                    lhs: Box::new(Expression::Integer(5)),
+                ExpressionKind::Binary {
+                    lhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
                     op: BinaryOperator::Minus,
-                    rhs: Box::new(Expression::Integer(5)),
-                },
+                    rhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
+                }
+                .into(),
             ),
             (
                 String::from("5 * 5"),
-                Expression::Binary {
-                    lhs: Box::new(Expression::Integer(5)),
+                ExpressionKind::Binary {
+                    lhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
                     op: BinaryOperator::Asterisk,
-                    rhs: Box::new(Expression::Integer(5)),
-                },
+                    rhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
+                }
+                .into(),
             ),
             (
                 String::from("5 / 5"),
-                Expression::Binary {
-                    lhs: Box::new(Expression::Integer(5)),
+                ExpressionKind::Binary {
+                    lhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
                     op: BinaryOperator::Slash,
-                    rhs: Box::new(Expression::Integer(5)),
-                },
+                    rhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
+                }
+                .into(),
             ),
             // include unary
             (
                 String::from("-5 + 5"),
-                Expression::Binary {
-                    lhs: Box::new(Expression::Unary {
-                        expr: Box::new(Expression::Integer(5)),
-                        op: UnaryOperator::Minus,
-                    }),
+                ExpressionKind::Binary {
+                    lhs: Box::new(
+                        ExpressionKind::Unary {
+                            expr: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
+                            op: UnaryOperator::Minus,
+                        }
+                        .into(),
+                    ),
                     op: BinaryOperator::Plus,
-                    rhs: Box::new(Expression::Integer(5)),
-                },
+                    rhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
+                }
+                .into(),
             ),
             (
                 String::from("5 + -5"),
-                Expression::Binary {
-                    lhs: Box::new(Expression::Integer(5)),
+                ExpressionKind::Binary {
+                    lhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
                     op: BinaryOperator::Plus,
-                    rhs: Box::new(Expression::Unary {
-                        expr: Box::new(Expression::Integer(5)),
-                        op: UnaryOperator::Minus,
-                    }),
-                },
+                    rhs: Box::new(
+                        ExpressionKind::Unary {
+                            expr: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
+                            op: UnaryOperator::Minus,
+                        }
+                        .into(),
+                    ),
+                }
+                .into(),
             ),
         ];
 
@@ -374,51 +630,80 @@ mod test {
         let cases = vec![
             (
                 String::from("5 + 5 * 5"),
-                Expression::Binary {
-                    lhs: Box::new(Expression::Integer(5)),
+                ExpressionKind::Binary {
+                    lhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
                     op: BinaryOperator::Plus,
-                    rhs: Box::new(Expression::Binary {
-                        lhs: Box::new(Expression::Integer(5)),
-                        op: BinaryOperator::Asterisk,
-                        rhs: Box::new(Expression::Integer(5)),
-                    }),
-                },
+                    rhs: Box::new(
+                        ExpressionKind::Binary {
+                            lhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
+                            op: BinaryOperator::Asterisk,
+                            rhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
+                        }
+                        .into(),
+                    ),
+                }
+                .into(),
             ),
             (
                 String::from("1 * 2 + 3 * 4"),
-                Expression::Binary {
-                    lhs: Box::new(Expression::Binary {
-                        lhs: Box::new(Expression::Integer(1)),
-                        op: BinaryOperator::Asterisk,
-                        rhs: Box::new(Expression::Integer(2)),
-                    }),
+                ExpressionKind::Binary {
+                    lhs: Box::new(
+                        ExpressionKind::Binary {
+                            lhs: Box::new(ExpressionKind::Integer(1, IntegerType::Int).into()),
+                            op: BinaryOperator::Asterisk,
+                            rhs: Box::new(ExpressionKind::Integer(2, IntegerType::Int).into()),
+                        }
+                        .into(),
+                    ),
                     op: BinaryOperator::Plus,
-                    rhs: Box::new(Expression::Binary {
-                        lhs: Box::new(Expression::Integer(3)),
-                        op: BinaryOperator::Asterisk,
-                        rhs: Box::new(Expression::Integer(4)),
-                    }),
-                },
+                    rhs: Box::new(
+                        ExpressionKind::Binary {
+                            lhs: Box::new(ExpressionKind::Integer(3, IntegerType::Int).into()),
+                            op: BinaryOperator::Asterisk,
+                            rhs: Box::new(ExpressionKind::Integer(4, IntegerType::Int).into()),
+                        }
+                        .into(),
+                    ),
+                }
+                .into(),
             ),
             (
                 String::from("1 * 2 >= 3 * 4 == 0"),
-                Expression::Binary {
-                    lhs: Box::new(Expression::Binary {
-                        lhs: Box::new(Expression::Binary {
-                            lhs: Box::new(Expression::Integer(3)),
-                            op: BinaryOperator::Asterisk,
-                            rhs: Box::new(Expression::Integer(4)),
-                        }),
-                        op: BinaryOperator::LtEq,
-                        rhs: Box::new(Expression::Binary {
-                            lhs: Box::new(Expression::Integer(1)),
-                            op: BinaryOperator::Asterisk,
-                            rhs: Box::new(Expression::Integer(2)),
-                        }),
-                    }),
+                ExpressionKind::Binary {
+                    lhs: Box::new(
+                        ExpressionKind::Binary {
+                            lhs: Box::new(
+                                ExpressionKind::Binary {
+                                    lhs: Box::new(
+                                        ExpressionKind::Integer(3, IntegerType::Int).into(),
+                                    ),
+                                    op: BinaryOperator::Asterisk,
+                                    rhs: Box::new(
+                                        ExpressionKind::Integer(4, IntegerType::Int).into(),
+                                    ),
+                                }
+                                .into(),
+                            ),
+                            op: BinaryOperator::LtEq,
+                            rhs: Box::new(
+                                ExpressionKind::Binary {
+                                    lhs: Box::new(
+                                        ExpressionKind::Integer(1, IntegerType::Int).into(),
+                                    ),
+                                    op: BinaryOperator::Asterisk,
+                                    rhs: Box::new(
+                                        ExpressionKind::Integer(2, IntegerType::Int).into(),
+                                    ),
+                                }
+                                .into(),
+                            ),
+                        }
+                        .into(),
+                    ),
                     op: BinaryOperator::Eq,
-                    rhs: Box::new(Expression::Integer(0)),
-                },
+                    rhs: Box::new(ExpressionKind::Integer(0, IntegerType::Int).into()),
+                }
+                .into(),
             ),
         ];
 
@@ -437,51 +722,82 @@ mod test {
         let cases = vec![
             (
                 String::from("(5 + 5) * 5"),
-                Expression::Binary {
-                    lhs: Box::new(Expression::Binary {
-                        lhs: Box::new(Expression::Integer(5)),
-                        op: BinaryOperator::Plus,
-                        rhs: Box::new(Expression::Integer(5)),
-                    }),
+                ExpressionKind::Binary {
+                    lhs: Box::new(
+                        ExpressionKind::Binary {
+                            lhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
+                            op: BinaryOperator::Plus,
+                            rhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
+                        }
+                        .into(),
+                    ),
                     op: BinaryOperator::Asterisk,
-                    rhs: Box::new(Expression::Integer(5)),
-                },
+                    rhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
+                }
+                .into(),
             ),
             (
                 String::from("1 * (2 + 3) * 4"),
-                Expression::Binary {
-                    lhs: Box::new(Expression::Binary {
-                        lhs: Box::new(Expression::Integer(1)),
-                        op: BinaryOperator::Asterisk,
-                        rhs: Box::new(Expression::Binary {
-                            lhs: Box::new(Expression::Integer(2)),
-                            op: BinaryOperator::Plus,
-                            rhs: Box::new(Expression::Integer(3)),
-                        }),
-                    }),
+                ExpressionKind::Binary {
+                    lhs: Box::new(
+                        ExpressionKind::Binary {
+                            lhs: Box::new(ExpressionKind::Integer(1, IntegerType::Int).into()),
+                            op: BinaryOperator::Asterisk,
+                            rhs: Box::new(
+                                ExpressionKind::Binary {
+                                    lhs: Box::new(
+                                        ExpressionKind::Integer(2, IntegerType::Int).into(),
+                                    ),
+                                    op: BinaryOperator::Plus,
+                                    rhs: Box::new(
+                                        ExpressionKind::Integer(3, IntegerType::Int).into(),
+                                    ),
+                                }
+                                .into(),
+                            ),
+                        }
+                        .into(),
+                    ),
                     op: BinaryOperator::Asterisk,
-                    rhs: Box::new(Expression::Integer(4)),
-                },
+                    rhs: Box::new(ExpressionKind::Integer(4, IntegerType::Int).into()),
+                }
+                .into(),
             ),
             (
                 String::from("1 * (2 * (3 + 4)) * 5"),
-                Expression::Binary {
-                    lhs: Box::new(Expression::Binary {
-                        lhs: Box::new(Expression::Integer(1)),
-                        op: BinaryOperator::Asterisk,
-                        rhs: Box::new(Expression::Binary {
-                            lhs: Box::new(Expression::Integer(2)),
+                ExpressionKind::Binary {
+                    lhs: Box::new(
+                        ExpressionKind::Binary {
+                            lhs: Box::new(ExpressionKind::Integer(1, IntegerType::Int).into()),
                             op: BinaryOperator::Asterisk,
-                            rhs: Box::new(Expression::Binary {
-                                lhs: Box::new(Expression::Integer(3)),
-                                op: BinaryOperator::Plus,
-                                rhs: Box::new(Expression::Integer(4)),
-                            }),
-                        }),
-                    }),
+                            rhs: Box::new(
+                                ExpressionKind::Binary {
+                                    lhs: Box::new(
+                                        ExpressionKind::Integer(2, IntegerType::Int).into(),
+                                    ),
+                                    op: BinaryOperator::Asterisk,
+                                    rhs: Box::new(
+                                        ExpressionKind::Binary {
+                                            lhs: Box::new(
+                                                ExpressionKind::Integer(3, IntegerType::Int).into(),
+                                            ),
+                                            op: BinaryOperator::Plus,
+                                            rhs: Box::new(
+                                                ExpressionKind::Integer(4, IntegerType::Int).into(),
+                                            ),
+                                        }
+                                        .into(),
+                                    ),
+                                }
+                                .into(),
+                            ),
+                        }
+                        .into(),
+                    ),
                     op: BinaryOperator::Asterisk,
-                    rhs: Box::new(Expression::Integer(5)),
-                },
+                    rhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
+                }
+                .into(),
             ),
         ];
 
@@ -495,22 +811,90 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_operator_precedence() {
+        let cases = vec![
+            ("1 + 2 % 3 << 4", "(1 + (2 % 3)) << 4"),
+            ("1 < 2 == 3 & 4 ^ 5 | 6", "((((1 < 2) == 3) & 4) ^ 5) | 6"),
+            ("1 || 2 && 3 | 4", "1 || (2 && (3 | 4))"),
+            ("!1 + ~2 - +3", "(!1 + ~2) - +3"),
+            ("-1++ * --2", "-1++ * --2"),
+            ("1 = 2 += 3 <<= 4 || 5", "1 = (2 += (3 <<= (4 || 5)))"),
+            ("1 = 2 ? 3 : 4 ? 5 : 6, 7", "1 = (2 ? 3 : (4 ? 5 : 6)), 7"),
+            ("1 || 2 ? 3, 4 : 5", "(1 || 2) ? 3, 4 : 5"),
+            ("(long)-1 * 2", "(long)-1 * 2"),
+            ("(const char *)(1 + 2)", "(const char *)(1 + 2)"),
+            ("(char *const)0", "(char *)0"),
+            ("(int (*)[4])0", "(int (*)[4])0"),
+        ];
+
+        for (input, expected) in cases {
+            let lexer = Lexer::new(String::from(input));
+            let mut parser = Parser::new(lexer);
+            let expr = parser.parse_expression(Precedence::Lowest).unwrap();
+            assert_eq!(expr.to_string(), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_sizeof_expression() {
+        let input = String::from(
+            "char c; int a[3]; int *p; struct s { char c; long l; } v; \
+             sizeof(int[2][3]); sizeof c + 1; sizeof(c + 1); sizeof a; sizeof a[0] * 2; \
+             sizeof &a; sizeof(p - p); sizeof \"abc\"; sizeof v; _Alignof(short); alignof v;",
+        );
+        let expected = vec![
+            "24ul", "1ul + 1", "4ul", "12ul", "4ul * 2", "8ul", "8ul", "4ul", "16ul", "2ul", "8ul",
+        ];
+
+        let mut parser = Parser::new(Lexer::new(input));
+        parser.enter_scope();
+        let statements = parser.parse().unwrap().0.statements;
+        let sizes = statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::Expression(expr) => Some(expr.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, expected);
+    }
+
     #[test]
     fn parse_call_expression() {
+        let implicit = |name: &str| {
+            Box::new(
+                ExpressionKind::Function {
+                    name: String::from(name),
+                    type_: Type::Function {
+                        return_type: Box::new(Type::Primitive(TypeEnum::Int)),
+                        parameters: vec![],
+                        variadic: false,
+                        prototyped: false,
+                    },
+                }
+                .into(),
+            )
+        };
         let cases = vec![
             (
                 String::from("foo();"),
-                Expression::Call {
-                    callee_name: String::from("foo"),
+                ExpressionKind::Call {
+                    callee: implicit("foo"),
                     arguments: vec![],
-                },
+                }
+                .into(),
             ),
             (
                 String::from("bar(1, 2);"),
-                Expression::Call {
-                    callee_name: String::from("bar"),
-                    arguments: vec![Expression::Integer(1), Expression::Integer(2)],
-                },
+                ExpressionKind::Call {
+                    callee: implicit("bar"),
+                    arguments: vec![
+                        ExpressionKind::Integer(1, IntegerType::Int).into(),
+                        ExpressionKind::Integer(2, IntegerType::Int).into(),
+                    ],
+                }
+                .into(),
             ),
         ];
 
@@ -531,31 +915,38 @@ mod test {
             vec![
                 Statement::InitDeclaration {
                     name: String::from("foo"),
-                    offset: 24,
+                    offset: 0,
                     type_: Type::Array {
                         type_: Box::new(Type::Primitive(TypeEnum::Int)),
                         size: 3,
                     },
                     init: None,
                 },
-                Statement::Expression(Expression::Index {
-                    expr: Box::new(Expression::LocalVariable {
-                        name: String::from("foo"),
-                        offset: 24,
-                        type_: Type::Array {
-                            type_: Box::new(Type::Primitive(TypeEnum::Int)),
-                            size: 3,
-                        },
-                    }),
-                    index: Box::new(Expression::Integer(1)),
-                }),
+                Statement::Expression(
+                    ExpressionKind::Index {
+                        expr: Box::new(
+                            ExpressionKind::LocalVariable {
+                                name: String::from("foo"),
+                                offset: 0,
+                                type_: Type::Array {
+                                    type_: Box::new(Type::Primitive(TypeEnum::Int)),
+                                    size: 3,
+                                },
+                            }
+                            .into(),
+                        ),
+                        index: Box::new(ExpressionKind::Integer(1, IntegerType::Int).into()),
+                    }
+                    .into(),
+                ),
             ],
         )];
 
         for (input, expected) in cases {
             let lexer = Lexer::new(input);
             let mut parser = Parser::new(lexer);
-            assert_eq!(parser.parse().unwrap().statements, expected);
+            parser.enter_scope();
+            assert_eq!(parser.parse().unwrap().0.statements, expected);
         }
     }
 
@@ -566,27 +957,59 @@ mod test {
             vec![
                 Statement::InitDeclaration {
                     name: String::from("array"),
-                    offset: 24,
+                    offset: 0,
                     type_: Type::Array {
                         type_: Box::new(Type::Primitive(TypeEnum::Int)),
                         size: 3,
                     },
-                    init: Some(Expression::Array {
-                        elements: vec![
-                            Expression::Integer(1),
-                            Expression::Integer(2),
-                            Expression::Integer(3),
-                        ],
-                    }),
+                    init: Some(
+                        ExpressionKind::Array {
+                            elements: vec![
+                                ExpressionKind::Integer(1, IntegerType::Int).into(),
+                                ExpressionKind::Integer(2, IntegerType::Int).into(),
+                                ExpressionKind::Integer(3, IntegerType::Int).into(),
+                            ],
+                        }
+                        .into(),
+                    ),
                 },
-                Statement::Return(Expression::Integer(0)),
+                Statement::Return(Some(ExpressionKind::Integer(0, IntegerType::Int).into())),
             ],
         )];
 
         for (input, expected) in cases {
             let lexer = Lexer::new(input);
             let mut parser = Parser::new(lexer);
-            assert_eq!(parser.parse().unwrap().statements, expected);
+            parser.enter_scope();
+            assert_eq!(parser.parse().unwrap().0.statements, expected);
+        }
+    }
+
+    #[test]
+    fn test_fold_integer() {
+        let cases = vec![
+            ("7 / -2", Some(-3)),
+            ("-1u >> 28", Some(15)),
+            ("-1 >> 28", Some(-1)),
+            ("-1 < 0u", Some(0)),
+            ("7u % -2", Some(7)),
+            ("(unsigned char)300", Some(44)),
+            ("(signed char)200", Some(-56)),
+            ("(_Bool)4", Some(1)),
+            ("(unsigned long)-1", Some(-1)),
+            ("~0u", Some(u32::MAX as i64)),
+            ("0 ? 1 : -1u", Some(u32::MAX as i64)),
+            ("sizeof(int) * 2", Some(8)),
+            ("1 / 0", None),
+            ("1.5 + 1", None),
+            ("(int)(1.5 * 3)", Some(4)),
+            ("(_Bool)0.5 + (1.5 < 2)", Some(2)),
+        ];
+
+        for (input, expected) in cases {
+            let mut parser = Parser::new(Lexer::new(String::from(input)));
+            let expr = parser.parse_expression(Precedence::Lowest).unwrap();
+            assert_eq!(fold_integer(&expr), expected, "{}", input);
         }
     }
 }
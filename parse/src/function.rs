@@ -1,69 +1,258 @@
-use ast::Statement;
+use ast::{promoted, Expression, ExpressionKind, Statement, Type, TypeEnum};
+use diagnostic::Diagnostic;
 use lex::tokens::Token;
+use span::Span;
 
+use crate::declarator::{ensure_supported, Parameter};
+use crate::scope::{conflicting_linkage, redefinition};
 use crate::{Parser, Precedence};
 
-impl Parser {
-    pub(crate) fn parse_function_declaration(&mut self, name: String) -> Result<Statement, String> {
-        let mut arguments = Vec::new();
-        while self.peeked_token != Token::RParen {
-            self.next_token();
-            let (type_, name) = self.parse_type_declaration()?;
-
-            if self.peeked_token == Token::Comma {
-                self.next_token();
-            }
+/// a function declared so far, by a prototype, its definition or a call.
+pub(crate) struct Function {
+    /// `Type::Function`.
+    type_: Type,
+    /// name in the definition, or in the first declaration until there is one.
+    span: Span,
+    defined: bool,
+    /// assumed from a call without a declaration, to return `int` and take any arguments.
+    implicit: bool,
+    /// declared `static` first, not visible outside the file.
+    static_: bool,
+}
 
-            arguments.push((type_, name));
+impl Parser {
+    /// declaration of the function `name` of `type_`, a definition when its body follows.
+    /// the body has a frame and a scope of its own, with the `parameters` of the declarator.
+    pub(crate) fn parse_function_declaration(
+        &mut self,
+        type_: Type,
+        name: String,
+        span: Span,
+        parameters: Vec<Parameter>,
+        static_: bool,
+    ) -> Result<Statement, Diagnostic> {
+        if self.peeked_token != Token::LBrace {
+            self.declare_function(&name, span, &type_, false, static_)?;
+            return Ok(Statement::Declaration { name, type_ });
         }
+        // declared before the body, which may call the function itself.
+        self.next_token(); // '{'
+        self.declare_function(&name, span, &type_, true, static_)?;
+        self.function = name.clone();
+        self.statics.clear();
 
-        if self.peeked_token == Token::RParen {
-            self.next_token();
-            self.next_token(); // skip ')'
-        } else {
-            return Err(format!(
-                "expected token ')' but got {:?}",
-                self.peeked_token
-            ));
-        }
+        let return_type = match type_ {
+            Type::Function { return_type, .. } => *return_type,
+            _ => unreachable!("`{}` is not a function type", type_),
+        };
+        let outer = std::mem::take(&mut self.frame);
+        self.enter_scope();
+        let function = self.parse_function_body(name, return_type, parameters, span);
+        self.leave_scope();
+        self.frame = outer;
+        function
+    }
 
-        let arguments = arguments
-            .iter()
-            .map(|(t, name)| self.new_local_var(t.clone(), name.clone()))
-            .collect::<Result<Vec<_>, _>>()?;
+    fn parse_function_body(
+        &mut self,
+        name: String,
+        return_type: Type,
+        parameters: Vec<Parameter>,
+        span: Span,
+    ) -> Result<Statement, Diagnostic> {
+        ensure_supported(&return_type, span)?;
+        let mut arguments = Vec::new();
+        for parameter in parameters {
+            let Some((name, span)) = parameter.name else {
+                return Err(Diagnostic::error("parameter name omitted")
+                    .with_code("E0100")
+                    .with_primary_label(span, "in the definition of this function"));
+            };
+            ensure_supported(&parameter.type_, span)?;
+            arguments.push(self.new_local_var(parameter.type_, name, span)?);
+        }
 
+        // the outermost block shares the scope of the parameters.
         let body = match self.parse_block_statement()? {
             Statement::Block(body) => body,
             _ => unreachable!(),
         };
+        self.check_gotos()?;
 
         Ok(Statement::FunctionDefinition {
+            static_: self.functions[&name].static_,
             name,
+            return_type,
             arguments,
             body,
+            stack_size: self.frame.stack_size(),
         })
     }
 
-    pub(crate) fn parse_return_statement(&mut self) -> Result<Statement, String> {
+    /// adds `name` to the function table. it may be declared any number of times with the same
+    /// type, or without a prototype besides one, but defined only once. a declaration without
+    /// `static` keeps the linkage of the previous one.
+    fn declare_function(
+        &mut self,
+        name: &str,
+        span: Span,
+        type_: &Type,
+        definition: bool,
+        static_: bool,
+    ) -> Result<(), Diagnostic> {
+        if let Some(previous) = self.functions.get_mut(name).filter(|f| !f.implicit) {
+            if static_ && !previous.static_ {
+                return Err(conflicting_linkage(name, false, span, previous.span));
+            }
+            if previous.defined && definition {
+                return Err(redefinition(name, span, previous.span));
+            }
+            if previous.type_ != *type_ {
+                let Some(composite) = composite(&previous.type_, type_) else {
+                    return Err(
+                    Diagnostic::error(format!("conflicting types for `{}`", name))
+                        .with_code("E0104")
+                        .with_primary_label(span, format!("declared here as `{}`", type_))
+                        .with_secondary_label(
+                            previous.span,
+                            format!("previously declared as `{}`", previous.type_),
+                        ),
+                    );
+                };
+                previous.type_ = composite;
+            }
+            if definition {
+                previous.defined = true;
+                previous.span = span;
+            }
+            return Ok(());
+        }
+        self.functions.insert(
+            name.to_string(),
+            Function {
+                type_: type_.clone(),
+                span,
+                defined: definition,
+                implicit: false,
+                static_,
+            },
+        );
+        Ok(())
+    }
+
+    /// designator of the function `name` at `span`. an undeclared function called right away is
+    /// declared implicitly, with a warning.
+    pub(crate) fn function_designator(&mut self, name: &str, span: Span) -> Option<Expression> {
+        if !self.functions.contains_key(name) {
+            if self.peeked_token != Token::LParen {
+                return None;
+            }
+            self.warn(
+                Diagnostic::warning(format!("implicit declaration of function `{}`", name))
+                    .with_primary_label(span, "not declared before this call")
+                    .with_note("declare it, or include the header declaring it"),
+            );
+            let type_ = Type::Function {
+                return_type: Box::new(Type::Primitive(TypeEnum::Int)),
+                parameters: vec![],
+                variadic: false,
+                prototyped: false,
+            };
+            self.functions.insert(
+                name.to_string(),
+                Function {
+                    type_,
+                    span,
+                    defined: false,
+                    implicit: true,
+                    static_: false,
+                },
+            );
+        }
+        let kind = ExpressionKind::Function {
+            name: name.to_string(),
+            type_: self.functions[name].type_.clone(),
+        };
+        Some(Expression::new(kind, span))
+    }
+
+    /// `return;` is only allowed in a function returning `void`.
+    pub(crate) fn parse_return_statement(&mut self) -> Result<Statement, Diagnostic> {
+        if self.peeked_token == Token::SemiColon {
+            let span = self.current_span;
+            self.next_token(); // ';'
+            return match &self.functions[&self.function].type_ {
+                Type::Function { return_type, .. }
+                    if **return_type != Type::Primitive(TypeEnum::Void) =>
+                {
+                    Err(Diagnostic::error(format!(
+                        "non-void function `{}` should return a value",
+                        self.function
+                    ))
+                    .with_code("E0115")
+                    .with_primary_label(
+                        span,
+                        format!("expected a value of type `{}`", return_type),
+                    ))
+                }
+                _ => Ok(Statement::Return(None)),
+            };
+        }
         self.next_token(); // skip 'return'
         let expr = self.parse_expression(Precedence::Lowest)?;
 
         if self.peeked_token == Token::SemiColon {
             self.next_token();
         } else {
-            return Err(format!(
-                "expected token ';' but got {:?}",
-                self.peeked_token
-            ));
+            return Err(self.unexpected_peeked("`;`"));
         }
 
-        Ok(Statement::Return(expr))
+        Ok(Statement::Return(Some(expr)))
     }
 }
 
+/// type of a function declared as both `previous` and `type_`, `None` if they conflict. a
+/// declaration without a prototype takes the prototype of the other, unless it has a variable
+/// part or parameters the default argument promotions would change.
+fn composite(previous: &Type, type_: &Type) -> Option<Type> {
+    let (
+        Type::Function {
+            return_type: previous_return,
+            prototyped: previous_prototyped,
+            ..
+        },
+        Type::Function {
+            return_type,
+            prototyped,
+            ..
+        },
+    ) = (previous, type_)
+    else {
+        return None;
+    };
+    let prototype = match (previous_prototyped, prototyped) {
+        _ if previous_return != return_type => return None,
+        (false, _) => type_,
+        (true, false) => previous,
+        (true, true) => return None,
+    };
+    let Type::Function {
+        parameters,
+        variadic,
+        ..
+    } = prototype
+    else {
+        unreachable!();
+    };
+    let promotable = parameters
+        .iter()
+        .all(|p| *p == promoted(p) && *p != Type::Primitive(TypeEnum::Float));
+    (!variadic && promotable).then(|| prototype.clone())
+}
+
 #[cfg(test)]
 mod test {
-    use ast::{BinaryOperator, Expression, Type, TypeEnum};
+    use ast::{BinaryOperator, ExpressionKind, IntegerType, Type, TypeEnum};
     use lex::Lexer;
 
     use super::*;
@@ -75,27 +264,39 @@ mod test {
                 String::from("int foo() { return 0; }"),
                 Statement::FunctionDefinition {
                     name: String::from("foo"),
+                    return_type: Type::Primitive(TypeEnum::Int),
                     arguments: vec![],
-                    body: vec![Statement::Return(Expression::Integer(0))],
+                    body: vec![Statement::Return(Some(
+                        ExpressionKind::Integer(0, IntegerType::Int).into(),
+                    ))],
+                    stack_size: 0,
+                    static_: false,
                 },
             ),
             (
                 String::from("int foo(int a, int b) { return 0; }"),
                 Statement::FunctionDefinition {
                     name: String::from("foo"),
+                    return_type: Type::Primitive(TypeEnum::Int),
                     arguments: vec![
-                        Expression::LocalVariable {
+                        ExpressionKind::LocalVariable {
                             name: String::from("a"),
-                            offset: 8,
+                            offset: 0,
                             type_: Type::Primitive(TypeEnum::Int),
-                        },
-                        Expression::LocalVariable {
+                        }
+                        .into(),
+                        ExpressionKind::LocalVariable {
                             name: String::from("b"),
-                            offset: 16,
+                            offset: 4,
                             type_: Type::Primitive(TypeEnum::Int),
-                        },
+                        }
+                        .into(),
                     ],
-                    body: vec![Statement::Return(Expression::Integer(0))],
+                    body: vec![Statement::Return(Some(
+                        ExpressionKind::Integer(0, IntegerType::Int).into(),
+                    ))],
+                    stack_size: 16,
+                    static_: false,
                 },
             ),
         ];
@@ -106,20 +307,133 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_parse_declaration() {
+        let char_p = || Type::Pointer(Box::new(Type::Primitive(TypeEnum::Char)));
+        let cases = vec![
+            (
+                "int f(int, char *);",
+                Statement::Declaration {
+                    name: String::from("f"),
+                    type_: Type::Function {
+                        return_type: Box::new(Type::Primitive(TypeEnum::Int)),
+                        parameters: vec![Type::Primitive(TypeEnum::Int), char_p()],
+                        variadic: false,
+                        prototyped: true,
+                    },
+                },
+            ),
+            (
+                "int printf(char *format, ...);",
+                Statement::Declaration {
+                    name: String::from("printf"),
+                    type_: Type::Function {
+                        return_type: Box::new(Type::Primitive(TypeEnum::Int)),
+                        parameters: vec![char_p()],
+                        variadic: true,
+                        prototyped: true,
+                    },
+                },
+            ),
+            (
+                "void f(void);",
+                Statement::Declaration {
+                    name: String::from("f"),
+                    type_: Type::Function {
+                        return_type: Box::new(Type::Primitive(TypeEnum::Void)),
+                        parameters: vec![],
+                        variadic: false,
+                        prototyped: true,
+                    },
+                },
+            ),
+            (
+                "int f();",
+                Statement::Declaration {
+                    name: String::from("f"),
+                    type_: Type::Function {
+                        return_type: Box::new(Type::Primitive(TypeEnum::Int)),
+                        parameters: vec![],
+                        variadic: false,
+                        prototyped: false,
+                    },
+                },
+            ),
+            (
+                "extern char *s;",
+                Statement::Declaration {
+                    name: String::from("s"),
+                    type_: char_p(),
+                },
+            ),
+        ];
+        for (input, expected) in cases {
+            let lexer = Lexer::new(String::from(input));
+            let mut parser = Parser::new(lexer);
+            assert_eq!(parser.parse_statement().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_declare_unprototyped() {
+        let valid = [
+            "int f();\nint f(int a, char *s) { return a; }",
+            "int f(double d);\nint f();\nint f() { return 0; }",
+            "int f();\nint f();",
+        ];
+        for input in valid {
+            let mut parser = Parser::new(Lexer::new(String::from(input)));
+            assert!(parser.parse().is_ok(), "{}", input);
+        }
+
+        let conflicting = [
+            "int f();\nchar f(int a);",
+            "int f();\nint f(char c);",
+            "int f(float x);\nint f();",
+            "int f(int a, ...);\nint f();",
+        ];
+        for input in conflicting {
+            let mut parser = Parser::new(Lexer::new(String::from(input)));
+            let errors = parser.parse().unwrap_err();
+            assert_eq!(
+                errors[0].to_string(),
+                "error[E0104]: conflicting types for `f`",
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_call() {
+        let input = "int f(int a, char *s, ...);\nint main() {\n    char c;\n    return f(0, 0) + f(1, &c, 2, 3) + g(c);\n}";
+        let (_, warnings) = Parser::new(Lexer::new(String::from(input)))
+            .parse()
+            .unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].to_string(),
+            "warning: implicit declaration of function `g`"
+        );
+    }
+
     #[test]
     fn test_parse_return_statement() {
         let cases = vec![
             (
                 String::from("return 5;"),
-                Statement::Return(Expression::Integer(5)),
+                Statement::Return(Some(ExpressionKind::Integer(5, IntegerType::Int).into())),
             ),
             (
                 String::from("return 5 + 5;"),
-                Statement::Return(Expression::Binary {
-                    lhs: Box::new(Expression::Integer(5)),
-                    op: BinaryOperator::Plus,
-                    rhs: Box::new(Expression::Integer(5)),
-                }),
+                Statement::Return(Some(
+                    ExpressionKind::Binary {
+                        lhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
+                        op: BinaryOperator::Plus,
+                        rhs: Box::new(ExpressionKind::Integer(5, IntegerType::Int).into()),
+                    }
+                    .into(),
+                )),
             ),
         ];
 
@@ -129,4 +443,23 @@ mod test {
             assert_eq!(parser.parse_statement().unwrap(), expected);
         }
     }
+
+    #[test]
+    fn test_parse_bare_return() {
+        let mut parser = Parser::new(Lexer::new(String::from("void f() { return; }")));
+        let (program, _) = parser.parse().unwrap();
+        let Statement::FunctionDefinition { body, .. } = &program.statements[0] else {
+            panic!("not a function definition");
+        };
+        assert_eq!(body[0], Statement::Return(None));
+
+        let mut parser = Parser::new(Lexer::new(String::from("int f() {\n    return;\n}")));
+        let errors = parser.parse().unwrap_err();
+        assert_eq!(
+            errors[0].to_string(),
+            "error[E0115]: non-void function `f` should return a value"
+        );
+        let span = errors[0].primary_span().unwrap();
+        assert_eq!((span.line, span.column), (2, 5));
+    }
 }
@@ -0,0 +1,115 @@
+use ast::Statement;
+use diagnostic::Diagnostic;
+use lex::tokens::Token;
+use span::Span;
+
+use crate::Parser;
+
+/// where the statements of the function being parsed may jump to.
+#[derive(Default)]
+pub(crate) struct Jumps {
+    /// loops around the current statement.
+    pub(crate) loops: usize,
+    /// switches around the current statement, innermost last.
+    pub(crate) switches: Vec<Switch>,
+    /// labels defined so far, with their span.
+    labels: Vec<(String, Span)>,
+    /// targets of the `goto`s so far, with the span of the label in them.
+    gotos: Vec<(String, Span)>,
+}
+
+/// labels of a switch, with their span.
+#[derive(Default)]
+pub(crate) struct Switch {
+    pub(crate) cases: Vec<(i64, Span)>,
+    pub(crate) default: Option<Span>,
+}
+
+impl Parser {
+    pub(crate) fn parse_break_statement(&mut self) -> Result<Statement, Diagnostic> {
+        if self.jumps.loops == 0 && self.jumps.switches.is_empty() {
+            return Err(misplaced(
+                "`break` statement not in loop or switch statement",
+                self.current_span,
+            ));
+        }
+        self.expect_semicolon()?;
+        Ok(Statement::Break)
+    }
+
+    pub(crate) fn parse_continue_statement(&mut self) -> Result<Statement, Diagnostic> {
+        if self.jumps.loops == 0 {
+            return Err(misplaced(
+                "`continue` statement not in loop statement",
+                self.current_span,
+            ));
+        }
+        self.expect_semicolon()?;
+        Ok(Statement::Continue)
+    }
+
+    pub(crate) fn parse_goto_statement(&mut self) -> Result<Statement, Diagnostic> {
+        let Token::Identifier(name) = self.peeked_token.clone() else {
+            return Err(self.unexpected_peeked("label"));
+        };
+        self.next_token();
+        self.jumps.gotos.push((name.clone(), self.current_span));
+        self.expect_semicolon()?;
+        Ok(Statement::Goto(name))
+    }
+
+    /// `name: statement`, labels have a namespace of their own in the function.
+    pub(crate) fn parse_labeled_statement(
+        &mut self,
+        name: String,
+    ) -> Result<Statement, Diagnostic> {
+        let span = self.current_span;
+        if let Some((_, previous)) = self.jumps.labels.iter().find(|(l, _)| *l == name) {
+            return Err(
+                Diagnostic::error(format!("redefinition of label `{}`", name))
+                    .with_code("E0103")
+                    .with_primary_label(span, "redefined here")
+                    .with_secondary_label(*previous, "previously defined here"),
+            );
+        }
+        self.jumps.labels.push((name.clone(), span));
+        self.next_token();
+        self.next_token(); // skip ':'
+        let body = Box::new(self.parse_statement()?);
+        Ok(Statement::Label { name, body })
+    }
+
+    /// error for the first `goto` of the function whose label is not defined in it.
+    pub(crate) fn check_gotos(&mut self) -> Result<(), Diagnostic> {
+        let jumps = std::mem::take(&mut self.jumps);
+        match jumps
+            .gotos
+            .iter()
+            .find(|(name, _)| !jumps.labels.iter().any(|(l, _)| l == name))
+        {
+            Some((name, span)) => Err(Diagnostic::error(format!(
+                "use of undeclared label `{}`",
+                name
+            ))
+            .with_code("E0101")
+            .with_primary_label(*span, "not found in this function")),
+            None => Ok(()),
+        }
+    }
+
+    /// `;` after the current token, which is left on it.
+    fn expect_semicolon(&mut self) -> Result<(), Diagnostic> {
+        if self.peeked_token != Token::SemiColon {
+            return Err(self.unexpected_peeked("`;`"));
+        }
+        self.next_token();
+        Ok(())
+    }
+}
+
+/// error for a jump or a label where it cannot be.
+pub(crate) fn misplaced(message: &str, span: Span) -> Diagnostic {
+    Diagnostic::error(message)
+        .with_code("E0111")
+        .with_primary_label(span, "not allowed here")
+}
@@ -1,53 +1,118 @@
+use std::collections::HashMap;
+
 use ast::{Program, Statement, Type};
-use lex::{tokens::Token, Lexer};
+use diagnostic::Diagnostic;
+use lex::tokens::{SpannedToken, Token};
+use lex::Lexer;
+use span::Span;
 
 mod branch;
+mod declarator;
+mod enum_;
 mod expression;
 mod function;
+mod jump;
 mod loop_;
+mod scope;
+mod struct_;
 mod variable;
 
+use declarator::size_missing;
+use function::Function;
+use jump::Jumps;
+use scope::{Frame, Scope, Storage};
+
 // entry
-pub fn parse(input: Lexer) -> Result<Program, String> {
+/// the program with the warnings about it, or the errors followed by the warnings.
+pub fn parse(
+    input: Lexer,
+    options: &ParseOptions,
+) -> Result<(Program, Vec<Diagnostic>), Vec<Diagnostic>> {
     let mut parser = Parser::new(input);
+    parser.error_limit = options.error_limit;
     parser.parse()
 }
 
-struct LVar {
-    name: String,
-    offset: usize,
-    type_: Type,
+pub struct ParseOptions {
+    /// number of errors after which parsing stops, 0 for no limit.
+    pub error_limit: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self { error_limit: 20 }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd)]
 enum Precedence {
     Lowest,
+    Comma,
     Assignment,
+    Conditional,
+    LogicalOr,
+    LogicalAnd,
+    BitOr,
+    BitXor,
+    BitAnd,
     Equals,
     LessGreater,
+    Shift,
     Sum,
     Product,
-    LBracket,
+    Prefix,
+    Postfix,
 }
 
 struct Parser {
     lexer: Lexer,
     current_token: Token,
     peeked_token: Token,
-    locals: Vec<LVar>,
+    current_span: Span,
+    peeked_span: Span,
+    /// innermost last, the first one is the file scope.
+    scopes: Vec<Scope>,
+    frame: Frame,
+    jumps: Jumps,
+    /// functions declared so far.
+    functions: HashMap<String, Function>,
+    /// name of the function being parsed.
+    function: String,
+    /// labels of the block scope `static` variables of the function being parsed.
+    statics: Vec<String>,
+    errors: Vec<Diagnostic>,
+    warnings: Vec<Diagnostic>,
+    /// errors from the lexer not reported yet, they go before the next parse error.
+    lexical_errors: Vec<Diagnostic>,
+    error_limit: usize,
+    /// set once the error limit is reached, parsing then stops as soon as possible.
+    aborted: bool,
 }
 
 /// parser base
 impl Parser {
-    fn new(mut lexer: Lexer) -> Self {
-        let current_token = lexer.next();
-        let peeked_token = lexer.next();
-        Self {
+    fn new(lexer: Lexer) -> Self {
+        let mut parser = Self {
             lexer,
-            current_token,
-            peeked_token,
-            locals: Vec::new(),
-        }
+            current_token: Token::Eof,
+            peeked_token: Token::Eof,
+            current_span: Span::default(),
+            peeked_span: Span::default(),
+            scopes: vec![Scope::new(0)],
+            frame: Frame::default(),
+            jumps: Jumps::default(),
+            functions: HashMap::new(),
+            function: String::new(),
+            statics: Vec::new(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            lexical_errors: Vec::new(),
+            error_limit: ParseOptions::default().error_limit,
+            aborted: false,
+        };
+        parser.next_token();
+        parser.next_token();
+        parser
     }
 
     fn peek_precedence(&self) -> Precedence {
@@ -56,83 +121,308 @@ impl Parser {
 
     fn get_precedence(&self, token: Token) -> Precedence {
         match token {
-            Token::Assignment => Precedence::Assignment,
+            Token::Assignment
+            | Token::PlusAssignment
+            | Token::MinusAssignment
+            | Token::AsteriskAssignment
+            | Token::SlashAssignment
+            | Token::PercentAssignment
+            | Token::LShiftAssignment
+            | Token::RShiftAssignment
+            | Token::AmpersandAssignment
+            | Token::PipeAssignment
+            | Token::CaretAssignment => Precedence::Assignment,
+            Token::Comma => Precedence::Comma,
+            Token::Question => Precedence::Conditional,
+            Token::Or => Precedence::LogicalOr,
+            Token::And => Precedence::LogicalAnd,
+            Token::Pipe => Precedence::BitOr,
+            Token::Caret => Precedence::BitXor,
+            Token::Ampersand => Precedence::BitAnd,
             Token::Eq | Token::NotEq => Precedence::Equals,
             Token::Lt | Token::LtEq | Token::Gt | Token::GtEq => Precedence::LessGreater,
+            Token::LShift | Token::RShift => Precedence::Shift,
             Token::Plus | Token::Minus => Precedence::Sum,
-            Token::Slash | Token::Asterisk => Precedence::Product,
-            Token::LBracket => Precedence::LBracket,
+            Token::Slash | Token::Asterisk | Token::Percent => Precedence::Product,
+            Token::LBracket
+            | Token::LParen
+            | Token::Increment
+            | Token::Decrement
+            | Token::Dot
+            | Token::Arrow => Precedence::Postfix,
             _ => Precedence::Lowest,
         }
     }
 
     fn next_token(&mut self) {
-        self.current_token = self.peeked_token.clone();
-        self.peeked_token = self.lexer.next();
+        let peeked = self.lex();
+        self.current_token = std::mem::replace(&mut self.peeked_token, peeked.token);
+        self.current_span = std::mem::replace(&mut self.peeked_span, peeked.span);
+    }
+
+    /// next token of the lexer, setting aside the errors before it.
+    fn lex(&mut self) -> SpannedToken {
+        loop {
+            match self.lexer.next() {
+                Ok(token) => return token,
+                Err(e) => self.lexical_errors.push(e.into()),
+            }
+        }
+    }
+
+    /// `expected ..., found ...` error pointing at the current token.
+    fn unexpected_current(&self, expected: &str) -> Diagnostic {
+        Self::unexpected_token(expected, &self.current_token, self.current_span)
+    }
+
+    /// `expected ..., found ...` error pointing at the peeked token.
+    fn unexpected_peeked(&self, expected: &str) -> Diagnostic {
+        Self::unexpected_token(expected, &self.peeked_token, self.peeked_span)
+    }
+
+    /// records an error, together with the lexical errors found before it.
+    fn report(&mut self, diagnostic: Diagnostic) {
+        let lexical = std::mem::take(&mut self.lexical_errors);
+        for diagnostic in lexical.into_iter().chain(std::iter::once(diagnostic)) {
+            if self.aborted {
+                return;
+            }
+            self.errors.push(diagnostic);
+            if self.error_limit != 0 && self.errors.len() >= self.error_limit {
+                self.errors.push(
+                    Diagnostic::error("too many errors emitted, stopping now")
+                        .with_code("E0102")
+                        .with_note(format!(
+                            "the limit is {}, use `-ferror-limit=N` to change it",
+                            self.error_limit
+                        )),
+                );
+                self.aborted = true;
+            }
+        }
+    }
+
+    /// records a warning, which does not stop the compilation.
+    fn warn(&mut self, diagnostic: Diagnostic) {
+        self.warnings.push(diagnostic);
+    }
+
+    /// panic-mode recovery: skips the rest of an erroneous statement.
+    ///
+    /// stops after the next `;` or after the `}` closing a block opened within the statement,
+    /// or on the `}` closing the enclosing block, so that parsing resumes at a statement
+    /// boundary.
+    fn synchronize(&mut self) {
+        let mut depth = 0;
+        loop {
+            match self.current_token {
+                Token::Eof => return,
+                Token::SemiColon if depth == 0 => {
+                    self.next_token();
+                    return;
+                }
+                Token::LBrace => depth += 1,
+                Token::RBrace if depth == 0 => return,
+                Token::RBrace => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.next_token();
+                        return;
+                    }
+                }
+                _ => {}
+            }
+            self.next_token();
+        }
+    }
+
+    /// skips the rest of the body of a struct, union or enum after an error in it, up to and
+    /// including its `}`, so that `synchronize` then skips the rest of the declaration.
+    fn skip_body(&mut self) {
+        let mut depth = 0;
+        loop {
+            match self.current_token {
+                Token::Eof => return,
+                Token::LBrace => depth += 1,
+                Token::RBrace if depth == 0 => {
+                    self.next_token();
+                    return;
+                }
+                Token::RBrace => depth -= 1,
+                _ => {}
+            }
+            self.next_token();
+        }
+    }
+
+    fn unexpected_token(expected: &str, found: &Token, span: Span) -> Diagnostic {
+        let found = match found {
+            Token::Eof => String::from("end of file"),
+            t => format!("`{}`", t),
+        };
+        Diagnostic::error(format!("expected {}, found {}", expected, found))
+            .with_code("E0100")
+            .with_primary_label(span, format!("expected {}", expected))
     }
 }
 
 impl Parser {
-    fn parse(&mut self) -> Result<Program, String> {
+    fn parse(&mut self) -> Result<(Program, Vec<Diagnostic>), Vec<Diagnostic>> {
         let mut statements = Vec::new();
-        while self.current_token != Token::Eof {
-            statements.push(self.parse_statement()?);
-            self.next_token();
+        while self.current_token != Token::Eof && !self.aborted {
+            match self.parse_statement() {
+                Ok(statement) => {
+                    statements.push(statement);
+                    self.next_token();
+                }
+                Err(e) => {
+                    self.report(e);
+                    self.synchronize();
+                    if self.current_token == Token::RBrace {
+                        self.next_token(); // stray '}' at file scope
+                    }
+                }
+            }
+        }
+
+        for diagnostic in std::mem::take(&mut self.lexical_errors) {
+            self.report(diagnostic);
+        }
+        let warnings = std::mem::take(&mut self.warnings);
+        if self.errors.is_empty() {
+            Ok((Program::new(statements), warnings))
+        } else {
+            let mut errors = std::mem::take(&mut self.errors);
+            errors.extend(warnings);
+            Err(errors)
         }
-        Ok(Program::new(statements))
     }
 
-    fn parse_statement(&mut self) -> Result<Statement, String> {
+    fn parse_statement(&mut self) -> Result<Statement, Diagnostic> {
         match self.current_token {
             Token::If => self.parse_if_statement(),
             Token::While => self.parse_while_statement(),
             Token::For => self.parse_for_statement(),
+            Token::Do => self.parse_do_while_statement(),
+            Token::Switch => self.parse_switch_statement(),
+            Token::Case | Token::Default => self.parse_case_statement(),
+            Token::Break => self.parse_break_statement(),
+            Token::Continue => self.parse_continue_statement(),
+            Token::Goto => self.parse_goto_statement(),
             Token::Return => self.parse_return_statement(),
-            Token::LBrace => self.parse_block_statement(),
-            Token::Void
-            | Token::Char
-            | Token::Short
-            | Token::Int
-            | Token::Long
-            | Token::Float
-            | Token::Double => {
-                let (ty, name) = self.parse_type_declaration()?;
-                self.next_token();
-                match self.current_token.clone() {
-                    Token::Assignment | Token::SemiColon => {
-                        self.parse_variable_declaration(ty, name)
+            Token::LBrace => {
+                self.enter_scope();
+                let block = self.parse_block_statement();
+                self.leave_scope();
+                block
+            }
+            Token::Identifier(ref name) if self.peeked_token == Token::Colon => {
+                self.parse_labeled_statement(name.clone())
+            }
+            Token::Extern | Token::Static | Token::Typedef => self.parse_declaration(),
+            _ if self.starts_type_name(&self.current_token) => self.parse_declaration(),
+            _ => self.parse_expression_statement(),
+        }
+    }
+
+    /// declaration starting with a storage class or a type specifier, of any number of
+    /// declarators. several are grouped in a block.
+    fn parse_declaration(&mut self) -> Result<Statement, Diagnostic> {
+        let storage_class = self.current_token.clone();
+        if matches!(
+            storage_class,
+            Token::Extern | Token::Static | Token::Typedef
+        ) {
+            self.next_token();
+        }
+        let static_ = storage_class == Token::Static;
+        let specifier = self.current_token.clone();
+        let base = self.parse_base_type()?;
+        if matches!(specifier, Token::Struct | Token::Union | Token::Enum)
+            && self.peeked_token == Token::SemiColon
+        {
+            // only declares the tag or the enumerators, e.g. `struct s { int a; };`.
+            self.next_token();
+            return Ok(Statement::Block(vec![]));
+        }
+
+        let mut declarations = vec![];
+        loop {
+            let declarator = self.parse_init_declarator(base.clone())?;
+            let Some((name, span)) = declarator.name else {
+                return Err(self.unexpected_peeked("identifier"));
+            };
+            let type_ = declarator.type_;
+            if let Some(missing) = declarator.size_missing {
+                if storage_class == Token::Typedef || self.peeked_token != Token::Assignment {
+                    return Err(size_missing(missing));
+                }
+            }
+            match storage_class {
+                Token::Typedef => self.declare_name(name, type_, Storage::Typedef, span)?,
+                _ if matches!(type_, Type::Function { .. }) => {
+                    let parameters = declarator.parameters.unwrap_or_default();
+                    let definition = self.peeked_token == Token::LBrace;
+                    let function =
+                        self.parse_function_declaration(type_, name, span, parameters, static_)?;
+                    if definition && declarations.is_empty() {
+                        return Ok(function);
                     }
-                    Token::LParen => self.parse_function_declaration(name),
-                    _ => Err(format!(
-                        "expected token '=' or '(' but got {:?}",
-                        self.current_token
-                    )),
+                    declarations.push(function);
+                }
+                Token::Extern if self.peeked_token != Token::Assignment => {
+                    declarations.push(self.parse_extern_declaration(type_, name, span)?)
                 }
+                _ => declarations.push(self.parse_variable_declaration(
+                    type_,
+                    name,
+                    span,
+                    static_,
+                    declarator.size_missing.is_some(),
+                )?),
+            }
+
+            self.next_token();
+            match self.current_token {
+                Token::Comma => {}
+                Token::SemiColon => break,
+                _ => return Err(self.unexpected_current("`,` or `;`")),
             }
-            _ => self.parse_expression_statement(),
         }
+        Ok(match declarations.len() {
+            1 => declarations.remove(0),
+            _ => Statement::Block(declarations),
+        })
     }
 
-    fn parse_block_statement(&mut self) -> Result<Statement, String> {
+    fn parse_block_statement(&mut self) -> Result<Statement, Diagnostic> {
         self.next_token(); // skip '{'
         let mut statements = Vec::new();
-        while self.current_token != Token::RBrace {
-            statements.push(self.parse_statement()?);
-            self.next_token();
+        while self.current_token != Token::RBrace && !self.aborted {
+            if self.current_token == Token::Eof {
+                return Err(self.unexpected_current("`}`"));
+            }
+            match self.parse_statement() {
+                Ok(statement) => {
+                    statements.push(statement);
+                    self.next_token();
+                }
+                Err(e) => {
+                    self.report(e);
+                    self.synchronize();
+                }
+            }
         }
         Ok(Statement::Block(statements))
     }
 
-    fn parse_expression_statement(&mut self) -> Result<Statement, String> {
+    fn parse_expression_statement(&mut self) -> Result<Statement, Diagnostic> {
         let expr = self.parse_expression(Precedence::Lowest)?;
 
         if self.peeked_token == Token::SemiColon || self.peeked_token == Token::RParen {
             self.next_token();
         } else {
-            return Err(format!(
-                "expected token ';' or ')' but got {:?}",
-                self.peeked_token
-            ));
+            return Err(self.unexpected_peeked("`;` or `)`"));
         }
 
         Ok(Statement::Expression(expr))
@@ -141,36 +431,70 @@ impl Parser {
 
 #[cfg(test)]
 mod test {
-    use ast::{BinaryOperator, Expression, TypeEnum, UnaryOperator};
+    use ast::{
+        BinaryOperator, Expression, ExpressionKind, IntegerType, Type, TypeEnum, UnaryOperator,
+    };
 
     use super::*;
 
+    /// designator of a function returning `int`, taking `parameters` or any arguments.
+    fn function(name: &str, parameters: Option<Vec<Type>>) -> Box<Expression> {
+        Box::new(
+            ExpressionKind::Function {
+                name: String::from(name),
+                type_: Type::Function {
+                    return_type: Box::new(Type::Primitive(TypeEnum::Int)),
+                    variadic: false,
+                    prototyped: parameters.is_some(),
+                    parameters: parameters.unwrap_or_default(),
+                },
+            }
+            .into(),
+        )
+    }
+
     #[test]
     fn test_parse() {
         let cases = vec![
             (
                 String::from("5;1+2*3;"),
                 Program::new(vec![
-                    Statement::Expression(Expression::Integer(5)),
-                    Statement::Expression(Expression::Binary {
-                        lhs: Box::new(Expression::Integer(1)),
-                        op: BinaryOperator::Plus,
-                        rhs: Box::new(Expression::Binary {
-                            lhs: Box::new(Expression::Integer(2)),
-                            op: BinaryOperator::Asterisk,
-                            rhs: Box::new(Expression::Integer(3)),
-                        }),
-                    }),
+                    Statement::Expression(ExpressionKind::Integer(5, IntegerType::Int).into()),
+                    Statement::Expression(
+                        ExpressionKind::Binary {
+                            lhs: Box::new(ExpressionKind::Integer(1, IntegerType::Int).into()),
+                            op: BinaryOperator::Plus,
+                            rhs: Box::new(
+                                ExpressionKind::Binary {
+                                    lhs: Box::new(
+                                        ExpressionKind::Integer(2, IntegerType::Int).into(),
+                                    ),
+                                    op: BinaryOperator::Asterisk,
+                                    rhs: Box::new(
+                                        ExpressionKind::Integer(3, IntegerType::Int).into(),
+                                    ),
+                                }
+                                .into(),
+                            ),
+                        }
+                        .into(),
+                    ),
                 ]),
             ),
             (
                 String::from("bar(1, 2); return 0;"),
                 Program::new(vec![
-                    Statement::Expression(Expression::Call {
-                        callee_name: String::from("bar"),
-                        arguments: vec![Expression::Integer(1), Expression::Integer(2)],
-                    }),
-                    Statement::Return(Expression::Integer(0)),
+                    Statement::Expression(
+                        ExpressionKind::Call {
+                            callee: function("bar", None),
+                            arguments: vec![
+                                ExpressionKind::Integer(1, IntegerType::Int).into(),
+                                ExpressionKind::Integer(2, IntegerType::Int).into(),
+                            ],
+                        }
+                        .into(),
+                    ),
+                    Statement::Return(Some(ExpressionKind::Integer(0, IntegerType::Int).into())),
                 ]),
             ),
             (
@@ -187,32 +511,54 @@ mod test {
                 Program::new(vec![
                     Statement::FunctionDefinition {
                         name: String::from("foo"),
-                        arguments: vec![Expression::LocalVariable {
-                            name: String::from("i"),
-                            offset: 8,
-                            type_: Type::Primitive(TypeEnum::Int),
-                        }],
-                        body: vec![Statement::Return(Expression::LocalVariable {
+                        return_type: Type::Primitive(TypeEnum::Int),
+                        arguments: vec![ExpressionKind::LocalVariable {
                             name: String::from("i"),
-                            offset: 8,
+                            offset: 0,
                             type_: Type::Primitive(TypeEnum::Int),
-                        })],
+                        }
+                        .into()],
+                        body: vec![Statement::Return(Some(
+                            ExpressionKind::LocalVariable {
+                                name: String::from("i"),
+                                offset: 0,
+                                type_: Type::Primitive(TypeEnum::Int),
+                            }
+                            .into(),
+                        ))],
+                        stack_size: 16,
+                        static_: false,
                     },
                     Statement::FunctionDefinition {
                         name: String::from("main"),
+                        return_type: Type::Primitive(TypeEnum::Int),
                         arguments: vec![],
                         body: vec![
                             Statement::InitDeclaration {
                                 name: String::from("a"),
-                                offset: 16,
+                                offset: 0,
                                 type_: Type::Primitive(TypeEnum::Int),
-                                init: Some(Expression::Call {
-                                    callee_name: String::from("foo"),
-                                    arguments: vec![Expression::Integer(10)],
-                                }),
+                                init: Some(
+                                    ExpressionKind::Call {
+                                        callee: function(
+                                            "foo",
+                                            Some(vec![Type::Primitive(TypeEnum::Int)]),
+                                        ),
+                                        arguments: vec![ExpressionKind::Integer(
+                                            10,
+                                            IntegerType::Int,
+                                        )
+                                        .into()],
+                                    }
+                                    .into(),
+                                ),
                             },
-                            Statement::Return(Expression::Integer(10)),
+                            Statement::Return(Some(
+                                ExpressionKind::Integer(10, IntegerType::Int).into(),
+                            )),
                         ],
+                        stack_size: 16,
+                        static_: false,
                     },
                 ]),
             ),
@@ -233,58 +579,91 @@ mod test {
                 Program::new(vec![
                     Statement::FunctionDefinition {
                         name: String::from("one"),
-                        arguments: vec![Expression::LocalVariable {
+                        return_type: Type::Primitive(TypeEnum::Int),
+                        arguments: vec![ExpressionKind::LocalVariable {
                             name: String::from("x"),
-                            offset: 8,
+                            offset: 0,
                             type_: Type::Pointer(Box::new(Type::Primitive(TypeEnum::Int))),
-                        }],
+                        }
+                        .into()],
                         body: vec![
-                            Statement::Expression(Expression::Binary {
-                                lhs: Box::new(Expression::Unary {
-                                    expr: Box::new(Expression::LocalVariable {
-                                        name: String::from("x"),
-                                        offset: 8,
-                                        type_: Type::Pointer(Box::new(Type::Primitive(
-                                            TypeEnum::Int,
-                                        ))),
-                                    }),
-                                    op: UnaryOperator::Dereference,
-                                }),
-                                op: BinaryOperator::Assignment,
-                                rhs: Box::new(Expression::Integer(1)),
-                            }),
-                            Statement::Return(Expression::Integer(0)),
+                            Statement::Expression(
+                                ExpressionKind::Binary {
+                                    lhs: Box::new(
+                                        ExpressionKind::Unary {
+                                            expr: Box::new(
+                                                ExpressionKind::LocalVariable {
+                                                    name: String::from("x"),
+                                                    offset: 0,
+                                                    type_: Type::Pointer(Box::new(
+                                                        Type::Primitive(TypeEnum::Int),
+                                                    )),
+                                                }
+                                                .into(),
+                                            ),
+                                            op: UnaryOperator::Dereference,
+                                        }
+                                        .into(),
+                                    ),
+                                    op: BinaryOperator::Assignment,
+                                    rhs: Box::new(
+                                        ExpressionKind::Integer(1, IntegerType::Int).into(),
+                                    ),
+                                }
+                                .into(),
+                            ),
+                            Statement::Return(Some(
+                                ExpressionKind::Integer(0, IntegerType::Int).into(),
+                            )),
                         ],
+                        stack_size: 16,
+                        static_: false,
                     },
                     Statement::FunctionDefinition {
                         name: String::from("main"),
+                        return_type: Type::Primitive(TypeEnum::Int),
                         arguments: vec![],
                         body: vec![
                             Statement::InitDeclaration {
                                 name: String::from("x"),
-                                offset: 16,
+                                offset: 0,
                                 type_: Type::Primitive(TypeEnum::Int),
-                                init: Some(Expression::Integer(0)),
+                                init: Some(ExpressionKind::Integer(0, IntegerType::Int).into()),
                             },
-                            Statement::Expression(Expression::Call {
-                                callee_name: String::from("one"),
-                                arguments: vec![Expression::Unary {
-                                    expr: Box::new(Expression::LocalVariable {
-                                        name: String::from("x"),
-                                        offset: 8,
-                                        type_: Type::Pointer(Box::new(Type::Primitive(
+                            Statement::Expression(
+                                ExpressionKind::Call {
+                                    callee: function(
+                                        "one",
+                                        Some(vec![Type::Pointer(Box::new(Type::Primitive(
                                             TypeEnum::Int,
-                                        ))),
-                                    }),
-                                    op: UnaryOperator::Reference,
-                                }],
-                            }),
-                            Statement::Return(Expression::LocalVariable {
-                                name: String::from("x"),
-                                offset: 8,
-                                type_: Type::Pointer(Box::new(Type::Primitive(TypeEnum::Int))),
-                            }),
+                                        )))]),
+                                    ),
+                                    arguments: vec![ExpressionKind::Unary {
+                                        expr: Box::new(
+                                            ExpressionKind::LocalVariable {
+                                                name: String::from("x"),
+                                                offset: 0,
+                                                type_: Type::Primitive(TypeEnum::Int),
+                                            }
+                                            .into(),
+                                        ),
+                                        op: UnaryOperator::Reference,
+                                    }
+                                    .into()],
+                                }
+                                .into(),
+                            ),
+                            Statement::Return(Some(
+                                ExpressionKind::LocalVariable {
+                                    name: String::from("x"),
+                                    offset: 0,
+                                    type_: Type::Primitive(TypeEnum::Int),
+                                }
+                                .into(),
+                            )),
                         ],
+                        stack_size: 16,
+                        static_: false,
                     },
                 ]),
             ),
@@ -293,7 +672,7 @@ mod test {
         for (input, expected) in cases {
             let lexer = Lexer::new(input);
             let mut parser = Parser::new(lexer);
-            assert_eq!(parser.parse().unwrap(), expected);
+            assert_eq!(parser.parse().unwrap().0, expected);
         }
     }
 
@@ -303,35 +682,51 @@ mod test {
             (String::from("{}"), Statement::Block(vec![])),
             (
                 String::from("{ return 0; }"),
-                Statement::Block(vec![Statement::Return(Expression::Integer(0))]),
+                Statement::Block(vec![Statement::Return(Some(
+                    ExpressionKind::Integer(0, IntegerType::Int).into(),
+                ))]),
             ),
             (
                 String::from("{ int i = 0; i = i + 1; return 0; }"),
                 Statement::Block(vec![
                     Statement::InitDeclaration {
                         name: String::from("i"),
-                        offset: 8,
+                        offset: 0,
                         type_: Type::Primitive(TypeEnum::Int),
-                        init: Some(Expression::Integer(0)),
+                        init: Some(ExpressionKind::Integer(0, IntegerType::Int).into()),
                     },
-                    Statement::Expression(Expression::Binary {
-                        lhs: Box::new(Expression::LocalVariable {
-                            name: String::from("i"),
-                            offset: 8,
-                            type_: Type::Primitive(TypeEnum::Int),
-                        }),
-                        op: BinaryOperator::Assignment,
-                        rhs: Box::new(Expression::Binary {
-                            lhs: Box::new(Expression::LocalVariable {
-                                name: String::from("i"),
-                                offset: 8,
-                                type_: Type::Primitive(TypeEnum::Int),
-                            }),
-                            op: BinaryOperator::Plus,
-                            rhs: Box::new(Expression::Integer(1)),
-                        }),
-                    }),
-                    Statement::Return(Expression::Integer(0)),
+                    Statement::Expression(
+                        ExpressionKind::Binary {
+                            lhs: Box::new(
+                                ExpressionKind::LocalVariable {
+                                    name: String::from("i"),
+                                    offset: 0,
+                                    type_: Type::Primitive(TypeEnum::Int),
+                                }
+                                .into(),
+                            ),
+                            op: BinaryOperator::Assignment,
+                            rhs: Box::new(
+                                ExpressionKind::Binary {
+                                    lhs: Box::new(
+                                        ExpressionKind::LocalVariable {
+                                            name: String::from("i"),
+                                            offset: 0,
+                                            type_: Type::Primitive(TypeEnum::Int),
+                                        }
+                                        .into(),
+                                    ),
+                                    op: BinaryOperator::Plus,
+                                    rhs: Box::new(
+                                        ExpressionKind::Integer(1, IntegerType::Int).into(),
+                                    ),
+                                }
+                                .into(),
+                            ),
+                        }
+                        .into(),
+                    ),
+                    Statement::Return(Some(ExpressionKind::Integer(0, IntegerType::Int).into())),
                 ]),
             ),
         ];
@@ -342,4 +737,385 @@ mod test {
             assert_eq!(parser.parse_statement().unwrap(), expected);
         }
     }
+
+    #[test]
+    fn test_scope() {
+        let input = String::from("int f(int a) { int b; { char a; int c; } { int d; } return a; }");
+        let statements = Parser::new(Lexer::new(input)).parse().unwrap().0.statements;
+        let Statement::FunctionDefinition { body, stack_size, .. } = &statements[0] else {
+            panic!("expected a function definition");
+        };
+        let offset = |e: &Expression| match e.kind {
+            ExpressionKind::LocalVariable { offset, .. } => offset,
+            _ => panic!("expected a variable"),
+        };
+        let declared = |s: &Statement| match s {
+            Statement::InitDeclaration { offset, .. } => *offset,
+            _ => panic!("expected a declaration"),
+        };
+        let blocks = body
+            .iter()
+            .filter_map(|s| match s {
+                Statement::Block(block) => Some(block.iter().map(declared).collect::<Vec<_>>()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(declared(&body[0]), 4);
+        // `c` after the 1 byte of `a`, `d` reuses the space of the first block.
+        assert_eq!(blocks, vec![vec![8, 12], vec![8]]);
+        let Statement::Return(Some(a)) = &body[3] else {
+            panic!("expected a return");
+        };
+        assert_eq!(offset(a), 0);
+        assert_eq!(*stack_size, 16);
+    }
+
+    #[test]
+    fn test_parse_typedef() {
+        let input = String::from(
+            "typedef struct { int x; } point; typedef point *handle; int f(handle h) { point p; return h->x; }",
+        );
+        let statements = Parser::new(Lexer::new(input)).parse().unwrap().0.statements;
+        let Statement::FunctionDefinition {
+            arguments, body, ..
+        } = &statements[2]
+        else {
+            panic!("expected a function definition");
+        };
+        assert_eq!(arguments[0].to_string(), "h");
+        let Statement::InitDeclaration { type_, .. } = &body[0] else {
+            panic!("expected a declaration");
+        };
+        assert_eq!(type_.to_string(), "struct <anonymous>");
+        let Statement::Return(Some(expr)) = &body[1] else {
+            panic!("expected a return");
+        };
+        assert_eq!(expr.to_string(), "h->x");
+    }
+
+    #[test]
+    fn test_error_location() {
+        let cases = vec![
+            (
+                String::from("int main() {\n    return 0\n}"),
+                String::from("error[E0100]: expected `;`, found `}`"),
+                (3, 1),
+            ),
+            (
+                String::from("int main() {\n    return x;\n}"),
+                String::from("error[E0101]: use of undeclared identifier `x`"),
+                (2, 12),
+            ),
+            (
+                String::from("int main() {\n    return 1 @ 2;\n}"),
+                String::from("error[E0001]: invalid character `@`"),
+                (2, 14),
+            ),
+            (
+                String::from("int main() {\n    char *s = \"abc;\n    return 0;\n}"),
+                String::from("error[E0002]: missing terminating \" character"),
+                (2, 15),
+            ),
+            (
+                String::from("int main() {\n    return 0;\n}\n/* comment"),
+                String::from("error[E0002]: unterminated comment"),
+                (4, 1),
+            ),
+            (
+                String::from("int main() {\n    int a;\n    int a;\n}"),
+                String::from("error[E0103]: redefinition of `a`"),
+                (3, 9),
+            ),
+            (
+                String::from("int f(int a) {\n    int a;\n}"),
+                String::from("error[E0103]: redefinition of `a`"),
+                (2, 9),
+            ),
+            (
+                String::from("int f(int a, int a) {\n    return a;\n}"),
+                String::from("error[E0103]: redefinition of `a`"),
+                (1, 18),
+            ),
+            (
+                String::from("int f() {\n    return 0;\n}\nint f() {\n    return 1;\n}"),
+                String::from("error[E0103]: redefinition of `f`"),
+                (4, 5),
+            ),
+            (
+                String::from("int main() {\n    return b;\n    int b;\n}"),
+                String::from("error[E0101]: use of undeclared identifier `b`"),
+                (2, 12),
+            ),
+            (
+                String::from("int a = 1;\nint a = 2;"),
+                String::from("error[E0103]: redefinition of `a`"),
+                (2, 5),
+            ),
+            (
+                String::from("int a;\nchar a;"),
+                String::from("error[E0103]: redefinition of `a`"),
+                (2, 6),
+            ),
+            (
+                String::from("int f(int a);\nchar f(int a) {\n    return a;\n}"),
+                String::from("error[E0104]: conflicting types for `f`"),
+                (2, 6),
+            ),
+            (
+                String::from("struct s;\nint main() {\n    struct s v;\n}"),
+                String::from("error[E0107]: variable has incomplete type `struct s`"),
+                (3, 14),
+            ),
+            (
+                String::from("struct s { int a; };\nint f(struct s *p) {\n    return p->b;\n}"),
+                String::from("error[E0108]: no member named `b` in `struct s`"),
+                (3, 15),
+            ),
+            (
+                String::from("int main() {\n    int a;\n    return a.b;\n}"),
+                String::from("error[E0108]: `a` is not a structure or union"),
+                (3, 13),
+            ),
+            (
+                String::from("struct s { int a; };\nunion s { int a; };"),
+                String::from("error[E0103]: redefinition of `s`"),
+                (2, 7),
+            ),
+            (
+                String::from("int main() {\n    int x;\n    enum { A = x };\n}"),
+                String::from(
+                    "error[E0109]: value `x` of `A` is not an integer constant expression",
+                ),
+                (3, 12),
+            ),
+            (
+                String::from("typedef int T;\nint main() {\n    return T;\n}"),
+                String::from("error[E0100]: unexpected type name `T`"),
+                (3, 12),
+            ),
+            (
+                String::from("int main() {\n    enum e x;\n}"),
+                String::from("error[E0101]: use of undeclared enum `e`"),
+                (2, 10),
+            ),
+            (
+                String::from("int main() {\n    if (1)\n        break;\n}"),
+                String::from("error[E0111]: `break` statement not in loop or switch statement"),
+                (3, 9),
+            ),
+            (
+                String::from("int main() {\n    switch (1) { continue; }\n}"),
+                String::from("error[E0111]: `continue` statement not in loop statement"),
+                (2, 18),
+            ),
+            (
+                String::from("int main() {\n    default: return 0;\n}"),
+                String::from("error[E0111]: `default` statement not in switch statement"),
+                (2, 5),
+            ),
+            (
+                String::from("int main() {\n    switch (1) {\n    case 1:\n    case 2 - 1:\n        return 0;\n    }\n}"),
+                String::from("error[E0112]: duplicate case value `1`"),
+                (4, 5),
+            ),
+            (
+                String::from("int main() {\n    goto end;\n}"),
+                String::from("error[E0101]: use of undeclared label `end`"),
+                (2, 10),
+            ),
+            (
+                String::from("int main() {\nl:\nl:\n    return 0;\n}"),
+                String::from("error[E0103]: redefinition of label `l`"),
+                (3, 1),
+            ),
+            (
+                String::from("int main() {\n    return (int x)1;\n}"),
+                String::from("error[E0100]: expected `)`, found `x`"),
+                (2, 17),
+            ),
+            (
+                String::from("int main() {\n    int a[];\n}"),
+                String::from("error[E0107]: array size missing"),
+                (2, 10),
+            ),
+            (
+                String::from("int main() {\n    int a[] = 5;\n}"),
+                String::from("error[E0107]: array initializer must be an initializer list or a string literal, not `5`"),
+                (2, 15),
+            ),
+            (
+                String::from("int main() {\n    int n;\n    int a[n];\n}"),
+                String::from("error[E0109]: array size `n` is not an integer constant expression"),
+                (3, 11),
+            ),
+            (
+                String::from("int main() {\n    int (*p[2];\n}"),
+                String::from("error[E0100]: expected `)`, found `;`"),
+                (2, 15),
+            ),
+            (
+                String::from("int main() {\n    { int a; }\n    return a;\n}"),
+                String::from("error[E0101]: use of undeclared identifier `a`"),
+                (3, 12),
+            ),
+            (
+                String::from("int main() {\n    for (int i = 0; i < 1; i++) {}\n    return i;\n}"),
+                String::from("error[E0101]: use of undeclared identifier `i`"),
+                (3, 12),
+            ),
+            (
+                String::from("struct s;\nint main() {\n    return sizeof(struct s);\n}"),
+                String::from(
+                    "error[E0107]: invalid application of `sizeof` to an incomplete type `struct s`",
+                ),
+                (3, 12),
+            ),
+            (
+                String::from("int main() {\n    return _Alignof(void);\n}"),
+                String::from(
+                    "error[E0107]: invalid application of `_Alignof` to an incomplete type `void`",
+                ),
+                (2, 12),
+            ),
+            (
+                String::from("int main() {\n    unsigned long double d;\n}"),
+                String::from("error[E0114]: cannot combine `double` with `unsigned long`"),
+                (2, 19),
+            ),
+            (
+                String::from("int main() {\n    long const double d;\n}"),
+                String::from("error[E0300]: `long double` is not supported"),
+                (2, 23),
+            ),
+            (
+                String::from("int main() {\n    return sizeof main;\n}"),
+                String::from(
+                    "error[E0113]: invalid application of `sizeof` to a function type `int ()`",
+                ),
+                (2, 12),
+            ),
+        ];
+
+        for (input, expected, (line, column)) in cases {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+            let errors = parser.parse().unwrap_err();
+            assert_eq!(errors[0].to_string(), expected);
+            let span = errors[0].primary_span().unwrap();
+            assert_eq!((span.line, span.column), (line, column));
+        }
+    }
+
+    #[test]
+    fn test_error_recovery() {
+        let input = String::from(
+            r#"
+                int main() {
+                    int a = 1
+                    a = a +;
+                    if (a == 1) {
+                        return );
+                    }
+                    while (a < 10 {
+                        a = a + 1;
+                    }
+                    return a;
+                }
+                int foo() {
+                    return 0
+                }
+                }
+                int bar() {
+                    return 1;
+            "#,
+        );
+        let expected = vec![
+            (
+                String::from("error[E0100]: expected `,` or `;`, found `a`"),
+                (4, 21),
+            ),
+            (
+                String::from("error[E0100]: expected expression, found `)`"),
+                (6, 32),
+            ),
+            (
+                String::from("error[E0100]: expected `)`, found `{`"),
+                (8, 35),
+            ),
+            (
+                String::from("error[E0100]: expected `;`, found `}`"),
+                (15, 17),
+            ),
+            (
+                String::from("error[E0100]: expected expression, found `}`"),
+                (16, 17),
+            ),
+            (
+                String::from("error[E0100]: expected `}`, found end of file"),
+                (19, 13),
+            ),
+        ];
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let errors = parser.parse().unwrap_err();
+        let errors = errors
+            .iter()
+            .map(|e| {
+                let span = e.primary_span().unwrap();
+                (e.to_string(), (span.line, span.column))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(errors, expected);
+    }
+
+    #[test]
+    fn test_error_recovery_in_body() {
+        let input = String::from(
+            "struct s { int a; int a; } v;\nenum e { A, B C };\nint main() {\n    return x;\n}",
+        );
+        let errors = Parser::new(Lexer::new(input)).parse().unwrap_err();
+        let errors = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec![
+                String::from("error[E0103]: redefinition of `a`"),
+                String::from("error[E0100]: expected `,` or `}`, found `C`"),
+                String::from("error[E0101]: use of undeclared identifier `x`"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_malformed_literals() {
+        // each literal is parsed as if it were valid, after its error.
+        let cases = vec![
+            ("0x", "error[E0004]: expected digits after the base prefix"),
+            ("09", "error[E0004]: invalid digit `9` in octal constant"),
+            ("1e", "error[E0004]: invalid floating constant `1e`"),
+            ("'ab'", "error[E0003]: multi-character character constant"),
+            ("\"\\q\"", "error[E0003]: unknown escape sequence `\\q`"),
+        ];
+        for (literal, expected) in cases {
+            let input = format!("int main() {{\n    return {};\n}}", literal);
+            let errors = Parser::new(Lexer::new(input)).parse().unwrap_err();
+            let errors = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+            assert_eq!(errors, vec![String::from(expected)], "{}", literal);
+        }
+    }
+
+    #[test]
+    fn test_error_limit() {
+        let input = String::from("int main() { 1 +; 2 +; 3 +; 4 +; return 0; }");
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        parser.error_limit = 2;
+        let errors = parser.parse().unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(
+            errors[2].to_string(),
+            "error[E0102]: too many errors emitted, stopping now"
+        );
+    }
 }
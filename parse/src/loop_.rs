@@ -1,19 +1,17 @@
 use ast::Statement;
+use diagnostic::Diagnostic;
 use lex::tokens::Token;
 
 use crate::{Parser, Precedence};
 
 impl Parser {
-    pub(super) fn parse_while_statement(&mut self) -> Result<Statement, String> {
+    pub(super) fn parse_while_statement(&mut self) -> Result<Statement, Diagnostic> {
         self.next_token(); // skip 'while'
 
         if self.current_token == Token::LParen {
             self.next_token();
         } else {
-            return Err(format!(
-                "expected token '(' but got {:?}",
-                self.current_token
-            ));
+            return Err(self.unexpected_current("`(`"));
         }
 
         let condition = self.parse_expression(Precedence::Lowest)?;
@@ -22,36 +20,70 @@ impl Parser {
             self.next_token(); // skip current
             self.next_token(); // skip ')'
         } else {
-            return Err(format!(
-                "expected token ')' but got {:?}",
-                self.peeked_token
-            ));
+            return Err(self.unexpected_peeked("`)`"));
         }
 
-        let body = Box::new(self.parse_statement()?);
+        let body = Box::new(self.parse_loop_body()?);
         Ok(Statement::While { condition, body })
     }
 
-    pub(super) fn parse_for_statement(&mut self) -> Result<Statement, String> {
+    pub(super) fn parse_do_while_statement(&mut self) -> Result<Statement, Diagnostic> {
+        self.next_token(); // skip 'do'
+        let body = Box::new(self.parse_loop_body()?);
+
+        if self.peeked_token != Token::While {
+            return Err(self.unexpected_peeked("`while`"));
+        }
+        self.next_token();
+        if self.peeked_token != Token::LParen {
+            return Err(self.unexpected_peeked("`(`"));
+        }
+        self.next_token();
+        self.next_token(); // skip '('
+
+        let condition = self.parse_expression(Precedence::Lowest)?;
+
+        if self.peeked_token != Token::RParen {
+            return Err(self.unexpected_peeked("`)`"));
+        }
+        self.next_token();
+        if self.peeked_token != Token::SemiColon {
+            return Err(self.unexpected_peeked("`;`"));
+        }
+        self.next_token();
+
+        Ok(Statement::DoWhile { body, condition })
+    }
+
+    /// `for` statement, whose init clause may declare variables, e.g. `int i = 0`. they are in
+    /// scope until the end of its body.
+    pub(super) fn parse_for_statement(&mut self) -> Result<Statement, Diagnostic> {
+        self.enter_scope();
+        let for_ = self.parse_for_clauses();
+        self.leave_scope();
+        for_
+    }
+
+    fn parse_for_clauses(&mut self) -> Result<Statement, Diagnostic> {
         self.next_token(); // skip 'for'
 
         if self.current_token == Token::LParen {
             self.next_token();
         } else {
-            return Err(format!(
-                "expected token '(' but got {:?}",
-                self.current_token
-            ));
+            return Err(self.unexpected_current("`(`"));
         }
 
         let init = if self.current_token == Token::SemiColon {
             None
+        } else if self.starts_type_name(&self.current_token) {
+            Some(Box::new(self.parse_declaration()?))
         } else {
             Some(Box::new(self.parse_expression_statement()?))
         };
         self.next_token(); // skip ';'
 
         let condition = if self.current_token == Token::SemiColon {
+            self.next_token(); // skip ';'
             None
         } else {
             let expr = self.parse_expression(Precedence::Lowest)?;
@@ -60,14 +92,12 @@ impl Parser {
                 self.next_token();
                 Some(expr)
             } else {
-                return Err(format!(
-                    "expected token ';' but got {:?}",
-                    self.current_token
-                ));
+                return Err(self.unexpected_current("`;`"));
             }
         };
 
         let post = if self.current_token == Token::RParen {
+            self.next_token(); // skip ')'
             None
         } else {
             let expr = self.parse_statement()?;
@@ -75,14 +105,11 @@ impl Parser {
                 self.next_token();
                 Some(Box::new(expr))
             } else {
-                return Err(format!(
-                    "expected token ')' but got {:?}",
-                    self.current_token
-                ));
+                return Err(self.unexpected_current("`)`"));
             }
         };
 
-        let body = Box::new(self.parse_statement()?);
+        let body = Box::new(self.parse_loop_body()?);
 
         Ok(Statement::For {
             init,
@@ -91,11 +118,19 @@ impl Parser {
             body,
         })
     }
+
+    /// body of a loop, where `break` and `continue` may appear.
+    fn parse_loop_body(&mut self) -> Result<Statement, Diagnostic> {
+        self.jumps.loops += 1;
+        let body = self.parse_statement();
+        self.jumps.loops -= 1;
+        body
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use ast::{BinaryOperator, Expression, Type, TypeEnum};
+    use ast::{BinaryOperator, ExpressionKind, IntegerType, Type, TypeEnum};
     use lex::Lexer;
 
     use super::*;
@@ -107,21 +142,27 @@ mod test {
             vec![
                 Statement::InitDeclaration {
                     name: String::from("a"),
-                    offset: 8,
+                    offset: 0,
                     type_: Type::Primitive(TypeEnum::Int),
-                    init: Some(Expression::Integer(0)),
+                    init: Some(ExpressionKind::Integer(0, IntegerType::Int).into()),
                 },
                 Statement::While {
-                    condition: Expression::Binary {
-                        lhs: Box::new(Expression::LocalVariable {
-                            name: String::from("a"),
-                            offset: 8,
-                            type_: Type::Primitive(TypeEnum::Int),
-                        }),
+                    condition: ExpressionKind::Binary {
+                        lhs: Box::new(
+                            ExpressionKind::LocalVariable {
+                                name: String::from("a"),
+                                offset: 0,
+                                type_: Type::Primitive(TypeEnum::Int),
+                            }
+                            .into(),
+                        ),
                         op: BinaryOperator::Eq,
-                        rhs: Box::new(Expression::Integer(0)),
-                    },
-                    body: Box::new(Statement::Return(Expression::Integer(0))),
+                        rhs: Box::new(ExpressionKind::Integer(0, IntegerType::Int).into()),
+                    }
+                    .into(),
+                    body: Box::new(Statement::Return(Some(
+                        ExpressionKind::Integer(0, IntegerType::Int).into(),
+                    ))),
                 },
             ],
         )];
@@ -129,7 +170,8 @@ mod test {
         for (input, expected) in cases {
             let lexer = Lexer::new(input);
             let mut parser = Parser::new(lexer);
-            assert_eq!(parser.parse().unwrap().statements, expected);
+            parser.enter_scope();
+            assert_eq!(parser.parse().unwrap().0.statements, expected);
         }
     }
 
@@ -140,47 +182,75 @@ mod test {
             vec![
                 Statement::InitDeclaration {
                     name: String::from("i"),
-                    offset: 8,
+                    offset: 0,
                     type_: Type::Primitive(TypeEnum::Int),
-                    init: Some(Expression::Integer(0)),
+                    init: Some(ExpressionKind::Integer(0, IntegerType::Int).into()),
                 },
                 Statement::For {
-                    init: Some(Box::new(Statement::Expression(Expression::Binary {
-                        lhs: Box::new(Expression::LocalVariable {
-                            name: String::from("i"),
-                            offset: 8,
-                            type_: Type::Primitive(TypeEnum::Int),
-                        }),
-                        op: BinaryOperator::Assignment,
-                        rhs: Box::new(Expression::Integer(0)),
-                    }))),
-                    condition: Some(Expression::Binary {
-                        lhs: Box::new(Expression::LocalVariable {
-                            name: String::from("i"),
-                            offset: 8,
-                            type_: Type::Primitive(TypeEnum::Int),
-                        }),
-                        op: BinaryOperator::Lt,
-                        rhs: Box::new(Expression::Integer(10)),
-                    }),
-                    post: Some(Box::new(Statement::Expression(Expression::Binary {
-                        lhs: Box::new(Expression::LocalVariable {
-                            name: String::from("i"),
-                            offset: 8,
-                            type_: Type::Primitive(TypeEnum::Int),
-                        }),
-                        op: BinaryOperator::Assignment,
-                        rhs: Box::new(Expression::Binary {
-                            lhs: Box::new(Expression::LocalVariable {
-                                name: String::from("i"),
-                                offset: 8,
-                                type_: Type::Primitive(TypeEnum::Int),
-                            }),
-                            op: BinaryOperator::Plus,
-                            rhs: Box::new(Expression::Integer(1)),
-                        }),
-                    }))),
-                    body: Box::new(Statement::Return(Expression::Integer(0))),
+                    init: Some(Box::new(Statement::Expression(
+                        ExpressionKind::Binary {
+                            lhs: Box::new(
+                                ExpressionKind::LocalVariable {
+                                    name: String::from("i"),
+                                    offset: 0,
+                                    type_: Type::Primitive(TypeEnum::Int),
+                                }
+                                .into(),
+                            ),
+                            op: BinaryOperator::Assignment,
+                            rhs: Box::new(ExpressionKind::Integer(0, IntegerType::Int).into()),
+                        }
+                        .into(),
+                    ))),
+                    condition: Some(
+                        ExpressionKind::Binary {
+                            lhs: Box::new(
+                                ExpressionKind::LocalVariable {
+                                    name: String::from("i"),
+                                    offset: 0,
+                                    type_: Type::Primitive(TypeEnum::Int),
+                                }
+                                .into(),
+                            ),
+                            op: BinaryOperator::Lt,
+                            rhs: Box::new(ExpressionKind::Integer(10, IntegerType::Int).into()),
+                        }
+                        .into(),
+                    ),
+                    post: Some(Box::new(Statement::Expression(
+                        ExpressionKind::Binary {
+                            lhs: Box::new(
+                                ExpressionKind::LocalVariable {
+                                    name: String::from("i"),
+                                    offset: 0,
+                                    type_: Type::Primitive(TypeEnum::Int),
+                                }
+                                .into(),
+                            ),
+                            op: BinaryOperator::Assignment,
+                            rhs: Box::new(
+                                ExpressionKind::Binary {
+                                    lhs: Box::new(
+                                        ExpressionKind::LocalVariable {
+                                            name: String::from("i"),
+                                            offset: 0,
+                                            type_: Type::Primitive(TypeEnum::Int),
+                                        }
+                                        .into(),
+                                    ),
+                                    op: BinaryOperator::Plus,
+                                    rhs: Box::new(
+                                        ExpressionKind::Integer(1, IntegerType::Int).into(),
+                                    ),
+                                }
+                                .into(),
+                            ),
+                        }
+                        .into(),
+                    ))),
+                    body: Box::new(Statement::Return(Some(
+                        ExpressionKind::Integer(0, IntegerType::Int).into(),
+                    ))),
                 },
             ],
         )];
@@ -188,7 +258,61 @@ mod test {
         for (input, expected) in cases {
             let lexer = Lexer::new(input);
             let mut parser = Parser::new(lexer);
-            assert_eq!(parser.parse().unwrap().statements, expected);
+            parser.enter_scope();
+            assert_eq!(parser.parse().unwrap().0.statements, expected);
         }
     }
+
+    #[test]
+    fn test_parse_do_while_statement() {
+        let input = String::from("int f() { do { continue; } while (0); l: goto l; }");
+        let statements = Parser::new(Lexer::new(input)).parse().unwrap().0.statements;
+        let Statement::FunctionDefinition { body, .. } = &statements[0] else {
+            panic!("expected a function definition");
+        };
+        assert_eq!(
+            body[..],
+            [
+                Statement::DoWhile {
+                    body: Box::new(Statement::Block(vec![Statement::Continue])),
+                    condition: ExpressionKind::Integer(0, IntegerType::Int).into(),
+                },
+                Statement::Label {
+                    name: String::from("l"),
+                    body: Box::new(Statement::Goto(String::from("l"))),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_for_declaration() {
+        let input = String::from("int f() { for (int i = 0; ; ) return i; }");
+        let statements = Parser::new(Lexer::new(input)).parse().unwrap().0.statements;
+        let Statement::FunctionDefinition { body, .. } = &statements[0] else {
+            panic!("expected a function definition");
+        };
+        let int = || Type::Primitive(TypeEnum::Int);
+        assert_eq!(
+            body[..],
+            [Statement::For {
+                init: Some(Box::new(Statement::InitDeclaration {
+                    name: String::from("i"),
+                    offset: 0,
+                    type_: int(),
+                    init: Some(ExpressionKind::Integer(0, IntegerType::Int).into()),
+                })),
+                condition: None,
+                post: None,
+                body: Box::new(Statement::Return(Some(
+                    ExpressionKind::LocalVariable {
+                        name: String::from("i"),
+                        offset: 0,
+                        type_: int(),
+                    }
+                    .into(),
+                ))),
+            }]
+        );
+    }
 }
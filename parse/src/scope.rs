@@ -0,0 +1,366 @@
+use ast::{Expression, ExpressionKind, IntegerType, StructType, Type};
+use diagnostic::Diagnostic;
+use lex::tokens::Token;
+use span::Span;
+
+use crate::Parser;
+
+pub(crate) struct Variable {
+    pub(crate) name: String,
+    pub(crate) type_: Type,
+    pub(crate) storage: Storage,
+    /// name in its declaration.
+    pub(crate) span: Span,
+}
+
+pub(crate) enum Storage {
+    /// start of the variable from the bottom of the frame.
+    Local(usize),
+    /// file scope variable, declared with an initializer or not yet, and `static` or not.
+    Global { initialized: bool, static_: bool },
+    /// block scope `static` variable, with the label of its slot.
+    Static(String),
+    /// enumeration constant with its value.
+    Enumerator(i64),
+    /// typedef name for `type_`.
+    Typedef,
+}
+
+impl Variable {
+    /// the variable used at `span`.
+    pub(crate) fn expression(&self, span: Span) -> Expression {
+        let kind = match &self.storage {
+            Storage::Local(offset) => ExpressionKind::LocalVariable {
+                name: self.name.clone(),
+                offset: *offset,
+                type_: self.type_.clone(),
+            },
+            Storage::Global { .. } => ExpressionKind::GlobalVariable {
+                name: self.name.clone(),
+                type_: self.type_.clone(),
+            },
+            Storage::Static(label) => ExpressionKind::GlobalVariable {
+                name: label.clone(),
+                type_: self.type_.clone(),
+            },
+            Storage::Enumerator(value) => ExpressionKind::Integer(*value as u64, IntegerType::Int),
+            Storage::Typedef => unreachable!("typedef name `{}` is not an expression", self.name),
+        };
+        Expression::new(kind, span)
+    }
+}
+
+/// variables declared in a block, the file, or a function's parameter list together with its
+/// outermost block.
+pub(crate) struct Scope {
+    variables: Vec<Variable>,
+    /// struct and union tags.
+    tags: Vec<Tag>,
+    /// enum tags, with their span.
+    enums: Vec<(String, Span)>,
+    /// frame offset when the scope was entered, the space past it is reused once it is left.
+    frame_offset: usize,
+}
+
+pub(crate) struct Tag {
+    pub(crate) type_: StructType,
+    /// tag in its definition, or in its first use until it is defined.
+    pub(crate) span: Span,
+}
+
+/// stack frame of the function being parsed.
+#[derive(Default)]
+pub(crate) struct Frame {
+    /// bytes from the bottom of the frame taken by the variables in scope.
+    offset: usize,
+    /// largest `offset` so far.
+    size: usize,
+}
+
+impl Frame {
+    /// bytes to reserve in the prologue, keeping `rsp` 16-byte aligned.
+    pub(crate) fn stack_size(&self) -> usize {
+        align_to(self.size, 16)
+    }
+}
+
+impl Scope {
+    pub(crate) fn new(frame_offset: usize) -> Self {
+        Self {
+            variables: vec![],
+            tags: vec![],
+            enums: vec![],
+            frame_offset,
+        }
+    }
+}
+
+impl Parser {
+    pub(crate) fn enter_scope(&mut self) {
+        self.scopes.push(Scope::new(self.frame.offset));
+    }
+
+    pub(crate) fn leave_scope(&mut self) {
+        if let Some(scope) = self.scopes.pop() {
+            self.frame.offset = scope.frame_offset;
+        }
+    }
+
+    pub(crate) fn is_file_scope(&self) -> bool {
+        self.scopes.len() == 1
+    }
+
+    /// declares `name` in the innermost scope and gives it a slot in the current frame.
+    /// variables declared later in a frame are at higher addresses.
+    pub(crate) fn new_local_var(
+        &mut self,
+        type_: Type,
+        name: String,
+        span: Span,
+    ) -> Result<Expression, Diagnostic> {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("the file scope is never left");
+        if let Some(previous) = scope.variables.iter().find(|v| v.name == name) {
+            return Err(redefinition(&name, span, previous.span));
+        }
+
+        let offset = align_to(self.frame.offset, type_.align());
+        self.frame.offset = offset + type_.size();
+        self.frame.size = self.frame.size.max(self.frame.offset);
+        let variable = Variable {
+            name,
+            type_,
+            storage: Storage::Local(offset),
+            span,
+        };
+        let expression = variable.expression(span);
+        scope.variables.push(variable);
+        Ok(expression)
+    }
+
+    /// declares `name` in the file scope. it may be declared again with the same type, as long
+    /// as only one of the declarations has an initializer. `static_` is `None` for an `extern`
+    /// declaration, which keeps the linkage of a previous one.
+    pub(crate) fn new_global_var(
+        &mut self,
+        type_: Type,
+        name: String,
+        span: Span,
+        initialized: bool,
+        static_: Option<bool>,
+    ) -> Result<Expression, Diagnostic> {
+        let scope = &mut self.scopes[0];
+        if let Some(previous) = scope.variables.iter_mut().find(|v| v.name == name) {
+            if let Storage::Global {
+                static_: linkage, ..
+            } = previous.storage
+            {
+                if static_.unwrap_or(linkage) != linkage {
+                    return Err(conflicting_linkage(&name, linkage, span, previous.span));
+                }
+            }
+            return match &mut previous.storage {
+                Storage::Global {
+                    initialized: done, ..
+                } if previous.type_ == type_ && !(*done && initialized) => {
+                    *done |= initialized;
+                    Ok(previous.expression(span))
+                }
+                _ => Err(redefinition(&name, span, previous.span)),
+            };
+        }
+
+        let variable = Variable {
+            name,
+            type_,
+            storage: Storage::Global {
+                initialized,
+                static_: static_.unwrap_or_default(),
+            },
+            span,
+        };
+        let expression = variable.expression(span);
+        scope.variables.push(variable);
+        Ok(expression)
+    }
+
+    /// declares the block scope `static` variable `name` in the innermost scope, and gives the
+    /// label of its slot: the function name and `name`, with a suffix if the function has
+    /// another one of that name.
+    pub(crate) fn new_static_var(
+        &mut self,
+        type_: Type,
+        name: String,
+        span: Span,
+    ) -> Result<String, Diagnostic> {
+        let mut label = format!("{}.{}", self.function, name);
+        if self.statics.contains(&label) {
+            label = format!("{}.{}", label, self.statics.len());
+        }
+        self.declare_name(name, type_, Storage::Static(label.clone()), span)?;
+        self.statics.push(label.clone());
+        Ok(label)
+    }
+
+    /// declares the tag of `type_` in the innermost scope, completing an incomplete declaration
+    /// of it there.
+    pub(crate) fn declare_tag(&mut self, type_: StructType, span: Span) -> Result<(), Diagnostic> {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("the file scope is never left");
+        match scope.tags.iter_mut().find(|t| t.type_.tag == type_.tag) {
+            Some(previous)
+                if previous.type_.members.is_some() || previous.type_.union != type_.union =>
+            {
+                let tag = type_.tag.unwrap_or_default();
+                Err(redefinition(&tag, span, previous.span))
+            }
+            Some(previous) => {
+                *previous = Tag { type_, span };
+                Ok(())
+            }
+            None => {
+                scope.tags.push(Tag { type_, span });
+                Ok(())
+            }
+        }
+    }
+
+    /// innermost tag called `name`.
+    pub(crate) fn find_tag(&self, name: &str) -> Option<&Tag> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|s| s.tags.iter().find(|t| t.type_.tag.as_deref() == Some(name)))
+    }
+
+    /// `type_` with the structs it refers to through pointers, arrays and qualifiers completed by the
+    /// definitions in scope. the members of complete structs are left as they are, which keeps
+    /// self-referential types finite.
+    pub(crate) fn complete_type(&self, type_: Type) -> Type {
+        match type_ {
+            Type::Struct(StructType {
+                tag: Some(tag),
+                members: None,
+                union,
+            }) => match self.find_tag(&tag) {
+                Some(found) if found.type_.union == union => Type::Struct(found.type_.clone()),
+                _ => Type::Struct(StructType::incomplete(tag, union)),
+            },
+            Type::Pointer(type_) => Type::Pointer(Box::new(self.complete_type(*type_))),
+            Type::Array { type_, size } => Type::Array {
+                type_: Box::new(self.complete_type(*type_)),
+                size,
+            },
+            Type::Qualified { qualifiers, type_ } => Type::Qualified {
+                qualifiers,
+                type_: Box::new(self.complete_type(*type_)),
+            },
+            type_ => type_,
+        }
+    }
+
+    /// declares an identifier without a slot in the frame, an enumerator, a typedef name or a
+    /// block scope `static` variable, in the innermost scope. a typedef name may be declared again for the same type.
+    pub(crate) fn declare_name(
+        &mut self,
+        name: String,
+        type_: Type,
+        storage: Storage,
+        span: Span,
+    ) -> Result<(), Diagnostic> {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("the file scope is never left");
+        if let Some(previous) = scope.variables.iter().find(|v| v.name == name) {
+            return match (&previous.storage, &storage) {
+                (Storage::Typedef, Storage::Typedef) if previous.type_ == type_ => Ok(()),
+                _ => Err(redefinition(&name, span, previous.span)),
+            };
+        }
+        scope.variables.push(Variable {
+            name,
+            type_,
+            storage,
+            span,
+        });
+        Ok(())
+    }
+
+    pub(crate) fn declare_enum(&mut self, tag: String, span: Span) -> Result<(), Diagnostic> {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("the file scope is never left");
+        if let Some((_, previous)) = scope.enums.iter().find(|(t, _)| *t == tag) {
+            return Err(redefinition(&tag, span, *previous));
+        }
+        scope.enums.push((tag, span));
+        Ok(())
+    }
+
+    pub(crate) fn find_enum(&self, tag: &str) -> bool {
+        self.scopes
+            .iter()
+            .any(|s| s.enums.iter().any(|(t, _)| t == tag))
+    }
+
+    /// type named by `token` when it is a typedef name in scope.
+    pub(crate) fn typedef_type(&self, token: &Token) -> Option<Type> {
+        let Token::Identifier(name) = token else {
+            return None;
+        };
+        match self.find_variable(name)? {
+            Variable {
+                storage: Storage::Typedef,
+                type_,
+                ..
+            } => Some(self.complete_type(type_.clone())),
+            _ => None,
+        }
+    }
+
+    /// innermost variable called `name`, those of inner scopes shadow the outer ones.
+    pub(crate) fn find_variable(&self, name: &str) -> Option<&Variable> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|s| s.variables.iter().find(|v| v.name == name))
+    }
+}
+
+pub(crate) fn redefinition(name: &str, span: Span, previous: Span) -> Diagnostic {
+    Diagnostic::error(format!("redefinition of `{}`", name))
+        .with_code("E0103")
+        .with_primary_label(span, "redefined here")
+        .with_secondary_label(previous, "previously defined here")
+}
+
+/// error for a declaration of `name` whose linkage differs from the previous one, `static` if
+/// `previous_static`.
+pub(crate) fn conflicting_linkage(
+    name: &str,
+    previous_static: bool,
+    span: Span,
+    previous: Span,
+) -> Diagnostic {
+    let (this, that) = match previous_static {
+        true => ("non-static", "static"),
+        false => ("static", "non-static"),
+    };
+    Diagnostic::error(format!(
+        "{} declaration of `{}` follows {} declaration",
+        this, name, that
+    ))
+    .with_code("E0104")
+    .with_primary_label(span, format!("declared {} here", this))
+    .with_secondary_label(previous, format!("previously declared {}", that))
+}
+
+fn align_to(n: usize, align: usize) -> usize {
+    (n + align - 1) / align * align
+}
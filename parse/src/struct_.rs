@@ -0,0 +1,323 @@
+use ast::{expression_type, Expression, ExpressionKind, StructType, Type, UnaryOperator};
+use diagnostic::Diagnostic;
+use lex::tokens::Token;
+use span::Span;
+
+use crate::scope::redefinition;
+use crate::Parser;
+
+impl Parser {
+    /// `struct tag`, `struct tag { members }` or `struct { members }`, and the same for unions,
+    /// from the keyword as the current token to the tag or the `}`.
+    pub(crate) fn parse_struct_type(&mut self) -> Result<Type, Diagnostic> {
+        let union = self.current_token == Token::Union;
+        let tag = match self.peeked_token.clone() {
+            Token::Identifier(tag) => {
+                self.next_token();
+                Some((tag, self.current_span))
+            }
+            _ => None,
+        };
+
+        if self.peeked_token != Token::LBrace {
+            let Some((tag, span)) = tag else {
+                return Err(self.unexpected_peeked("identifier or `{`"));
+            };
+            if self.find_tag(&tag).is_none() {
+                // used before its definition, e.g. in `struct s *p;`.
+                self.declare_tag(StructType::incomplete(tag.clone(), union), span)?;
+            }
+            return Ok(self.complete_type(Type::Struct(StructType::incomplete(tag, union))));
+        }
+
+        // declared before the members, which may point to it.
+        if let Some((tag, span)) = &tag {
+            self.declare_tag(StructType::incomplete(tag.clone(), union), *span)?;
+        }
+        self.next_token(); // '{'
+        let members = match self.parse_members() {
+            Ok(members) => members,
+            Err(e) => {
+                self.skip_body();
+                return Err(e);
+            }
+        };
+
+        let type_ = StructType::new(tag.as_ref().map(|(tag, _)| tag.clone()), union, members);
+        if let Some((_, span)) = tag {
+            self.declare_tag(type_.clone(), span)?;
+        }
+        Ok(Type::Struct(type_))
+    }
+
+    /// member declarations up to the `}` closing them. an untagged struct or union without a
+    /// declarator is an anonymous member, its members are named as those of the enclosing type.
+    fn parse_members(&mut self) -> Result<Vec<(String, Type)>, Diagnostic> {
+        let mut members: Vec<(String, Type)> = vec![];
+        // names of the members with their spans, those in anonymous members included.
+        let mut names = vec![];
+        while self.peeked_token != Token::RBrace {
+            self.next_token();
+            let start = self.current_span;
+            let base = self.parse_base_type()?;
+            if let Type::Struct(struct_ @ StructType { tag: None, .. }) = &base {
+                if self.peeked_token == Token::SemiColon {
+                    for name in member_names(struct_) {
+                        declare_member(&mut names, name, start)?;
+                    }
+                    members.push((String::new(), base));
+                    self.next_token(); // ';'
+                    continue;
+                }
+            }
+            loop {
+                let (type_, name, span) = self.parse_type_declaration(base.clone())?;
+                declare_member(&mut names, name.clone(), span)?;
+                ensure_complete(&type_, span, "field")?;
+                members.push((name, type_));
+
+                self.next_token();
+                match self.current_token {
+                    Token::Comma => {}
+                    Token::SemiColon => break,
+                    _ => return Err(self.unexpected_current("`,` or `;`")),
+                }
+            }
+        }
+        self.next_token(); // '}'
+        Ok(members)
+    }
+
+    /// `expr.name`, or `expr->name` when `arrow`, from the operator as the current token.
+    pub(crate) fn parse_member_expression(
+        &mut self,
+        expr: Expression,
+        arrow: bool,
+    ) -> Result<Expression, Diagnostic> {
+        let operator = self.current_span;
+        let Token::Identifier(name) = self.peeked_token.clone() else {
+            return Err(self.unexpected_peeked("member name"));
+        };
+        self.next_token();
+
+        let base = match (arrow, expression_type(&expr)) {
+            (false, Some(type_)) => Some(type_),
+            (true, Some(Type::Pointer(type_))) => Some(*type_),
+            _ => None,
+        };
+        let struct_ = match base.map(|t| self.complete_type(t.unqualified().clone())) {
+            Some(Type::Struct(struct_)) => struct_,
+            _ => {
+                let expected = match arrow {
+                    true => "a pointer to a structure or union",
+                    false => "a structure or union",
+                };
+                return Err(Diagnostic::error(format!("`{}` is not {}", expr, expected))
+                    .with_code("E0108")
+                    .with_primary_label(operator, "member access here"));
+            }
+        };
+        if struct_.members.is_none() {
+            return Err(incomplete(
+                &Type::Struct(struct_),
+                self.current_span,
+                "member access into",
+            ));
+        }
+        let Some(member) = struct_.member(&name) else {
+            return Err(Diagnostic::error(format!(
+                "no member named `{}` in `{}`",
+                name,
+                Type::Struct(struct_)
+            ))
+            .with_code("E0108")
+            .with_primary_label(self.current_span, "unknown member"));
+        };
+
+        let type_ = self.complete_type(member.type_);
+        let offset = member.offset;
+        let start = expr.span;
+        let expr = match arrow {
+            true => {
+                let kind = ExpressionKind::Unary {
+                    expr: Box::new(expr),
+                    op: UnaryOperator::Dereference,
+                };
+                Expression::new(kind, start)
+            }
+            false => expr,
+        };
+        let kind = ExpressionKind::Member {
+            expr: Box::new(expr),
+            name,
+            offset,
+            type_,
+        };
+        Ok(self.spanned(kind, start))
+    }
+}
+
+/// adds `name` to the member `names` of a struct, unless one is already called so.
+fn declare_member(
+    names: &mut Vec<(String, Span)>,
+    name: String,
+    span: Span,
+) -> Result<(), Diagnostic> {
+    if let Some((_, previous)) = names.iter().find(|(n, _)| *n == name) {
+        return Err(redefinition(&name, span, *previous));
+    }
+    names.push((name, span));
+    Ok(())
+}
+
+/// names of the members of `struct_`, those of its anonymous members included.
+fn member_names(struct_: &StructType) -> Vec<String> {
+    let mut names = vec![];
+    for member in struct_.members.iter().flatten() {
+        match member.type_.unqualified() {
+            Type::Struct(inner) if member.name.is_empty() => names.extend(member_names(inner)),
+            _ => names.push(member.name.clone()),
+        }
+    }
+    names
+}
+
+/// error unless an object of `type_` has a known size, `what` is the kind of object.
+pub(crate) fn ensure_complete(type_: &Type, span: Span, what: &str) -> Result<(), Diagnostic> {
+    if is_complete(type_) {
+        Ok(())
+    } else {
+        Err(incomplete(type_, span, &format!("{} has", what)))
+    }
+}
+
+/// whether the size of `type_` is known, structs and unions are complete once defined.
+pub(crate) fn is_complete(type_: &Type) -> bool {
+    match type_.unqualified() {
+        Type::Struct(StructType { members: None, .. }) => false,
+        Type::Array { type_, .. } => is_complete(type_),
+        _ => true,
+    }
+}
+
+pub(crate) fn incomplete(type_: &Type, span: Span, context: &str) -> Diagnostic {
+    Diagnostic::error(format!("{} incomplete type `{}`", context, type_))
+        .with_code("E0107")
+        .with_primary_label(span, "the type is declared but not defined")
+}
+
+#[cfg(test)]
+mod test {
+    use ast::{ExpressionKind, Member, Statement, TypeEnum};
+    use lex::Lexer;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_struct_type() {
+        let input = String::from(
+            "struct s { char a; int b; struct s *next; } v; union u { char c; long l; } w;",
+        );
+        let statements = Parser::new(Lexer::new(input)).parse().unwrap().0.statements;
+        let types = statements
+            .iter()
+            .map(|s| match s {
+                Statement::GlobalDeclaration { type_, .. } => type_.clone(),
+                _ => panic!("expected a declaration"),
+            })
+            .collect::<Vec<_>>();
+        let Type::Struct(StructType {
+            members: Some(members),
+            ..
+        }) = &types[0]
+        else {
+            panic!("expected a complete struct");
+        };
+        assert_eq!(
+            members[..2],
+            [
+                Member {
+                    name: String::from("a"),
+                    type_: Type::Primitive(TypeEnum::Char),
+                    offset: 0,
+                },
+                Member {
+                    name: String::from("b"),
+                    type_: Type::Primitive(TypeEnum::Int),
+                    offset: 4,
+                },
+            ]
+        );
+        assert_eq!(members[2].offset, 8);
+        assert_eq!(
+            members[2].type_,
+            Type::Pointer(Box::new(Type::Struct(StructType::incomplete(
+                String::from("s"),
+                false
+            ))))
+        );
+        assert_eq!((types[0].size(), types[0].align()), (16, 8));
+        assert_eq!((types[1].size(), types[1].align()), (8, 8));
+    }
+
+    #[test]
+    fn test_parse_member_expression() {
+        let input = String::from(
+            "struct s { int a; struct s *next; }; int f(struct s *p) { return p->next->a; }",
+        );
+        let statements = Parser::new(Lexer::new(input)).parse().unwrap().0.statements;
+        let Statement::FunctionDefinition { body, .. } = &statements[1] else {
+            panic!("expected a function definition");
+        };
+        let Statement::Return(Some(expr)) = &body[0] else {
+            panic!("expected a return");
+        };
+        assert_eq!(expr.to_string(), "p->next->a");
+        let ExpressionKind::Member { expr, offset, .. } = &expr.kind else {
+            panic!("expected a member");
+        };
+        assert_eq!(*offset, 0);
+        // completed through the tag, so that `next` can be followed.
+        let ExpressionKind::Unary { expr, .. } = &expr.kind else {
+            panic!("expected a dereference");
+        };
+        assert!(matches!(
+            &expr.kind,
+            ExpressionKind::Member { offset: 8, type_: Type::Pointer(s), .. }
+                if matches!(s.as_ref(), Type::Struct(StructType { members: Some(_), .. }))
+        ));
+    }
+
+    #[test]
+    fn test_anonymous_member() {
+        let input = String::from(
+            "struct c { int x; union { int y; struct { char z; long w; }; }; } v; \
+             long f(void) { return v.w; }",
+        );
+        let statements = Parser::new(Lexer::new(input)).parse().unwrap().0.statements;
+        let Statement::GlobalDeclaration {
+            type_: Type::Struct(struct_),
+            ..
+        } = &statements[0]
+        else {
+            panic!("expected a declaration");
+        };
+        let offsets = ["x", "y", "z", "w"].map(|name| struct_.member(name).map(|m| m.offset));
+        assert_eq!(offsets, [Some(0), Some(8), Some(8), Some(16)]);
+        assert_eq!(Type::Struct(struct_.clone()).size(), 24);
+        let Statement::FunctionDefinition { body, .. } = &statements[1] else {
+            panic!("expected a function definition");
+        };
+        assert!(matches!(
+            &body[0],
+            Statement::Return(Some(Expression {
+                kind: ExpressionKind::Member { offset: 16, .. },
+                ..
+            }))
+        ));
+
+        let input = String::from("struct d { int x; struct { int x; }; };");
+        let errors = Parser::new(Lexer::new(input)).parse().unwrap_err();
+        assert_eq!(errors[0].to_string(), "error[E0103]: redefinition of `x`");
+    }
+}
@@ -1,119 +1,100 @@
-use ast::{Expression, Statement, Type, TypeEnum};
+use ast::{Expression, ExpressionKind, Statement, Type};
+use diagnostic::Diagnostic;
 use lex::tokens::Token;
 
-use crate::{LVar, Parser, Precedence};
+use span::Span;
+
+use crate::declarator::ensure_supported;
+use crate::struct_::ensure_complete;
+use crate::{Parser, Precedence};
 
 impl Parser {
+    /// variable declared by the declarator just parsed, with its initializer if any. a block
+    /// scope `static` one gets a slot outside the frame, like a file scope one.
+    /// the last token of the initializer, or of the declarator, is left as the current one.
+    /// `sized_by_init` is true for an array whose size is that of its initializer, which is
+    /// parsed before the array is declared then.
     pub(super) fn parse_variable_declaration(
         &mut self,
-        type_: Type,
-        name: String,
-    ) -> Result<Statement, String> {
-        let offset = match self.new_local_var(type_.clone(), name.clone())? {
-            Expression::LocalVariable { offset, .. } => offset,
-            _ => unreachable!(),
-        };
-
-        let init = match self.current_token {
-            Token::SemiColon => None,
-            Token::Assignment => {
-                self.next_token();
-                let e = Some(self.parse_expression(Precedence::Lowest)?);
-                self.next_token();
-                e
-            }
-            _ => {
-                return Err(format!(
-                    "expected token ';' but got {:?}",
-                    self.current_token
-                ))
-            }
-        };
-
-        Ok(Statement::InitDeclaration {
-            name,
-            offset,
-            type_,
-            init,
-        })
-    }
-    pub(super) fn new_local_var(
-        &mut self,
-        type_: Type,
-        name: String,
-    ) -> Result<Expression, String> {
-        let offset = self.locals.last().map(|l| l.offset).unwrap_or(0) + type_.size();
-        let v = LVar {
-            name: name.clone(),
-            offset,
-            type_: type_.clone(),
-        };
-        self.locals.push(v);
-        Ok(Expression::LocalVariable {
-            name,
-            offset,
-            type_,
-        })
-    }
-    pub(super) fn find_local_var(&self, name: &str) -> Option<&LVar> {
-        self.locals.iter().find(|s| s.name == name)
-    }
-    pub(super) fn parse_type_declaration(&mut self) -> Result<(Type, String), String> {
-        let base = match self.current_token {
-            Token::Void => Type::Primitive(TypeEnum::Void),
-            Token::Char => Type::Primitive(TypeEnum::Char),
-            Token::Short => Type::Primitive(TypeEnum::Short),
-            Token::Int => Type::Primitive(TypeEnum::Int),
-            Token::Long => Type::Primitive(TypeEnum::Long),
-            Token::Float => Type::Primitive(TypeEnum::Float),
-            Token::Double => Type::Primitive(TypeEnum::Double),
-            _ => return Err(format!("Expected type, but got {:?}", self.current_token)),
-        };
-        self.next_token();
-
-        let mut p_count = 0;
-        while self.current_token == Token::Asterisk {
-            p_count += 1;
-            self.next_token();
+        mut type_: Type,
+        mut name: String,
+        span: Span,
+        static_: bool,
+        sized_by_init: bool,
+    ) -> Result<Statement, Diagnostic> {
+        let initialized = self.peeked_token == Token::Assignment;
+        let mut init = None;
+        if sized_by_init {
+            let expr = self.parse_initializer()?;
+            type_ = sized_array(type_, &expr)?;
+            init = Some(expr);
         }
-
-        let name = match self.current_token.clone() {
-            Token::Identifier(name) => name,
-            _ => {
-                return Err(format!(
-                    "Expected identifier, but got {:?}",
-                    self.current_token
-                ))
+        ensure_complete(&type_, span, "variable")?;
+        ensure_supported(&type_, span)?;
+        // the offset of a variable in the frame, the others are labelled by `name`.
+        let offset = if self.is_file_scope() {
+            let static_ = Some(static_);
+            self.new_global_var(type_.clone(), name.clone(), span, initialized, static_)?;
+            None
+        } else if static_ {
+            name = self.new_static_var(type_.clone(), name, span)?;
+            None
+        } else {
+            match self.new_local_var(type_.clone(), name.clone(), span)?.kind {
+                ExpressionKind::LocalVariable { offset, .. } => Some(offset),
+                _ => unreachable!("`{}` is not a local variable", name),
             }
         };
 
-        let mut t = base;
-        // parse array
-        while self.peeked_token == Token::LBracket {
-            self.next_token(); // skip '['
+        if initialized && init.is_none() {
+            init = Some(self.parse_initializer()?);
+        }
 
-            let Token::Integer(size) = self.peeked_token else {
-            return Err(format!("Expected integer, but got {:?}", self.peeked_token));
-        };
-            self.next_token();
+        match offset {
+            Some(offset) => Ok(Statement::InitDeclaration {
+                name,
+                offset,
+                type_,
+                init,
+            }),
+            None => Ok(Statement::GlobalDeclaration {
+                name,
+                type_,
+                init,
+                static_,
+            }),
+        }
+    }
 
-            self.next_token(); // skip ']'
+    /// initializer after the `=` which is the peeked token.
+    fn parse_initializer(&mut self) -> Result<Expression, Diagnostic> {
+        self.next_token(); // '='
+        self.next_token();
+        self.parse_expression(Precedence::Comma)
+    }
 
-            t = Type::Array {
-                type_: Box::new(t),
-                size,
-            };
-        }
-        for _ in 0..p_count {
-            t = Type::Pointer(Box::new(t));
+    /// `extern` declaration of a variable defined elsewhere, or later in the file.
+    pub(super) fn parse_extern_declaration(
+        &mut self,
+        type_: Type,
+        name: String,
+        span: Span,
+    ) -> Result<Statement, Diagnostic> {
+        if !self.is_file_scope() {
+            return Err(
+                Diagnostic::error("block scope `extern` declarations are not supported")
+                    .with_code("E0300")
+                    .with_primary_label(span, "declared here"),
+            );
         }
-
-        Ok((t, name))
+        self.new_global_var(type_.clone(), name.clone(), span, false, None)?;
+        Ok(Statement::Declaration { name, type_ })
     }
 }
 
 #[cfg(test)]
 mod test {
+    use ast::{ExpressionKind, IntegerType, TypeEnum};
     use lex::Lexer;
 
     use super::*;
@@ -125,16 +106,16 @@ mod test {
                 String::from("int a = 0;"),
                 Statement::InitDeclaration {
                     name: String::from("a"),
-                    offset: 8,
+                    offset: 0,
                     type_: Type::Primitive(TypeEnum::Int),
-                    init: Some(Expression::Integer(0)),
+                    init: Some(ExpressionKind::Integer(0, IntegerType::Int).into()),
                 },
             ),
             (
                 String::from("int i;"),
                 Statement::InitDeclaration {
                     name: String::from("i"),
-                    offset: 8,
+                    offset: 0,
                     type_: Type::Primitive(TypeEnum::Int),
                     init: None,
                 },
@@ -143,7 +124,7 @@ mod test {
                 String::from("int *i;"),
                 Statement::InitDeclaration {
                     name: String::from("i"),
-                    offset: 8,
+                    offset: 0,
                     type_: Type::Pointer(Box::new(Type::Primitive(TypeEnum::Int))),
                     init: None,
                 },
@@ -152,7 +133,7 @@ mod test {
                 String::from("int **i;"),
                 Statement::InitDeclaration {
                     name: String::from("i"),
-                    offset: 8,
+                    offset: 0,
                     type_: Type::Pointer(Box::new(Type::Pointer(Box::new(Type::Primitive(
                         TypeEnum::Int,
                     ))))),
@@ -164,6 +145,7 @@ mod test {
         for (input, expected) in cases {
             let lexer = Lexer::new(input);
             let mut parser = Parser::new(lexer);
+            parser.enter_scope();
             assert_eq!(parser.parse_statement().unwrap(), expected);
         }
     }
@@ -175,7 +157,7 @@ mod test {
                 String::from("int a[10];"),
                 vec![Statement::InitDeclaration {
                     name: String::from("a"),
-                    offset: 80,
+                    offset: 0,
                     type_: Type::Array {
                         type_: Box::new(Type::Primitive(TypeEnum::Int)),
                         size: 10,
@@ -187,13 +169,13 @@ mod test {
                 String::from("int a[5][10];"),
                 vec![Statement::InitDeclaration {
                     name: String::from("a"),
-                    offset: 80,
+                    offset: 0,
                     type_: Type::Array {
                         type_: Box::new(Type::Array {
                             type_: Box::new(Type::Primitive(TypeEnum::Int)),
-                            size: 5,
+                            size: 10,
                         }),
-                        size: 10,
+                        size: 5,
                     },
                     init: None,
                 }],
@@ -202,20 +184,211 @@ mod test {
                 String::from("int *a[10];"),
                 vec![Statement::InitDeclaration {
                     name: String::from("a"),
-                    offset: 8,
-                    type_: Type::Pointer(Box::new(Type::Array {
-                        type_: Box::new(Type::Primitive(TypeEnum::Int)),
+                    offset: 0,
+                    type_: Type::Array {
+                        type_: Box::new(Type::Pointer(Box::new(Type::Primitive(TypeEnum::Int)))),
                         size: 10,
-                    })),
+                    },
                     init: None,
                 }],
             ),
+            (
+                String::from("char s[] = \"hi\";"),
+                vec![Statement::InitDeclaration {
+                    name: String::from("s"),
+                    offset: 0,
+                    type_: Type::Array {
+                        type_: Box::new(Type::Primitive(TypeEnum::Char)),
+                        size: 3,
+                    },
+                    init: Some(ExpressionKind::String(b"hi".to_vec()).into()),
+                }],
+            ),
+            (
+                String::from("int a[] = {1, 2};"),
+                vec![Statement::InitDeclaration {
+                    name: String::from("a"),
+                    offset: 0,
+                    type_: Type::Array {
+                        type_: Box::new(Type::Primitive(TypeEnum::Int)),
+                        size: 2,
+                    },
+                    init: Some(
+                        ExpressionKind::Array {
+                            elements: vec![
+                                ExpressionKind::Integer(1, IntegerType::Int).into(),
+                                ExpressionKind::Integer(2, IntegerType::Int).into(),
+                            ],
+                        }
+                        .into(),
+                    ),
+                }],
+            ),
         ];
 
         for (input, expected) in cases {
             let lexer = Lexer::new(input);
             let mut parser = Parser::new(lexer);
-            assert_eq!(parser.parse().unwrap().statements, expected);
+            parser.enter_scope();
+            assert_eq!(parser.parse().unwrap().0.statements, expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_global_declaration() {
+        let int = || Type::Primitive(TypeEnum::Int);
+        let input = String::from("int a; int a = 1; char *s = \"hi\"; int f() { int a; return a; } int g() { return a; }");
+        let statements = Parser::new(Lexer::new(input)).parse().unwrap().0.statements;
+        assert_eq!(
+            statements[..3],
+            [
+                Statement::GlobalDeclaration {
+                    name: String::from("a"),
+                    type_: int(),
+                    init: None,
+                    static_: false,
+                },
+                Statement::GlobalDeclaration {
+                    name: String::from("a"),
+                    type_: int(),
+                    init: Some(ExpressionKind::Integer(1, IntegerType::Int).into()),
+                    static_: false,
+                },
+                Statement::GlobalDeclaration {
+                    name: String::from("s"),
+                    type_: Type::Pointer(Box::new(Type::Primitive(TypeEnum::Char))),
+                    init: Some(ExpressionKind::String(b"hi".to_vec()).into()),
+                    static_: false,
+                },
+            ]
+        );
+
+        // the local `a` shadows the global one.
+        let returned = |s: &Statement| match s {
+            Statement::FunctionDefinition { body, .. } => body.last().cloned(),
+            _ => None,
+        };
+        assert_eq!(
+            returned(&statements[3]),
+            Some(Statement::Return(Some(
+                ExpressionKind::LocalVariable {
+                    name: String::from("a"),
+                    offset: 0,
+                    type_: int(),
+                }
+                .into()
+            )))
+        );
+        assert_eq!(
+            returned(&statements[4]),
+            Some(Statement::Return(Some(
+                ExpressionKind::GlobalVariable {
+                    name: String::from("a"),
+                    type_: int(),
+                }
+                .into()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_parse_static() {
+        let int = || Type::Primitive(TypeEnum::Int);
+        let input = String::from(
+            "static int a; int f(void) { static int n = 1; { static int n; } return n; }",
+        );
+        let statements = Parser::new(Lexer::new(input)).parse().unwrap().0.statements;
+        assert_eq!(
+            statements[0],
+            Statement::GlobalDeclaration {
+                name: String::from("a"),
+                type_: int(),
+                init: None,
+                static_: true,
+            }
+        );
+        let Statement::FunctionDefinition { body, .. } = &statements[1] else {
+            panic!("`f` is not a function definition");
+        };
+        // each `n` gets a slot of its own, the inner one is out of scope at the `return`.
+        assert_eq!(
+            body[..],
+            [
+                Statement::GlobalDeclaration {
+                    name: String::from("f.n"),
+                    type_: int(),
+                    init: Some(ExpressionKind::Integer(1, IntegerType::Int).into()),
+                    static_: true,
+                },
+                Statement::Block(vec![Statement::GlobalDeclaration {
+                    name: String::from("f.n.1"),
+                    type_: int(),
+                    init: None,
+                    static_: true,
+                }]),
+                Statement::Return(Some(
+                    ExpressionKind::GlobalVariable {
+                        name: String::from("f.n"),
+                        type_: int(),
+                    }
+                    .into()
+                )),
+            ]
+        );
+
+        let conflicting = [
+            (
+                "int a;\nstatic int a;",
+                "static declaration of `a` follows non-static declaration",
+            ),
+            (
+                "static int a;\nint a;",
+                "non-static declaration of `a` follows static declaration",
+            ),
+            (
+                "int f(void);\nstatic int f(void);",
+                "static declaration of `f` follows non-static declaration",
+            ),
+        ];
+        for (input, expected) in conflicting {
+            let errors = Parser::new(Lexer::new(String::from(input)))
+                .parse()
+                .unwrap_err();
+            assert_eq!(errors[0].to_string(), format!("error[E0104]: {}", expected));
         }
+        let valid = "static int a;\nextern int a;\nstatic int f(void);\nint f(void) { return a; }";
+        let statements = Parser::new(Lexer::new(String::from(valid)))
+            .parse()
+            .unwrap()
+            .0
+            .statements;
+        assert!(matches!(
+            statements[3],
+            Statement::FunctionDefinition { static_: true, .. }
+        ));
+    }
+}
+
+/// the array `type_` declared without a size, with that of its initializer `init`: the number of
+/// its elements, or of the characters of a string literal and its terminating null.
+fn sized_array(type_: Type, init: &Expression) -> Result<Type, Diagnostic> {
+    let size = match &init.kind {
+        ExpressionKind::Array { elements } => elements.len(),
+        ExpressionKind::String(bytes) => bytes.len() + 1,
+        _ => {
+            return Err(Diagnostic::error(format!(
+                "array initializer must be an initializer list or a string literal, not `{}`",
+                init
+            ))
+            .with_code("E0107")
+            .with_primary_label(init.span, "cannot give the size of the array"))
+        }
+    };
+    match type_ {
+        Type::Array { type_, .. } => Ok(Type::Array {
+            type_,
+            size: size as i32,
+        }),
+        type_ => unreachable!("`{}` is not an array", type_),
     }
 }
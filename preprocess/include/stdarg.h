@@ -0,0 +1,22 @@
+/* glibc headers include this with `__need___va_list` defined to get `__gnuc_va_list` only. */
+#ifndef __GNUC_VA_LIST
+#define __GNUC_VA_LIST
+/* only passed on to the library, e.g. to `vprintf`, which takes a pointer. */
+typedef char *__builtin_va_list;
+typedef __builtin_va_list __gnuc_va_list;
+#endif
+
+#ifndef __need___va_list
+#ifndef __STDARG_H
+#define __STDARG_H
+
+typedef __gnuc_va_list va_list;
+
+#define va_start(ap, last) __builtin_va_start(ap, last)
+#define va_end(ap) __builtin_va_end(ap)
+#define va_arg(ap, type) __builtin_va_arg(ap, type)
+#define va_copy(dest, src) __builtin_va_copy(dest, src)
+
+#endif
+#endif
+#undef __need___va_list
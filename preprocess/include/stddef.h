@@ -0,0 +1,12 @@
+#ifndef __STDDEF_H
+#define __STDDEF_H
+
+typedef __SIZE_TYPE__ size_t;
+typedef __PTRDIFF_TYPE__ ptrdiff_t;
+typedef __WCHAR_TYPE__ wchar_t;
+typedef long max_align_t;
+
+#define NULL ((void *)0)
+#define offsetof(type, member) ((size_t)&(((type *)0)->member))
+
+#endif
@@ -0,0 +1,57 @@
+use std::path::Path;
+
+/// directory name under which the headers shipped with the compiler are reported.
+pub(crate) const BUILTIN_DIR: &str = "<built-in>";
+
+/// macros defined before the input is read.
+pub(crate) const PREDEFINED: &str = "\
+#define __STDC__ 1
+#define __STDC_VERSION__ 201112L
+#define __STDC_HOSTED__ 1
+#define __STDC_NO_ATOMICS__ 1
+#define __STDC_NO_COMPLEX__ 1
+#define __STDC_NO_THREADS__ 1
+#define __STDC_NO_VLA__ 1
+#define __ubcc__ 1
+#define __x86_64__ 1
+#define __x86_64 1
+#define __amd64__ 1
+#define __amd64 1
+#define __LP64__ 1
+#define _LP64 1
+#define __linux__ 1
+#define __linux 1
+#define __unix__ 1
+#define __unix 1
+#define __ELF__ 1
+#define __CHAR_BIT__ 8
+#define __SIZEOF_SHORT__ 2
+#define __SIZEOF_INT__ 4
+#define __SIZEOF_LONG__ 8
+#define __SIZEOF_LONG_LONG__ 8
+#define __SIZEOF_POINTER__ 8
+#define __SIZEOF_FLOAT__ 4
+#define __SIZEOF_DOUBLE__ 8
+#define __SIZE_TYPE__ unsigned long
+#define __PTRDIFF_TYPE__ long
+#define __WCHAR_TYPE__ int
+";
+
+/// headers which come with the compiler rather than the C library.
+const HEADERS: [(&str, &str); 3] = [
+    ("stdarg.h", include_str!("../include/stdarg.h")),
+    ("stdbool.h", include_str!("../include/stdbool.h")),
+    ("stddef.h", include_str!("../include/stddef.h")),
+];
+
+pub(crate) fn header(name: &str) -> Option<&'static str> {
+    HEADERS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, src)| *src)
+}
+
+/// contents of a path under `BUILTIN_DIR`.
+pub(crate) fn read(path: &Path) -> Option<&'static str> {
+    header(path.strip_prefix(BUILTIN_DIR).ok()?.to_str()?)
+}
@@ -0,0 +1,424 @@
+use diagnostic::Diagnostic;
+use span::Span;
+
+use crate::token::{PPToken, TokenKind};
+use crate::Preprocessor;
+
+impl Preprocessor<'_> {
+    /// value of the controlling expression of `#if` or `#elif`.
+    pub(crate) fn evaluate(&mut self, directive: &PPToken, args: &[PPToken]) -> bool {
+        let tokens = match self.replace_defined(args) {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                self.report(e);
+                return false;
+            }
+        };
+        // identifiers left after expansion are not macros and evaluate to 0.
+        let tokens = self
+            .expand(tokens)
+            .into_iter()
+            .map(|t| match t.kind {
+                TokenKind::Identifier => PPToken {
+                    kind: TokenKind::Number,
+                    text: String::from("0"),
+                    ..t
+                },
+                _ => t,
+            })
+            .collect::<Vec<_>>();
+
+        let mut evaluator = Evaluator {
+            tokens: &tokens,
+            position: 0,
+            end: directive.span,
+            evaluating: true,
+        };
+        let value = evaluator.expression().and_then(|v| match evaluator.peek() {
+            Some(t) => Err(expression_error(
+                format!("missing binary operator before `{}`", t.text),
+                t.span,
+            )),
+            None => Ok(v),
+        });
+        match value {
+            Ok(v) => v.is_true(),
+            Err(e) => {
+                self.report(e);
+                false
+            }
+        }
+    }
+
+    pub(crate) fn is_defined(&self, name: &str) -> bool {
+        self.macros.contains_key(name) || name == "__FILE__" || name == "__LINE__"
+    }
+
+    /// replaces `defined X` and `defined(X)` with `1` or `0`.
+    fn replace_defined(&self, args: &[PPToken]) -> Result<Vec<PPToken>, Diagnostic> {
+        let mut tokens = vec![];
+        let mut i = 0;
+        while i < args.len() {
+            let token = &args[i];
+            if !(token.is_identifier() && token.text == "defined") {
+                tokens.push(token.clone());
+                i += 1;
+                continue;
+            }
+            let parenthesized = args.get(i + 1).map_or(false, |t| t.is("("));
+            let name = args.get(if parenthesized { i + 2 } else { i + 1 });
+            let name = match name {
+                Some(name) if name.is_identifier() => name,
+                _ => {
+                    return Err(Diagnostic::error("macro name missing after `defined`")
+                        .with_code("E0404")
+                        .with_primary_label(token.span, "expected an identifier after this"))
+                }
+            };
+            if parenthesized {
+                match args.get(i + 3) {
+                    Some(t) if t.is(")") => {}
+                    _ => {
+                        return Err(Diagnostic::error("missing `)` after `defined`")
+                            .with_code("E0404")
+                            .with_primary_label(name.span, "expected `)` after this"))
+                    }
+                }
+            }
+            let value = if self.is_defined(&name.text) {
+                "1"
+            } else {
+                "0"
+            };
+            tokens.push(PPToken {
+                has_space: token.has_space,
+                ..PPToken::new(TokenKind::Number, String::from(value), token.span)
+            });
+            i += if parenthesized { 4 } else { 2 };
+        }
+        Ok(tokens)
+    }
+}
+
+fn expression_error(message: String, span: Span) -> Diagnostic {
+    Diagnostic::error(message)
+        .with_code("E0404")
+        .with_primary_label(span, "in this `#if` expression")
+}
+
+/// value of a `#if` expression: every signed type acts as `intmax_t` and every unsigned one as
+/// `uintmax_t`, which are both 64 bits here. `value` holds the bits of either.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Value {
+    value: i64,
+    unsigned: bool,
+}
+
+impl Value {
+    fn signed(value: i64) -> Self {
+        Self {
+            value,
+            unsigned: false,
+        }
+    }
+
+    fn is_true(self) -> bool {
+        self.value != 0
+    }
+}
+
+/// integer constant expression of a conditional directive, see C11 6.10.1.
+struct Evaluator<'a> {
+    tokens: &'a [PPToken],
+    position: usize,
+    /// span reported when the expression ends too early.
+    end: Span,
+    /// false in the operand `&&`, `||` or `?:` does not use, where `1 / 0` is no error.
+    evaluating: bool,
+}
+
+impl Evaluator<'_> {
+    fn peek(&self) -> Option<&PPToken> {
+        self.tokens.get(self.position)
+    }
+
+    fn eat(&mut self, punctuator: &str) -> bool {
+        if self.peek().map_or(false, |t| t.is(punctuator)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punctuator: &str) -> Result<(), Diagnostic> {
+        if self.eat(punctuator) {
+            return Ok(());
+        }
+        let (found, span) = match self.peek() {
+            Some(t) => (format!("`{}`", t.text), t.span),
+            None => (String::from("end of line"), self.end),
+        };
+        Err(expression_error(
+            format!("expected `{}`, found {}", punctuator, found),
+            span,
+        ))
+    }
+
+    /// `parse` run without evaluating when `skip`, for an operand whose value is not used.
+    fn operand<T>(
+        &mut self,
+        skip: bool,
+        parse: impl FnOnce(&mut Self) -> Result<T, Diagnostic>,
+    ) -> Result<T, Diagnostic> {
+        let evaluating = self.evaluating;
+        self.evaluating = evaluating && !skip;
+        let result = parse(self);
+        self.evaluating = evaluating;
+        result
+    }
+
+    /// conditional expression, `a ? b : c`.
+    fn expression(&mut self) -> Result<Value, Diagnostic> {
+        let condition = self.binary(0)?;
+        if !self.eat("?") {
+            return Ok(condition);
+        }
+        let then = self.operand(!condition.is_true(), Self::expression)?;
+        self.expect(":")?;
+        let otherwise = self.operand(condition.is_true(), Self::expression)?;
+        let value = if condition.is_true() { then } else { otherwise };
+        Ok(Value {
+            value: value.value,
+            unsigned: then.unsigned || otherwise.unsigned,
+        })
+    }
+
+    fn binary(&mut self, min_precedence: u8) -> Result<Value, Diagnostic> {
+        let mut lhs = self.unary()?;
+        while let Some(op) = self.peek().filter(|t| t.kind == TokenKind::Punctuator) {
+            let precedence = match op.text.as_str() {
+                "||" => 1,
+                "&&" => 2,
+                "|" => 3,
+                "^" => 4,
+                "&" => 5,
+                "==" | "!=" => 6,
+                "<" | ">" | "<=" | ">=" => 7,
+                "<<" | ">>" => 8,
+                "+" | "-" => 9,
+                "*" | "/" | "%" => 10,
+                _ => break,
+            };
+            if precedence < min_precedence {
+                break;
+            }
+            let op = op.clone();
+            self.position += 1;
+            let skip = match op.text.as_str() {
+                "||" => lhs.is_true(),
+                "&&" => !lhs.is_true(),
+                _ => false,
+            };
+            let rhs = self.operand(skip, |evaluator| evaluator.binary(precedence + 1))?;
+            lhs = match binary(&op.text, lhs, rhs) {
+                Some(value) => value,
+                None if !self.evaluating => Value::signed(0),
+                None => {
+                    return Err(expression_error(
+                        String::from("division by zero in `#if`"),
+                        op.span,
+                    ))
+                }
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Value, Diagnostic> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(expression_error(
+                String::from("expected value in expression"),
+                self.end,
+            ));
+        };
+        self.position += 1;
+        match token.kind {
+            TokenKind::Punctuator => match token.text.as_str() {
+                "+" => self.unary(),
+                "-" => self.unary().map(|v| Value {
+                    value: v.value.wrapping_neg(),
+                    ..v
+                }),
+                "~" => self.unary().map(|v| Value {
+                    value: !v.value,
+                    ..v
+                }),
+                "!" => self.unary().map(|v| Value::signed(!v.is_true() as i64)),
+                "(" => {
+                    let value = self.expression()?;
+                    self.expect(")")?;
+                    Ok(value)
+                }
+                _ => Err(expression_error(
+                    format!("expected value in expression, found `{}`", token.text),
+                    token.span,
+                )),
+            },
+            TokenKind::Number => parse_integer(&token.text).ok_or_else(|| {
+                expression_error(
+                    format!("invalid integer constant `{}`", token.text),
+                    token.span,
+                )
+            }),
+            TokenKind::Character => {
+                character_value(&token.text)
+                    .map(Value::signed)
+                    .ok_or_else(|| {
+                        expression_error(
+                            format!("invalid character constant {}", token.text),
+                            token.span,
+                        )
+                    })
+            }
+            _ => Err(expression_error(
+                format!("expected value in expression, found `{}`", token.text),
+                token.span,
+            )),
+        }
+    }
+}
+
+/// `lhs op rhs` after the usual arithmetic conversions, `None` on a division by zero.
+fn binary(op: &str, lhs: Value, rhs: Value) -> Option<Value> {
+    let unsigned = lhs.unsigned || rhs.unsigned;
+    let (l, r) = (lhs.value, rhs.value);
+    let (ul, ur) = (l as u64, r as u64);
+    let truth = |b: bool| Some(Value::signed(b as i64));
+    let value = match op {
+        "||" => return truth(lhs.is_true() || rhs.is_true()),
+        "&&" => return truth(lhs.is_true() && rhs.is_true()),
+        "==" => return truth(l == r),
+        "!=" => return truth(l != r),
+        "<" if unsigned => return truth(ul < ur),
+        ">" if unsigned => return truth(ul > ur),
+        "<=" if unsigned => return truth(ul <= ur),
+        ">=" if unsigned => return truth(ul >= ur),
+        "<" => return truth(l < r),
+        ">" => return truth(l > r),
+        "<=" => return truth(l <= r),
+        ">=" => return truth(l >= r),
+        // the result of a shift has the type of its left operand.
+        "<<" => {
+            return Some(Value {
+                value: l.wrapping_shl(r as u32),
+                ..lhs
+            })
+        }
+        ">>" if lhs.unsigned => {
+            return Some(Value {
+                value: ul.wrapping_shr(r as u32) as i64,
+                ..lhs
+            })
+        }
+        ">>" => {
+            return Some(Value {
+                value: l.wrapping_shr(r as u32),
+                ..lhs
+            })
+        }
+        "|" => l | r,
+        "^" => l ^ r,
+        "&" => l & r,
+        "+" => l.wrapping_add(r),
+        "-" => l.wrapping_sub(r),
+        "*" => l.wrapping_mul(r),
+        _ if r == 0 => return None,
+        "/" if unsigned => (ul / ur) as i64,
+        "%" if unsigned => (ul % ur) as i64,
+        "/" => l.wrapping_div(r),
+        _ => l.wrapping_rem(r),
+    };
+    Some(Value { value, unsigned })
+}
+
+/// decimal, octal, hexadecimal or binary integer with an optional `u`/`l` suffix. it is unsigned
+/// with a `u`, or when it is too large for `intmax_t`.
+fn parse_integer(text: &str) -> Option<Value> {
+    let digits = text.trim_end_matches(['u', 'U', 'l', 'L']);
+    let suffix = &text[digits.len()..];
+    let lower = digits.to_ascii_lowercase();
+    let (digits, radix) = if let Some(hex) = lower.strip_prefix("0x") {
+        (hex, 16)
+    } else if let Some(binary) = lower.strip_prefix("0b") {
+        (binary, 2)
+    } else if lower.len() > 1 && lower.starts_with('0') {
+        (&lower[1..], 8)
+    } else {
+        (lower.as_str(), 10)
+    };
+    let value = u64::from_str_radix(digits, radix).ok()?;
+    Some(Value {
+        value: value as i64,
+        unsigned: suffix.contains(['u', 'U']) || value > i64::MAX as u64,
+    })
+}
+
+/// value of a single character constant such as `'a'` or `'\n'`.
+fn character_value(text: &str) -> Option<i64> {
+    let inner = text.strip_prefix('\'')?.strip_suffix('\'')?;
+    let Some(escape) = inner.strip_prefix('\\') else {
+        let mut chars = inner.chars();
+        let c = chars.next()?;
+        return chars.next().is_none().then_some(c as i64);
+    };
+    let value = match escape {
+        "n" => 10,
+        "t" => 9,
+        "r" => 13,
+        "a" => 7,
+        "b" => 8,
+        "f" => 12,
+        "v" => 11,
+        "\\" | "'" | "\"" | "?" => escape.as_bytes()[0] as i64,
+        _ => match escape.strip_prefix('x') {
+            Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+            None => i64::from_str_radix(escape, 8).ok()?,
+        },
+    };
+    Some(value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_literals() {
+        let cases = vec![
+            ("42", Some((42, false))),
+            ("0x1F", Some((31, false))),
+            ("010", Some((8, false))),
+            ("0", Some((0, false))),
+            ("201112L", Some((201112, false))),
+            ("10ul", Some((10, true))),
+            ("0U", Some((0, true))),
+            ("0xffffffffffffffff", Some((-1, true))),
+            ("1.5", None),
+        ];
+        for (input, expected) in cases {
+            let expected = expected.map(|(value, unsigned)| Value { value, unsigned });
+            assert_eq!(parse_integer(input), expected);
+        }
+        let cases = vec![
+            ("'a'", Some(97)),
+            ("'\\n'", Some(10)),
+            ("'\\0'", Some(0)),
+            ("'\\x41'", Some(65)),
+            ("'ab'", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(character_value(input), expected);
+        }
+    }
+}
@@ -0,0 +1,370 @@
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use diagnostic::Diagnostic;
+use span::{FileId, Span};
+
+use crate::builtin::{self, BUILTIN_DIR};
+use crate::macro_::Macro;
+use crate::token::{PPToken, TokenKind};
+use crate::Preprocessor;
+
+/// limit on nested `#include`s, mostly to stop files including themselves forever.
+const MAX_INCLUDE_DEPTH: usize = 200;
+
+/// state of an `#if` group and the `#elif`/`#else` groups following it.
+pub(crate) struct Conditional {
+    /// the `#if`, `#ifdef` or `#ifndef`.
+    pub(crate) span: Span,
+    /// lines of the current group are kept.
+    pub(crate) active: bool,
+    /// one of the groups was or is being kept, later ones are skipped.
+    taken: bool,
+    has_else: bool,
+    /// the enclosing group is kept.
+    parent_active: bool,
+}
+
+fn directive_error(message: impl Into<String>, span: Span, label: &str) -> Diagnostic {
+    Diagnostic::error(message)
+        .with_code("E0400")
+        .with_primary_label(span, label)
+}
+
+impl Preprocessor<'_> {
+    /// handles the directive on the line starting with `hash`.
+    pub(crate) fn directive(
+        &mut self,
+        hash: &PPToken,
+        line: &[PPToken],
+        conditionals: &mut Vec<Conditional>,
+    ) {
+        let active = conditionals.last().map_or(true, |c| c.active);
+        // a `#` on its own is a null directive.
+        let Some(name) = line.first() else {
+            return;
+        };
+        let args = &line[1..];
+
+        match name.text.as_str() {
+            "if" | "ifdef" | "ifndef" => {
+                let value = active && self.condition(name, args);
+                conditionals.push(Conditional {
+                    span: name.span,
+                    active: value,
+                    taken: value,
+                    has_else: false,
+                    parent_active: active,
+                });
+            }
+            "elif" | "else" => {
+                let Some(conditional) = conditionals.last_mut() else {
+                    let message = format!("`#{}` without `#if`", name.text);
+                    self.report(directive_error(message, name.span, "unexpected directive"));
+                    return;
+                };
+                if conditional.has_else {
+                    let message = format!("`#{}` after `#else`", name.text);
+                    self.report(directive_error(message, name.span, "unexpected directive"));
+                    return;
+                }
+                let value = conditional.parent_active
+                    && !conditional.taken
+                    && (name.text == "else" || self.condition(name, args));
+                conditional.active = value;
+                conditional.taken |= value;
+                conditional.has_else = name.text == "else";
+            }
+            "endif" => {
+                if conditionals.pop().is_none() {
+                    self.report(directive_error(
+                        "`#endif` without `#if`",
+                        name.span,
+                        "unexpected directive",
+                    ));
+                }
+            }
+            _ if !active => {}
+            "define" => self.define(name, args),
+            "undef" => match args.first() {
+                Some(t) if t.is_identifier() => {
+                    self.macros.remove(&t.text);
+                }
+                _ => self.report(directive_error(
+                    "macro name must be an identifier",
+                    name.span,
+                    "expected a macro name after this",
+                )),
+            },
+            "include" => self.include(name, args),
+            "pragma" => {
+                if args.first().map_or(false, |t| t.text == "once") {
+                    self.once.insert(hash.span.file_id);
+                }
+                // other pragmas are ignored.
+            }
+            "error" => {
+                let message = args.iter().enumerate().fold(String::new(), |s, (i, t)| {
+                    let space = if i > 0 && t.has_space { " " } else { "" };
+                    s + space + &t.text
+                });
+                self.report(
+                    Diagnostic::error(message)
+                        .with_code("E0405")
+                        .with_primary_label(hash.span.to(name.span), "`#error` directive"),
+                );
+            }
+            _ => {
+                let message = format!("invalid preprocessing directive `#{}`", name.text);
+                self.report(directive_error(message, name.span, "unknown directive"));
+            }
+        }
+    }
+
+    fn condition(&mut self, directive: &PPToken, args: &[PPToken]) -> bool {
+        match directive.text.as_str() {
+            "ifdef" | "ifndef" => match args.first() {
+                Some(t) if t.is_identifier() => {
+                    self.is_defined(&t.text) == (directive.text == "ifdef")
+                }
+                _ => {
+                    self.report(directive_error(
+                        "macro name must be an identifier",
+                        directive.span,
+                        "expected a macro name after this",
+                    ));
+                    false
+                }
+            },
+            _ if args.is_empty() => {
+                let message = format!("`#{}` with no expression", directive.text);
+                self.report(directive_error(
+                    message,
+                    directive.span,
+                    "expected an expression",
+                ));
+                false
+            }
+            _ => self.evaluate(directive, args),
+        }
+    }
+
+    fn define(&mut self, directive: &PPToken, args: &[PPToken]) {
+        let name = match args.first() {
+            Some(t) if t.is_identifier() && t.text != "defined" => t,
+            _ => {
+                return self.report(directive_error(
+                    "macro name must be an identifier",
+                    directive.span,
+                    "expected a macro name after this",
+                ))
+            }
+        };
+        let rest = &args[1..];
+
+        // `(` right after the name starts a parameter list.
+        let macro_ = if rest.first().map_or(false, |t| t.is("(") && !t.has_space) {
+            let Some((params, variadic, body)) = self.parameters(name, &rest[1..]) else {
+                return;
+            };
+            Macro::Function {
+                params,
+                variadic,
+                body: body.to_vec(),
+            }
+        } else {
+            Macro::Object {
+                body: rest.to_vec(),
+            }
+        };
+
+        let (body, params) = match &macro_ {
+            Macro::Object { body } => (body, &[][..]),
+            Macro::Function { body, params, .. } => (body, &params[..]),
+        };
+        if let Some(t) = body
+            .first()
+            .filter(|t| t.is("##"))
+            .or_else(|| body.last().filter(|t| t.is("##")))
+        {
+            return self.report(directive_error(
+                "`##` cannot appear at either end of a macro expansion",
+                t.span,
+                "missing an operand",
+            ));
+        }
+        if let Macro::Function { .. } = macro_ {
+            for (i, t) in body.iter().enumerate() {
+                let is_param = |t: &PPToken| t.is_identifier() && params.contains(&t.text);
+                if t.is("#") && !body.get(i + 1).map_or(false, is_param) {
+                    return self.report(directive_error(
+                        "`#` is not followed by a macro parameter",
+                        t.span,
+                        "expected a parameter name after this",
+                    ));
+                }
+            }
+        }
+
+        self.macros.insert(name.text.clone(), Rc::new(macro_));
+    }
+
+    /// parses `a, b, ...)` of a function-like macro, returning the rest of the line as its body.
+    fn parameters<'t>(
+        &mut self,
+        name: &PPToken,
+        tokens: &'t [PPToken],
+    ) -> Option<(Vec<String>, bool, &'t [PPToken])> {
+        let mut params = vec![];
+        let mut i = 0;
+        if tokens.first().map_or(false, |t| t.is(")")) {
+            return Some((params, false, &tokens[1..]));
+        }
+        loop {
+            match tokens.get(i) {
+                Some(t) if t.is("...") => {
+                    params.push(String::from("__VA_ARGS__"));
+                    return match tokens.get(i + 1) {
+                        Some(t) if t.is(")") => Some((params, true, &tokens[i + 2..])),
+                        t => {
+                            let span = t.map_or(name.span, |t| t.span);
+                            self.report(directive_error(
+                                "expected `)` after `...`",
+                                span,
+                                "expected `)`",
+                            ));
+                            None
+                        }
+                    };
+                }
+                Some(t) if t.is_identifier() && !params.contains(&t.text) => {
+                    params.push(t.text.clone())
+                }
+                t => {
+                    let span = t.map_or(name.span, |t| t.span);
+                    self.report(directive_error(
+                        "invalid macro parameter list",
+                        span,
+                        "expected a unique parameter name",
+                    ));
+                    return None;
+                }
+            }
+            match tokens.get(i + 1) {
+                Some(t) if t.is(",") => i += 2,
+                Some(t) if t.is(")") => return Some((params, false, &tokens[i + 2..])),
+                t => {
+                    let span = t.map_or(name.span, |t| t.span);
+                    self.report(directive_error(
+                        "expected `,` or `)` in macro parameter list",
+                        span,
+                        "expected `,` or `)`",
+                    ));
+                    return None;
+                }
+            }
+        }
+    }
+
+    fn include(&mut self, directive: &PPToken, args: &[PPToken]) {
+        // `#include MACRO` is expanded first.
+        let args = match args.first() {
+            Some(t) if t.kind == TokenKind::String || t.is("<") => args.to_vec(),
+            _ => self.expand(args.to_vec()),
+        };
+        let header = match args.first() {
+            Some(t) if t.kind == TokenKind::String && t.text.starts_with('"') => {
+                Some((t.text[1..t.text.len() - 1].to_string(), false, t.span))
+            }
+            Some(t) if t.is("<") => args.iter().position(|t| t.is(">")).map(|end| {
+                let name = args[1..end]
+                    .iter()
+                    .enumerate()
+                    .fold(String::new(), |s, (i, t)| {
+                        let space = if i > 0 && t.has_space { " " } else { "" };
+                        s + space + &t.text
+                    });
+                (name, true, t.span.to(args[end].span))
+            }),
+            _ => None,
+        };
+        let Some((name, angled, span)) = header else {
+            return self.report(directive_error(
+                "expected \"FILENAME\" or <FILENAME>",
+                directive.span,
+                "after this directive",
+            ));
+        };
+
+        let Some(path) = self.find_include(&name, angled, directive.span.file_id) else {
+            return self.report(
+                Diagnostic::error(format!("`{}` file not found", name))
+                    .with_code("E0402")
+                    .with_primary_label(span, "included here"),
+            );
+        };
+        let file_id = match self.files.get(&path) {
+            Some(id) => *id,
+            None => {
+                let src = match builtin::read(&path) {
+                    Some(src) => Ok(src.to_string()),
+                    None => std::fs::read_to_string(&path),
+                };
+                let src = match src {
+                    Ok(src) => src,
+                    Err(e) => {
+                        return self.report(
+                            Diagnostic::error(format!("failed to read `{}`: {}", name, e))
+                                .with_code("E0402")
+                                .with_primary_label(span, "included here"),
+                        )
+                    }
+                };
+                let id = self
+                    .sources
+                    .add_file(path.to_string_lossy().into_owned(), src);
+                self.files.insert(path, id);
+                id
+            }
+        };
+
+        if self.once.contains(&file_id) {
+            return;
+        }
+        if self.include_depth >= MAX_INCLUDE_DEPTH {
+            return self.report(
+                Diagnostic::error("`#include` nested too deeply")
+                    .with_code("E0402")
+                    .with_primary_label(span, "included here"),
+            );
+        }
+        self.include_depth += 1;
+        self.process_file(file_id);
+        self.include_depth -= 1;
+    }
+
+    /// `"name"` is looked up next to the including file first, then both forms search the
+    /// `-I` directories, the compiler's own headers and the system directories.
+    fn find_include(&self, name: &str, angled: bool, includer: FileId) -> Option<PathBuf> {
+        let mut candidates = vec![];
+        if !angled {
+            let includer = Path::new(self.sources.name(includer));
+            candidates.push(match includer.parent() {
+                Some(dir) => dir.join(name),
+                None => PathBuf::from(name),
+            });
+        }
+        candidates.extend(self.options.include_paths.iter().map(|dir| dir.join(name)));
+        if let Some(path) = candidates.into_iter().find(|p| p.is_file()) {
+            return Some(path);
+        }
+        if builtin::header(name).is_some() {
+            return Some(Path::new(BUILTIN_DIR).join(name));
+        }
+        self.options
+            .system_include_paths
+            .iter()
+            .map(|dir| dir.join(name))
+            .find(|p| p.is_file())
+    }
+}
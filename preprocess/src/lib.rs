@@ -0,0 +1,389 @@
+//! C preprocessor: macro expansion, conditional compilation and `#include`.
+//!
+//! the result is plain text for the lexer. it starts every run of lines with a GCC style line
+//! marker, `# <line> "<file>"`, so that tokens can still be traced back to their source.
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use diagnostic::Diagnostic;
+use span::{FileId, SourceMap};
+
+use directive::Conditional;
+use macro_::Macro;
+use output::Output;
+use token::PPToken;
+
+mod builtin;
+mod condition;
+mod directive;
+mod macro_;
+mod output;
+mod token;
+
+#[derive(Debug, Clone)]
+pub struct PreprocessOptions {
+    /// directories given with `-I`.
+    pub include_paths: Vec<PathBuf>,
+    /// directories searched last, for the C library headers.
+    pub system_include_paths: Vec<PathBuf>,
+}
+
+impl Default for PreprocessOptions {
+    fn default() -> Self {
+        Self {
+            include_paths: vec![],
+            system_include_paths: [
+                "/usr/local/include",
+                "/usr/include/x86_64-linux-gnu",
+                "/usr/include",
+            ]
+            .iter()
+            .map(PathBuf::from)
+            .collect(),
+        }
+    }
+}
+
+/// preprocesses the file `file_id` of `sources`, registering every included file there too.
+pub fn preprocess(
+    sources: &mut SourceMap,
+    file_id: FileId,
+    options: &PreprocessOptions,
+) -> Result<String, Vec<Diagnostic>> {
+    let mut preprocessor = Preprocessor {
+        sources,
+        options,
+        macros: HashMap::new(),
+        files: HashMap::new(),
+        once: HashSet::new(),
+        include_depth: 0,
+        output: Output::default(),
+        diagnostics: vec![],
+    };
+    let name = preprocessor.sources.name(file_id).to_string();
+    preprocessor.files.insert(PathBuf::from(name), file_id);
+
+    let predefined = preprocessor.sources.add_file(
+        String::from(builtin::BUILTIN_DIR),
+        String::from(builtin::PREDEFINED),
+    );
+    preprocessor.process_file(predefined);
+    preprocessor.process_file(file_id);
+
+    if preprocessor.diagnostics.is_empty() {
+        Ok(preprocessor.output.finish())
+    } else {
+        Err(preprocessor.diagnostics)
+    }
+}
+
+struct Preprocessor<'a> {
+    sources: &'a mut SourceMap,
+    options: &'a PreprocessOptions,
+    macros: HashMap<String, Rc<Macro>>,
+    /// files read so far, by path.
+    files: HashMap<PathBuf, FileId>,
+    /// files marked with `#pragma once`.
+    once: HashSet<FileId>,
+    include_depth: usize,
+    output: Output,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Preprocessor<'_> {
+    fn process_file(&mut self, file_id: FileId) {
+        let src = match self.sources.get(file_id) {
+            Some(file) => file.src.clone(),
+            None => return,
+        };
+        let tokens = match token::tokenize(&src, file_id) {
+            Ok(tokens) => tokens,
+            Err(e) => return self.report(e),
+        };
+
+        let mut conditionals: Vec<Conditional> = vec![];
+        // lines between directives are expanded together, as macro calls may span lines.
+        let mut text: Vec<PPToken> = vec![];
+        let mut i = 0;
+        while i < tokens.len() {
+            let end = tokens[i + 1..]
+                .iter()
+                .position(|t| t.at_line_start)
+                .map_or(tokens.len(), |p| i + 1 + p);
+            if tokens[i].at_line_start && tokens[i].is("#") {
+                self.write(std::mem::take(&mut text));
+                self.directive(&tokens[i], &tokens[i + 1..end], &mut conditionals);
+            } else if conditionals.last().map_or(true, |c| c.active) {
+                text.extend_from_slice(&tokens[i..end]);
+            }
+            i = end;
+        }
+        self.write(text);
+
+        for conditional in conditionals {
+            self.report(
+                Diagnostic::error("unterminated conditional directive")
+                    .with_code("E0400")
+                    .with_primary_label(conditional.span, "not closed by an `#endif`"),
+            );
+        }
+    }
+
+    /// macro-expands `text` and appends it to the output.
+    fn write(&mut self, text: Vec<PPToken>) {
+        for token in self.expand(text) {
+            if token.kind == token::TokenKind::Other && token.text.starts_with(['"', '\'']) {
+                let quote = &token.text[..1];
+                self.report(
+                    Diagnostic::error(format!("missing terminating {} character", quote))
+                        .with_code("E0401")
+                        .with_primary_label(token.span, "literal starts here"),
+                );
+            }
+            self.output.write(&token, self.sources);
+        }
+    }
+
+    fn report(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn preprocess_str(input: &str) -> Result<String, Vec<Diagnostic>> {
+        let mut sources = SourceMap::new();
+        let file_id = sources.add_file(String::from("main.c"), input.to_string());
+        preprocess(&mut sources, file_id, &PreprocessOptions::default())
+    }
+
+    #[test]
+    fn test_object_like_macro() {
+        let cases = vec![
+            (
+                "#define N 10\nint a = N;\n",
+                "# 2 \"main.c\"\nint a = 10;\n",
+            ),
+            (
+                "#define A B + 1\n#define B A * 2\nA;\n",
+                "# 3 \"main.c\"\nA * 2 + 1;\n",
+            ),
+            ("#define N 1\n#undef N\nN;\n", "# 3 \"main.c\"\nN;\n"),
+            // a `-` next to an expanded `-1` must not become `--`.
+            ("#define M -1\n-M;\n", "# 2 \"main.c\"\n- -1;\n"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(preprocess_str(input).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_function_like_macro() {
+        let cases = vec![
+            (
+                "#define max(a, b) ((a) > (b) ? (a) : (b))\nmax(1, f(2, 3));\n",
+                "((1) > (f(2, 3)) ? (1) : (f(2, 3)));\n",
+            ),
+            ("#define f(x) x\nf;\nf (1);\n", "f;\n1;\n"),
+            (
+                "#define str(x) #x\nstr( a  \"b\\n\"  c );\n",
+                "\"a \\\"b\\\\n\\\" c\";\n",
+            ),
+            (
+                "#define cat(a, b) a ## b\ncat(x, 1);\ncat(, y);\ncat(,);\n",
+                "x1;\ny;\n      ;\n",
+            ),
+            (
+                "#define log(fmt, ...) printf(fmt, __VA_ARGS__)\nlog(\"%d %d\", 1, 2);\n",
+                "printf(\"%d %d\", 1, 2);\n",
+            ),
+            (
+                "#define f(x) x + f(x)\n#define g f\ng(g(1));\n",
+                "1 + f(1) + f(1 + f(1));\n",
+            ),
+            ("#define f(x) (x)\nf(\n  1\n);\n", "(\n1)\n ;\n"),
+        ];
+        for (input, expected) in cases {
+            let output = preprocess_str(input).unwrap();
+            // drop the line marker in front.
+            let output = output.split_once('\n').unwrap().1.trim_start_matches('\n');
+            assert_eq!(output, expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_conditional() {
+        let input = r#"#define A 2
+#if A > 1 && defined(A) && !defined B
+a
+#elif 1
+b
+#else
+c
+#endif
+#ifdef B
+d
+#elif A == 2
+e
+#endif
+#ifndef A
+#if 1 / 0
+#endif
+f
+#else
+g
+#endif
+#if __LINE__ == 21 && 'a' == 97 && (1 ? 2 : 0) == 2 && unknown == 0
+h
+#endif
+"#;
+        let output = preprocess_str(input).unwrap();
+        let lines = output
+            .lines()
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec!["a", "e", "g", "h"]);
+    }
+
+    #[test]
+    fn test_conditional_arithmetic() {
+        let input = r#"#define ULONG_MAX 0xffffffffffffffffUL
+#if ULONG_MAX > 0xffffffffUL && -1 < 0u == 0 && -1 / 2u > 1 && (1 ? -1 : 0u) > 0
+a
+#endif
+#if -1 >> 1 == -1 && -1u >> 63 == 1 && -1 < 0
+b
+#endif
+#if 0 && (1 / 0) || 1 || 1 % 0
+c
+#endif
+#if 1 ? 2 : (1 / 0)
+d
+#endif
+"#;
+        let output = preprocess_str(input).unwrap();
+        let lines = output
+            .lines()
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_line_markers() {
+        let input =
+            "int a;\n\n\nint b;\n\n\n\n\n\n\n\n\n\n\n  int c = __LINE__;\nchar *f = __FILE__;\n";
+        assert_eq!(
+            preprocess_str(input).unwrap(),
+            "# 1 \"main.c\"\nint a;\n\n\nint b;\n# 15 \"main.c\"\n  int c = 15;\nchar *f = \"main.c\";\n"
+        );
+    }
+
+    #[test]
+    fn test_include() {
+        let dir = std::env::temp_dir().join(format!("ubcc-preprocess-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(
+            dir.join("a.h"),
+            "#pragma once\nint a;\n#include \"sub/b.h\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("sub/b.h"), "#include \"c.h\"\n").unwrap();
+        std::fs::write(dir.join("sub/c.h"), "int c = __LINE__;\n").unwrap();
+
+        let mut sources = SourceMap::new();
+        let main = sources.add_file(
+            dir.join("main.c").to_string_lossy().into_owned(),
+            String::from(
+                "#include \"a.h\"\n#include <a.h>\n#define H <stddef.h>\n#include H\nint main;\n",
+            ),
+        );
+        let options = PreprocessOptions {
+            include_paths: vec![dir.clone()],
+            ..PreprocessOptions::default()
+        };
+        let output = preprocess(&mut sources, main, &options).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let d = dir.to_string_lossy();
+        let expected = [
+            format!("# 2 \"{}/a.h\"", d),
+            String::from("int a;"),
+            format!("# 1 \"{}/sub/c.h\"", d),
+            String::from("int c = 1;"),
+        ];
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines[..4], expected);
+        assert!(output.contains("# 4 \"<built-in>/stddef.h\"\ntypedef unsigned long size_t;"));
+        assert!(output.ends_with(&format!("# 5 \"{}/main.c\"\nint main;\n", d)));
+    }
+
+    #[test]
+    fn test_errors() {
+        let cases = vec![
+            (
+                "#include \"missing.h\"\n",
+                "`missing.h` file not found",
+                (1, 10),
+            ),
+            (
+                "#if 1\nint a;\n",
+                "unterminated conditional directive",
+                (1, 2),
+            ),
+            ("#endif\n", "`#endif` without `#if`", (1, 2)),
+            ("#error stop  here\n", "stop here", (1, 1)),
+            ("#foo\n", "invalid preprocessing directive `#foo`", (1, 2)),
+            (
+                "#define f(a) a\nf(1, 2);\n",
+                "macro `f` takes 1 argument, but 2 were given",
+                (2, 1),
+            ),
+            (
+                "#define f(a) a\nf(1;\n",
+                "unterminated argument list invoking macro `f`",
+                (2, 1),
+            ),
+            (
+                "#define f(a) #b\n",
+                "`#` is not followed by a macro parameter",
+                (1, 14),
+            ),
+            ("#if 1 +\n#endif\n", "expected value in expression", (1, 2)),
+            (
+                "#if (1\n#endif\n",
+                "expected `)`, found end of line",
+                (1, 2),
+            ),
+            (
+                "#if 1 2\n#endif\n",
+                "missing binary operator before `2`",
+                (1, 7),
+            ),
+            (
+                "#if 1 || 0 ? 1 / 0 : 0\n#endif\n",
+                "division by zero in `#if`",
+                (1, 16),
+            ),
+            (
+                "#define cat(a, b) a ## b\ncat(+, /);\n",
+                "pasting `+` and `/` does not give a valid preprocessing token",
+                (2, 5),
+            ),
+            (
+                "char *s = \"abc;\n",
+                "missing terminating \" character",
+                (1, 11),
+            ),
+        ];
+        for (input, message, (line, column)) in cases {
+            let errors = preprocess_str(input).unwrap_err();
+            assert_eq!(errors[0].message, message, "{}", input);
+            let span = errors[0].primary_span().unwrap();
+            assert_eq!((span.line, span.column), (line, column), "{}", input);
+        }
+    }
+}
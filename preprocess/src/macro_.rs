@@ -0,0 +1,345 @@
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use diagnostic::Diagnostic;
+
+use crate::token::{tokenize, PPToken, TokenKind};
+use crate::Preprocessor;
+
+#[derive(Debug)]
+pub(crate) enum Macro {
+    Object {
+        body: Vec<PPToken>,
+    },
+    Function {
+        /// names of the parameters, ending with `__VA_ARGS__` if variadic.
+        params: Vec<String>,
+        variadic: bool,
+        body: Vec<PPToken>,
+    },
+}
+
+impl Preprocessor<'_> {
+    /// expands every macro invocation in `tokens`, rescanning the results.
+    ///
+    /// hide sets keep a macro from being expanded again inside its own expansion (C11 6.10.3.4).
+    pub(crate) fn expand(&mut self, tokens: Vec<PPToken>) -> Vec<PPToken> {
+        let mut input: VecDeque<PPToken> = tokens.into();
+        let mut output = vec![];
+        while let Some(token) = input.pop_front() {
+            if !token.is_identifier() || token.hideset.contains(&token.text) {
+                output.push(token);
+                continue;
+            }
+            if let Some(token) = self.expand_builtin(&token) {
+                output.push(token);
+                continue;
+            }
+            let Some(macro_) = self.macros.get(&token.text).map(Rc::clone) else {
+                output.push(token);
+                continue;
+            };
+
+            let expansion = match macro_.as_ref() {
+                Macro::Object { body } => {
+                    let hideset = with(&token.hideset, &token.text);
+                    self.substitute(body, &[], &[], &token, &hideset)
+                }
+                Macro::Function {
+                    params,
+                    variadic,
+                    body,
+                } => {
+                    // a function-like macro name not followed by `(` is left alone.
+                    if !input.front().map_or(false, |t| t.is("(")) {
+                        output.push(token);
+                        continue;
+                    }
+                    let limit = if *variadic { params.len() } else { usize::MAX };
+                    let Some((args, rparen)) = self.collect_arguments(&token, &mut input, limit) else {
+                        continue;
+                    };
+                    let Some(args) = self.check_arguments(&token, args, params, *variadic) else {
+                        continue;
+                    };
+                    let hideset = with(
+                        &token
+                            .hideset
+                            .iter()
+                            .filter(|name| rparen.hideset.contains(name))
+                            .cloned()
+                            .collect::<Vec<_>>(),
+                        &token.text,
+                    );
+                    self.substitute(body, params, &args, &token, &hideset)
+                }
+            };
+            for t in expansion.into_iter().rev() {
+                input.push_front(t);
+            }
+        }
+        output
+    }
+
+    /// `__FILE__` and `__LINE__`, which depend on where they are used.
+    fn expand_builtin(&self, token: &PPToken) -> Option<PPToken> {
+        let (kind, text) = match token.text.as_str() {
+            "__FILE__" => (
+                TokenKind::String,
+                quote(self.sources.name(token.span.file_id)),
+            ),
+            "__LINE__" => (TokenKind::Number, token.span.line.to_string()),
+            _ => return None,
+        };
+        Some(PPToken {
+            has_space: token.has_space,
+            hideset: vec![token.text.clone()],
+            ..PPToken::new(kind, text, token.span)
+        })
+    }
+
+    /// reads the parenthesized arguments following a function-like macro name.
+    ///
+    /// commas only separate the first `limit` arguments, the rest go to `__VA_ARGS__`.
+    fn collect_arguments(
+        &mut self,
+        name: &PPToken,
+        input: &mut VecDeque<PPToken>,
+        limit: usize,
+    ) -> Option<(Vec<Vec<PPToken>>, PPToken)> {
+        input.pop_front(); // consume '('
+        let mut args = vec![vec![]];
+        let mut depth = 0;
+        while let Some(token) = input.pop_front() {
+            if token.is("(") {
+                depth += 1;
+            } else if token.is(")") {
+                if depth == 0 {
+                    return Some((args, token));
+                }
+                depth -= 1;
+            } else if token.is(",") && depth == 0 && args.len() < limit {
+                args.push(vec![]);
+                continue;
+            }
+            args.last_mut().unwrap().push(token);
+        }
+        self.report(
+            Diagnostic::error(format!(
+                "unterminated argument list invoking macro `{}`",
+                name.text
+            ))
+            .with_code("E0403")
+            .with_primary_label(name.span, "macro invoked here"),
+        );
+        None
+    }
+
+    fn check_arguments(
+        &mut self,
+        name: &PPToken,
+        mut args: Vec<Vec<PPToken>>,
+        params: &[String],
+        variadic: bool,
+    ) -> Option<Vec<Vec<PPToken>>> {
+        // `f()` passes no arguments rather than a single empty one, unless `f` takes one.
+        if params.is_empty() && args.len() == 1 && args[0].is_empty() {
+            args.clear();
+        }
+        let required = if variadic {
+            params.len() - 1
+        } else {
+            params.len()
+        };
+        if args.len() == required && variadic {
+            args.push(vec![]);
+        }
+        if args.len() == params.len() {
+            return Some(args);
+        }
+        self.report(
+            Diagnostic::error(format!(
+                "macro `{}` takes {} argument{}, but {} {} given",
+                name.text,
+                required,
+                if required == 1 { "" } else { "s" },
+                args.len(),
+                if args.len() == 1 { "was" } else { "were" },
+            ))
+            .with_code("E0403")
+            .with_primary_label(name.span, "macro invoked here"),
+        );
+        None
+    }
+
+    /// replaces parameters in the macro body with the arguments, applying `#` and `##`.
+    ///
+    /// tokens of the body take the location of the invocation, argument tokens keep theirs.
+    fn substitute(
+        &mut self,
+        body: &[PPToken],
+        params: &[String],
+        args: &[Vec<PPToken>],
+        invocation: &PPToken,
+        hideset: &[String],
+    ) -> Vec<PPToken> {
+        let param = |t: &PPToken| {
+            if t.is_identifier() {
+                params.iter().position(|p| *p == t.text)
+            } else {
+                None
+            }
+        };
+        let mut expanded_args: Vec<Option<Vec<PPToken>>> = vec![None; args.len()];
+        let mut result: Vec<PPToken> = vec![];
+
+        let mut i = 0;
+        while i < body.len() {
+            let token = &body[i];
+            let next = body.get(i + 1);
+
+            if token.is("#") {
+                if let Some(n) = next.and_then(param) {
+                    let mut s = stringize(&args[n], invocation);
+                    s.has_space = token.has_space;
+                    result.push(s);
+                    i += 2;
+                    continue;
+                }
+            }
+
+            if token.is("##") {
+                let rhs = &body[i + 1];
+                match param(rhs) {
+                    Some(n) => {
+                        let mut arg = args[n].iter().cloned();
+                        if let Some(first) = arg.next() {
+                            match result.pop() {
+                                Some(lhs) => {
+                                    let pasted = self.paste(lhs, &first);
+                                    result.push(pasted);
+                                }
+                                None => result.push(first),
+                            }
+                            result.extend(arg);
+                        }
+                    }
+                    None => {
+                        let rhs = PPToken {
+                            span: invocation.span,
+                            ..rhs.clone()
+                        };
+                        match result.pop() {
+                            Some(lhs) => {
+                                let pasted = self.paste(lhs, &rhs);
+                                result.push(pasted);
+                            }
+                            None => result.push(rhs),
+                        }
+                    }
+                }
+                i += 2;
+                continue;
+            }
+
+            if let Some(n) = param(token) {
+                // operands of `##` are not macro-expanded.
+                if next.map_or(false, |t| t.is("##")) {
+                    if args[n].is_empty() {
+                        // an empty left operand leaves the right one as is.
+                        match body.get(i + 2).and_then(param) {
+                            Some(m) => {
+                                result.extend(args[m].iter().cloned());
+                                i += 3;
+                            }
+                            None => i += 2,
+                        }
+                        continue;
+                    }
+                    result.extend(args[n].iter().cloned());
+                    i += 1;
+                    continue;
+                }
+
+                if expanded_args[n].is_none() {
+                    expanded_args[n] = Some(self.expand(args[n].clone()));
+                }
+                let mut expanded = expanded_args[n].clone().unwrap();
+                if let Some(first) = expanded.first_mut() {
+                    first.has_space = token.has_space;
+                }
+                result.extend(expanded);
+                i += 1;
+                continue;
+            }
+
+            result.push(PPToken {
+                span: invocation.span,
+                ..token.clone()
+            });
+            i += 1;
+        }
+
+        if let Some(first) = result.first_mut() {
+            first.has_space = invocation.has_space;
+        }
+        for token in result.iter_mut() {
+            for name in hideset {
+                if !token.hideset.contains(name) {
+                    token.hideset.push(name.clone());
+                }
+            }
+        }
+        result
+    }
+
+    /// `lhs ## rhs`, which must form a single token.
+    fn paste(&mut self, lhs: PPToken, rhs: &PPToken) -> PPToken {
+        let text = format!("{}{}", lhs.text, rhs.text);
+        let kind = match tokenize(&text, lhs.span.file_id).as_deref() {
+            Ok([token]) => token.kind,
+            _ => {
+                self.report(
+                    Diagnostic::error(format!(
+                        "pasting `{}` and `{}` does not give a valid preprocessing token",
+                        lhs.text, rhs.text
+                    ))
+                    .with_code("E0403")
+                    .with_primary_label(lhs.span, "in this macro expansion"),
+                );
+                TokenKind::Other
+            }
+        };
+        PPToken { kind, text, ..lhs }
+    }
+}
+
+fn with(hideset: &[String], name: &str) -> Vec<String> {
+    let mut hideset = hideset.to_vec();
+    if !hideset.iter().any(|n| n == name) {
+        hideset.push(name.to_string());
+    }
+    hideset
+}
+
+/// `"..."` literal of `s`.
+pub(crate) fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// `#arg`, spelling the argument as a string literal.
+fn stringize(arg: &[PPToken], invocation: &PPToken) -> PPToken {
+    let mut s = String::new();
+    for (i, token) in arg.iter().enumerate() {
+        if i > 0 && token.has_space {
+            s.push(' ');
+        }
+        match token.kind {
+            TokenKind::String | TokenKind::Character => {
+                s.push_str(&token.text.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+            _ => s.push_str(&token.text),
+        }
+    }
+    PPToken::new(TokenKind::String, format!("\"{}\"", s), invocation.span)
+}
@@ -0,0 +1,93 @@
+use span::{FileId, SourceMap};
+
+use crate::macro_::quote;
+use crate::token::{tokenize, PPToken};
+
+/// gaps of up to this many lines are filled with blank lines rather than a line marker.
+const MAX_BLANK_LINES: usize = 8;
+
+/// text of the preprocessed translation unit.
+///
+/// tokens are laid out on their original lines and, as far as possible, columns, and
+/// `# <line> "<file>"` markers are written whenever that is not enough, so the lexer can
+/// still point diagnostics at the original source.
+#[derive(Default)]
+pub(crate) struct Output {
+    text: String,
+    file_id: Option<FileId>,
+    line: usize,
+    /// bytes written on the current line.
+    column: usize,
+    /// the current line has tokens out of a macro expansion.
+    expanded: bool,
+    last: Option<PPToken>,
+}
+
+impl Output {
+    pub(crate) fn write(&mut self, token: &PPToken, sources: &SourceMap) {
+        let span = token.span;
+        if self.file_id != Some(span.file_id) {
+            self.file_id = Some(span.file_id);
+            self.line_marker(span.line, sources.name(span.file_id));
+        } else if span.line > self.line {
+            if span.line - self.line <= MAX_BLANK_LINES {
+                for _ in self.line..span.line {
+                    self.text.push('\n');
+                }
+                self.line = span.line;
+                self.column = 0;
+                self.expanded = false;
+            } else {
+                self.line_marker(span.line, sources.name(span.file_id));
+            }
+        }
+
+        // columns only match the source up to the first macro expansion on the line.
+        self.expanded |= !token.hideset.is_empty();
+        if !self.expanded && span.line == self.line && span.column > self.column + 1 {
+            let padding = span.column - 1 - self.column;
+            self.text.push_str(&" ".repeat(padding));
+            self.column += padding;
+        } else if self.column > 0 && (token.has_space || self.would_paste(token)) {
+            self.text.push(' ');
+            self.column += 1;
+        }
+        self.text.push_str(&token.text);
+        self.column += token.text.len();
+        self.last = Some(token.clone());
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        if self.column > 0 {
+            self.text.push('\n');
+        }
+        self.text
+    }
+
+    fn line_marker(&mut self, line: usize, name: &str) {
+        if self.column > 0 {
+            self.text.push('\n');
+        }
+        self.text.push_str(&format!("# {} {}\n", line, quote(name)));
+        self.line = line;
+        self.column = 0;
+        self.expanded = false;
+    }
+
+    /// whether `token` would merge with the previous one if written right after it,
+    /// e.g. `-` followed by `-1` out of a macro expansion.
+    fn would_paste(&self, token: &PPToken) -> bool {
+        let Some(last) = &self.last else {
+            return false;
+        };
+        // neighbours in the source were told apart by the tokenizer already.
+        if last.hideset.is_empty() && token.hideset.is_empty() {
+            return false;
+        }
+        let text = format!("{}{}", last.text, token.text);
+        match tokenize(&text, token.span.file_id) {
+            Ok(tokens) => tokens.first().map_or(true, |t| t.text != last.text),
+            Err(_) => true,
+        }
+    }
+}
@@ -0,0 +1,294 @@
+use diagnostic::Diagnostic;
+use span::{FileId, Span};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum TokenKind {
+    Identifier,
+    Number,
+    Character,
+    String,
+    Punctuator,
+    /// any other character, or an unterminated literal running to the end of the line.
+    Other,
+}
+
+/// preprocessing token, see C11 6.4.
+#[derive(Debug, Clone)]
+pub(crate) struct PPToken {
+    pub(crate) kind: TokenKind,
+    pub(crate) text: String,
+    pub(crate) span: Span,
+    /// first token of its line.
+    pub(crate) at_line_start: bool,
+    /// preceded by whitespace or a comment.
+    pub(crate) has_space: bool,
+    /// macros which must not be expanded again from this token.
+    pub(crate) hideset: Vec<String>,
+}
+
+impl PPToken {
+    pub(crate) fn new(kind: TokenKind, text: String, span: Span) -> Self {
+        Self {
+            kind,
+            text,
+            span,
+            at_line_start: false,
+            has_space: false,
+            hideset: vec![],
+        }
+    }
+
+    pub(crate) fn is(&self, punctuator: &str) -> bool {
+        self.kind == TokenKind::Punctuator && self.text == punctuator
+    }
+
+    pub(crate) fn is_identifier(&self) -> bool {
+        self.kind == TokenKind::Identifier
+    }
+}
+
+const PUNCTUATORS: [&str; 23] = [
+    "...", "<<=", ">>=", "->", "++", "--", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "*=",
+    "/=", "%=", "+=", "-=", "&=", "^=", "|=", "##",
+];
+
+const SINGLE_PUNCTUATORS: &[u8] = b"[](){}.&*+-~!/%<>^|?:;=,#";
+
+/// a byte of the source with its original location, line splices removed.
+struct Char {
+    byte: u8,
+    position: usize,
+    line: usize,
+    column: usize,
+}
+
+/// drops backslash-newline sequences, joining the lines around them.
+fn splice(src: &str) -> Vec<Char> {
+    let bytes = src.as_bytes();
+    let (mut line, mut column) = (1, 1);
+    let mut chars = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            let newline = match &bytes[i + 1..] {
+                [b'\n', ..] => 1,
+                [b'\r', b'\n', ..] => 2,
+                _ => 0,
+            };
+            if newline > 0 {
+                i += 1 + newline;
+                line += 1;
+                column = 1;
+                continue;
+            }
+        }
+        chars.push(Char {
+            byte: bytes[i],
+            position: i,
+            line,
+            column,
+        });
+        if bytes[i] == b'\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+        i += 1;
+    }
+    chars
+}
+
+/// splits `src` into preprocessing tokens, dropping comments.
+pub(crate) fn tokenize(src: &str, file_id: FileId) -> Result<Vec<PPToken>, Diagnostic> {
+    let chars = splice(src);
+    let mut tokens = vec![];
+    let (mut at_line_start, mut has_space) = (true, false);
+    let mut i = 0;
+    while i < chars.len() {
+        let byte = |i: usize| chars.get(i).map_or(0, |c| c.byte);
+        match (byte(i), byte(i + 1)) {
+            (b'\n', _) => {
+                at_line_start = true;
+                has_space = false;
+                i += 1;
+                continue;
+            }
+            (b' ' | b'\t' | b'\r' | b'\x0b' | b'\x0c', _) => {
+                has_space = true;
+                i += 1;
+                continue;
+            }
+            (b'/', b'/') => {
+                while i < chars.len() && chars[i].byte != b'\n' {
+                    i += 1;
+                }
+                has_space = true;
+                continue;
+            }
+            (b'/', b'*') => {
+                let start = &chars[i];
+                i += 2;
+                while i < chars.len() && !(byte(i) == b'*' && byte(i + 1) == b'/') {
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    let span = Span::new(
+                        file_id,
+                        start.position,
+                        start.position + 2,
+                        start.line,
+                        start.column,
+                    );
+                    return Err(Diagnostic::error("unterminated comment")
+                        .with_code("E0401")
+                        .with_primary_label(span, "comment starts here"));
+                }
+                i += 2;
+                has_space = true;
+                continue;
+            }
+            _ => {}
+        }
+
+        let start = i;
+        let kind = read_token(&chars, &mut i);
+        let bytes = chars[start..i].iter().map(|c| c.byte).collect::<Vec<_>>();
+        let span = Span::new(
+            file_id,
+            chars[start].position,
+            chars[i - 1].position + 1,
+            chars[start].line,
+            chars[start].column,
+        );
+        tokens.push(PPToken {
+            at_line_start,
+            has_space,
+            ..PPToken::new(kind, String::from_utf8_lossy(&bytes).into_owned(), span)
+        });
+        at_line_start = false;
+        has_space = false;
+    }
+    Ok(tokens)
+}
+
+fn starts_with(chars: &[Char], i: usize, s: &str) -> bool {
+    s.bytes()
+        .enumerate()
+        .all(|(j, b)| chars.get(i + j).map_or(false, |c| c.byte == b))
+}
+
+/// reads the token starting at `chars[*i]`, advancing `i` past it.
+fn read_token(chars: &[Char], i: &mut usize) -> TokenKind {
+    let byte = |i: usize| chars.get(i).map_or(0, |c| c.byte);
+    let c = byte(*i);
+
+    // character constants and string literals, with an optional encoding prefix.
+    let prefix = ["u8", "u", "U", "L"]
+        .iter()
+        .find(|p| starts_with(chars, *i, p) && matches!(byte(*i + p.len()), b'"' | b'\''))
+        .map_or(0, |p| p.len());
+    let quote = byte(*i + prefix);
+    if quote == b'"' || quote == b'\'' {
+        let mut j = *i + prefix + 1;
+        while j < chars.len() && chars[j].byte != b'\n' {
+            match chars[j].byte {
+                b'\\' => j += 2,
+                b if b == quote => {
+                    *i = j + 1;
+                    return if quote == b'"' {
+                        TokenKind::String
+                    } else {
+                        TokenKind::Character
+                    };
+                }
+                _ => j += 1,
+            }
+        }
+        // unterminated, the rest of the line becomes a single token.
+        while *i < chars.len() && chars[*i].byte != b'\n' {
+            *i += 1;
+        }
+        return TokenKind::Other;
+    }
+
+    if c.is_ascii_digit() || (c == b'.' && byte(*i + 1).is_ascii_digit()) {
+        *i += 1;
+        loop {
+            match (byte(*i), byte(*i + 1)) {
+                (b'e' | b'E' | b'p' | b'P', b'+' | b'-') => *i += 2,
+                (b, _) if b.is_ascii_alphanumeric() || b == b'_' || b == b'.' => *i += 1,
+                _ => break,
+            }
+        }
+        return TokenKind::Number;
+    }
+
+    if c.is_ascii_alphabetic() || c == b'_' || c == b'$' {
+        while byte(*i).is_ascii_alphanumeric() || byte(*i) == b'_' || byte(*i) == b'$' {
+            *i += 1;
+        }
+        return TokenKind::Identifier;
+    }
+
+    if let Some(p) = PUNCTUATORS.iter().find(|p| starts_with(chars, *i, p)) {
+        *i += p.len();
+        return TokenKind::Punctuator;
+    }
+    *i += 1;
+    if SINGLE_PUNCTUATORS.contains(&c) {
+        return TokenKind::Punctuator;
+    }
+    // keep multi-byte characters in one piece.
+    while *i < chars.len() && (0x80..0xc0).contains(&chars[*i].byte) {
+        *i += 1;
+    }
+    TokenKind::Other
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        let input = "#define f(x) L\"a\\\"b\" 'c' 1.5e+3f x##y ... \\\n  a /* b */ c // d\n@";
+        let tokens = tokenize(input, FileId(0)).unwrap();
+        let cases = vec![
+            (TokenKind::Punctuator, "#", true, false),
+            (TokenKind::Identifier, "define", false, false),
+            (TokenKind::Identifier, "f", false, true),
+            (TokenKind::Punctuator, "(", false, false),
+            (TokenKind::Identifier, "x", false, false),
+            (TokenKind::Punctuator, ")", false, false),
+            (TokenKind::String, "L\"a\\\"b\"", false, true),
+            (TokenKind::Character, "'c'", false, true),
+            (TokenKind::Number, "1.5e+3f", false, true),
+            (TokenKind::Identifier, "x", false, true),
+            (TokenKind::Punctuator, "##", false, false),
+            (TokenKind::Identifier, "y", false, false),
+            (TokenKind::Punctuator, "...", false, true),
+            (TokenKind::Identifier, "a", false, true),
+            (TokenKind::Identifier, "c", false, true),
+            (TokenKind::Other, "@", true, false),
+        ];
+        assert_eq!(tokens.len(), cases.len());
+        for (token, (kind, text, at_line_start, has_space)) in tokens.iter().zip(cases) {
+            assert_eq!(token.kind, kind);
+            assert_eq!(token.text, text);
+            assert_eq!(token.at_line_start, at_line_start);
+            assert_eq!(token.has_space, has_space);
+        }
+        // `a` comes after the line splice.
+        assert_eq!(tokens[13].span, Span::new(FileId(0), 46, 47, 2, 3));
+    }
+
+    #[test]
+    fn test_tokenize_error() {
+        assert!(tokenize("int a; /* comment", FileId(0)).is_err());
+        let tokens = tokenize("don't\nx", FileId(0)).unwrap();
+        assert_eq!(tokens[1].kind, TokenKind::Other);
+        assert_eq!(tokens[1].text, "'t");
+        assert_eq!(tokens[2].text, "x");
+    }
+}
@@ -0,0 +1,26 @@
+use ast::{Fold, Operation, Type};
+
+use crate::{ExpressionKind, TypedExpression};
+
+/// the implicit conversions are casts, so the operands fold already converted.
+impl Fold for TypedExpression {
+    fn type_(&self) -> Option<Type> {
+        Some(self.type_.clone())
+    }
+
+    fn operation(&self) -> Operation<'_, Self> {
+        match &self.kind {
+            ExpressionKind::Integer(value, _) => Operation::Integer(*value),
+            ExpressionKind::Float(value, _) => Operation::Float(*value),
+            ExpressionKind::Unary { expr, op } => Operation::Unary(op, expr),
+            ExpressionKind::Binary { lhs, op, rhs } => Operation::Binary(lhs, op, rhs),
+            ExpressionKind::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => Operation::Conditional(condition, then, otherwise),
+            ExpressionKind::Cast { expr, .. } => Operation::Cast(expr),
+            _ => Operation::Other,
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use ast::{common_type, is_arithmetic, is_integer, is_scalar, promoted, Type, TypeEnum};
+use diagnostic::Diagnostic;
+
+use crate::{ExpressionKind, TypedExpression};
+
+/// `expr` used for its value: arrays are converted to a pointer to their first element, and
+/// function designators to a pointer to the function.
+pub(crate) fn value(expr: TypedExpression) -> TypedExpression {
+    let pointer = match &expr.type_ {
+        Type::Array { type_, .. } => Type::Pointer(type_.clone()),
+        function @ Type::Function { .. } => Type::Pointer(Box::new(function.clone())),
+        _ => return expr,
+    };
+    convert(expr, &pointer)
+}
+
+/// implicit conversion of `expr` to `type_`, left out when it already has the type.
+pub(crate) fn convert(expr: TypedExpression, type_: &Type) -> TypedExpression {
+    if expr.type_ == *type_.unqualified() {
+        return expr;
+    }
+    let span = expr.span;
+    TypedExpression::new(
+        ExpressionKind::Cast {
+            expr: Box::new(expr),
+            implicit: true,
+        },
+        type_,
+        span,
+    )
+}
+
+/// the integer promotions, values of the types smaller than `int` are used as `int`.
+pub(crate) fn promote(expr: TypedExpression) -> TypedExpression {
+    let type_ = promoted(&expr.type_);
+    convert(expr, &type_)
+}
+
+/// the default argument promotions, for the arguments a prototype does not give a type:
+/// the integer promotions, and `float` to `double`.
+pub(crate) fn promote_argument(expr: TypedExpression) -> TypedExpression {
+    match expr.type_ {
+        Type::Primitive(TypeEnum::Float) => convert(expr, &Type::Primitive(TypeEnum::Double)),
+        _ => promote(expr),
+    }
+}
+
+/// `lhs` and `rhs` converted to their common type.
+pub(crate) fn arithmetic_conversions(
+    lhs: TypedExpression,
+    rhs: TypedExpression,
+) -> (TypedExpression, TypedExpression) {
+    let type_ = common_type(&lhs.type_, &rhs.type_);
+    (convert(lhs, &type_), convert(rhs, &type_))
+}
+
+/// where a value is converted as if by assignment.
+pub(crate) enum Assignment {
+    Assign,
+    Initialize,
+    /// to the parameter at this index.
+    Argument(usize),
+    Return,
+}
+
+/// `expr` converted to `type_` as if by assignment: scalars convert to one another, structs only
+/// to the same struct.
+pub(crate) fn assign(
+    expr: TypedExpression,
+    type_: &Type,
+    assignment: Assignment,
+) -> Result<TypedExpression, Diagnostic> {
+    let compatible = match type_.unqualified() {
+        Type::Struct(_) => expr.type_ == *type_.unqualified(),
+        Type::Primitive(TypeEnum::Void) => false,
+        // pointers and floating values do not convert to one another.
+        Type::Pointer(_) => is_integer(&expr.type_) || matches!(expr.type_, Type::Pointer(_)),
+        to if !is_integer(to) => is_arithmetic(&expr.type_),
+        _ => is_scalar(&expr.type_),
+    };
+    if compatible {
+        return Ok(convert(expr, type_));
+    }
+    let (from, to) = (&expr.type_, type_);
+    let message = match assignment {
+        Assignment::Assign => format!("assigning to `{}` from `{}`", to, from),
+        Assignment::Initialize => format!("initializing `{}` with an expression of `{}`", to, from),
+        Assignment::Argument(i) => {
+            format!("passing `{}` to parameter {} of type `{}`", from, i + 1, to)
+        }
+        Assignment::Return => format!(
+            "returning `{}` from a function with result type `{}`",
+            from, to
+        ),
+    };
+    Err(Diagnostic::error(format!("incompatible types {}", message))
+        .with_code("E0202")
+        .with_primary_label(expr.span, format!("expected `{}`, found `{}`", to, from)))
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+
+use ast::{Expression, ExpressionKind as Parsed};
+
+use crate::{ExpressionKind, TypedExpression};
+
+/// like the expression in the source, the implicit conversions left out.
+impl fmt::Display for TypedExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", source(self))
+    }
+}
+
+fn source(expr: &TypedExpression) -> Expression {
+    let boxed = |expr: &TypedExpression| Box::new(source(expr));
+    let type_ = expr.type_.clone();
+    let kind = match &expr.kind {
+        ExpressionKind::LocalVariable { name, offset } => Parsed::LocalVariable {
+            name: name.clone(),
+            offset: *offset,
+            type_,
+        },
+        ExpressionKind::GlobalVariable { name } => Parsed::GlobalVariable {
+            name: name.clone(),
+            type_,
+        },
+        ExpressionKind::Function { name } => Parsed::Function {
+            name: name.clone(),
+            type_,
+        },
+        ExpressionKind::Integer(n, type_) => Parsed::Integer(*n, *type_),
+        ExpressionKind::Float(n, type_) => Parsed::Float(*n, *type_),
+        ExpressionKind::Binary { lhs, op, rhs } => Parsed::Binary {
+            lhs: boxed(lhs),
+            op: op.clone(),
+            rhs: boxed(rhs),
+        },
+        ExpressionKind::Unary { expr, op } => Parsed::Unary {
+            expr: boxed(expr),
+            op: op.clone(),
+        },
+        ExpressionKind::Call { callee, arguments } => Parsed::Call {
+            callee: boxed(callee),
+            arguments: arguments.iter().map(source).collect(),
+        },
+        ExpressionKind::Index { expr, index } => Parsed::Index {
+            expr: boxed(expr),
+            index: boxed(index),
+        },
+        ExpressionKind::Member { expr, name, offset } => Parsed::Member {
+            expr: boxed(expr),
+            name: name.clone(),
+            offset: *offset,
+            type_,
+        },
+        ExpressionKind::Array { elements } => Parsed::Array {
+            elements: elements.iter().map(source).collect(),
+        },
+        ExpressionKind::Conditional {
+            condition,
+            then,
+            otherwise,
+        } => Parsed::Conditional {
+            condition: boxed(condition),
+            then: boxed(then),
+            otherwise: boxed(otherwise),
+        },
+        ExpressionKind::Comma { lhs, rhs } => Parsed::Comma {
+            lhs: boxed(lhs),
+            rhs: boxed(rhs),
+        },
+        ExpressionKind::Cast {
+            expr,
+            implicit: true,
+        } => return source(expr),
+        ExpressionKind::Cast {
+            expr,
+            implicit: false,
+        } => Parsed::Cast {
+            expr: boxed(expr),
+            type_,
+        },
+        ExpressionKind::String(bytes) => Parsed::String(bytes.clone()),
+    };
+    Expression::new(kind, expr.span)
+}
@@ -0,0 +1,1045 @@
+use ast::{
+    binary_type, conditional_type, fold_integer, is_arithmetic, is_integer, is_scalar, unary_type,
+    BinaryOperator, Expression, ExpressionKind as Parsed, Type, TypeEnum, UnaryOperator,
+};
+use diagnostic::Diagnostic;
+use span::Span;
+
+use crate::conversion::{
+    arithmetic_conversions, assign, convert, promote, promote_argument, value, Assignment,
+};
+use crate::{invalid_operands, ExpressionKind, TypedExpression};
+
+/// `expr` with its type and the types of its operands. only dispatches on the kind of `expr`,
+/// since every operand nests a frame of this function.
+pub(crate) fn check(expr: &Expression) -> Result<TypedExpression, Diagnostic> {
+    let span = expr.span;
+    match &expr.kind {
+        Parsed::Unary { expr, op } => check_unary(expr, op, span),
+        Parsed::Binary { lhs, op, rhs } => check_binary(lhs, op, rhs, span),
+        Parsed::Call { callee, arguments } => check_call(callee, arguments, span),
+        Parsed::Index { expr, index } => check_index(expr, index, span),
+        Parsed::Member {
+            expr,
+            name,
+            offset,
+            type_,
+        } => check_member(expr, name, *offset, type_, span),
+        Parsed::Conditional {
+            condition,
+            then,
+            otherwise,
+        } => check_conditional(condition, then, otherwise, span),
+        Parsed::Comma { lhs, rhs } => check_comma(lhs, rhs, span),
+        Parsed::Cast { expr, type_ } => check_cast(expr, type_, span),
+        _ => check_primary(expr),
+    }
+    .and_then(supported)
+}
+
+/// error for a `long double` value, a type which only declarations may name.
+fn supported(expr: TypedExpression) -> Result<TypedExpression, Diagnostic> {
+    match expr.type_.unqualified() {
+        Type::Primitive(TypeEnum::LongDouble) => {
+            Err(Diagnostic::error("`long double` is not supported")
+                .with_code("E0300")
+                .with_primary_label(expr.span, "`long double` value"))
+        }
+        _ => Ok(expr),
+    }
+}
+
+/// a variable, a function designator or a literal.
+fn check_primary(expr: &Expression) -> Result<TypedExpression, Diagnostic> {
+    let span = expr.span;
+    let typed = match &expr.kind {
+        Parsed::LocalVariable {
+            name,
+            offset,
+            type_,
+        } => TypedExpression::new(
+            ExpressionKind::LocalVariable {
+                name: name.clone(),
+                offset: *offset,
+            },
+            type_,
+            span,
+        ),
+        Parsed::GlobalVariable { name, type_ } => TypedExpression::new(
+            ExpressionKind::GlobalVariable { name: name.clone() },
+            type_,
+            span,
+        ),
+        Parsed::Function { name, type_ } => {
+            TypedExpression::new(ExpressionKind::Function { name: name.clone() }, type_, span)
+        }
+        Parsed::Integer(n, type_) => {
+            TypedExpression::new(ExpressionKind::Integer(*n, *type_), &type_.type_(), span)
+        }
+        Parsed::Float(n, type_) => {
+            TypedExpression::new(ExpressionKind::Float(*n, *type_), &type_.type_(), span)
+        }
+        // the literal holds its terminating null.
+        Parsed::String(bytes) => TypedExpression::new(
+            ExpressionKind::String(bytes.clone()),
+            &Type::Array {
+                type_: Box::new(Type::Primitive(TypeEnum::Char)),
+                size: bytes.len() as i32 + 1,
+            },
+            span,
+        ),
+        Parsed::Array { .. } => {
+            return Err(Diagnostic::error(format!(
+                "initializer list `{}` can only initialize a variable",
+                expr
+            ))
+            .with_code("E0203")
+            .with_primary_label(span, "not in a declaration"))
+        }
+        _ => unreachable!("`{}` is not a primary expression", expr),
+    };
+    Ok(typed)
+}
+
+/// `expr[index]` at `span`, either operand may be the pointer.
+fn check_index(
+    expr: &Expression,
+    index: &Expression,
+    span: Span,
+) -> Result<TypedExpression, Diagnostic> {
+    let (expr, index) = match (value(check(expr)?), value(check(index)?)) {
+        (expr, index) if is_integer(&expr.type_) => (index, expr),
+        operands => operands,
+    };
+    let Type::Pointer(element) = expr.type_.clone() else {
+        return Err(invalid_operands(
+            format!(
+                "subscripted value `{}` of type `{}` is not an array or pointer",
+                expr, expr.type_
+            ),
+            expr.span,
+            "subscripted here",
+        ));
+    };
+    if !is_integer(&index.type_) {
+        return Err(invalid_operands(
+            format!(
+                "array subscript `{}` of type `{}` is not an integer",
+                index, index.type_
+            ),
+            index.span,
+            "not an integer",
+        ));
+    }
+    Ok(TypedExpression::new(
+        ExpressionKind::Index {
+            expr: Box::new(expr),
+            index: Box::new(promote(index)),
+        },
+        &element,
+        span,
+    ))
+}
+
+/// member `name` of `expr` at `span`, whose offset and type the parser resolved.
+fn check_member(
+    expr: &Expression,
+    name: &str,
+    offset: usize,
+    type_: &Type,
+    span: Span,
+) -> Result<TypedExpression, Diagnostic> {
+    Ok(TypedExpression::new(
+        ExpressionKind::Member {
+            expr: Box::new(check(expr)?),
+            name: name.to_string(),
+            offset,
+        },
+        type_,
+        span,
+    ))
+}
+
+/// `lhs, rhs` at `span`, of the type of `rhs`.
+fn check_comma(
+    lhs: &Expression,
+    rhs: &Expression,
+    span: Span,
+) -> Result<TypedExpression, Diagnostic> {
+    let (lhs, rhs) = (check(lhs)?, value(check(rhs)?));
+    let type_ = rhs.type_.clone();
+    Ok(TypedExpression::new(
+        ExpressionKind::Comma {
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+        },
+        &type_,
+        span,
+    ))
+}
+
+/// `expr` used where a scalar is tested against 0, by `what`.
+pub(crate) fn check_condition(
+    expr: &Expression,
+    what: &str,
+) -> Result<TypedExpression, Diagnostic> {
+    scalar(value(check(expr)?), what)
+}
+
+/// error unless `condition` is a scalar, which `what` tests against 0. a floating `condition` is
+/// converted to `_Bool`, so that it is tested like an integer.
+fn scalar(condition: TypedExpression, what: &str) -> Result<TypedExpression, Diagnostic> {
+    if !is_scalar(&condition.type_) {
+        return Err(invalid_operands(
+            format!(
+                "{} requires a scalar value, `{}` has type `{}`",
+                what, condition, condition.type_
+            ),
+            condition.span,
+            "expected a scalar",
+        ));
+    }
+    Ok(
+        match is_arithmetic(&condition.type_) && !is_integer(&condition.type_) {
+            true => convert(condition, &Type::Primitive(TypeEnum::Bool)),
+            false => condition,
+        },
+    )
+}
+
+fn check_unary(
+    operand: &Expression,
+    op: &UnaryOperator,
+    span: Span,
+) -> Result<TypedExpression, Diagnostic> {
+    check(operand).and_then(|operand| unary(operand, op, span))
+}
+
+/// `op operand` at `span`, once its operand is checked.
+fn unary(
+    operand: TypedExpression,
+    op: &UnaryOperator,
+    span: Span,
+) -> Result<TypedExpression, Diagnostic> {
+    let operand = match op {
+        UnaryOperator::Reference => {
+            if !is_lvalue(&operand) && !matches!(operand.type_, Type::Function { .. }) {
+                return Err(Diagnostic::error(format!(
+                    "cannot take the address of the value `{}`",
+                    operand
+                ))
+                .with_code("E0201")
+                .with_primary_label(operand.span, "not an object"));
+            }
+            operand
+        }
+        UnaryOperator::Dereference => value(operand),
+        UnaryOperator::Not => scalar(value(operand), "`!`")?,
+        UnaryOperator::Plus | UnaryOperator::Minus | UnaryOperator::BitNot => {
+            promote(value(operand))
+        }
+        UnaryOperator::Increment
+        | UnaryOperator::Decrement
+        | UnaryOperator::PostIncrement
+        | UnaryOperator::PostDecrement => {
+            ensure_assignable(&operand)?;
+            operand
+        }
+    };
+    let Some(type_) = unary_type(op, &operand.type_) else {
+        return Err(unary_error(&operand, op));
+    };
+    Ok(TypedExpression::new(
+        ExpressionKind::Unary {
+            expr: Box::new(operand),
+            op: op.clone(),
+        },
+        &type_,
+        span,
+    ))
+}
+
+/// error for `op operand`, whose operand the operator does not take.
+fn unary_error(operand: &TypedExpression, op: &UnaryOperator) -> Diagnostic {
+    let (message, label) = match op {
+        UnaryOperator::Dereference => (
+            format!(
+                "indirection requires a pointer operand, `{}` has type `{}`",
+                operand, operand.type_
+            ),
+            "not a pointer",
+        ),
+        UnaryOperator::Increment | UnaryOperator::PostIncrement => (
+            format!("cannot increment `{}` of type `{}`", operand, operand.type_),
+            "expected a scalar",
+        ),
+        UnaryOperator::Decrement | UnaryOperator::PostDecrement => (
+            format!("cannot decrement `{}` of type `{}`", operand, operand.type_),
+            "expected a scalar",
+        ),
+        _ => (
+            format!(
+                "invalid argument type `{}` to unary `{}`",
+                operand.type_, op
+            ),
+            "invalid operand",
+        ),
+    };
+    invalid_operands(message, operand.span, label)
+}
+
+fn check_binary(
+    lhs: &Expression,
+    op: &BinaryOperator,
+    rhs: &Expression,
+    span: Span,
+) -> Result<TypedExpression, Diagnostic> {
+    if let BinaryOperator::And | BinaryOperator::Or = op {
+        return check_logical(lhs, op, rhs, span);
+    }
+    // unwrapping `lhs` with `?` would keep a copy of the result in the frame of each operand
+    // of a long sum.
+    check(lhs).and_then(|lhs| typed_binary(lhs, op, value(check(rhs)?), span))
+}
+
+/// `lhs && rhs` or `lhs || rhs` at `span`, whose operands are tested against 0.
+fn check_logical(
+    lhs: &Expression,
+    op: &BinaryOperator,
+    rhs: &Expression,
+    span: Span,
+) -> Result<TypedExpression, Diagnostic> {
+    let what = format!("`{}`", op);
+    let (lhs, rhs) = (check_condition(lhs, &what)?, check_condition(rhs, &what)?);
+    Ok(binary(lhs, op, rhs, &Type::Primitive(TypeEnum::Int), span))
+}
+
+/// `lhs op rhs` at `span`, once its operands are checked.
+fn typed_binary(
+    lhs: TypedExpression,
+    op: &BinaryOperator,
+    rhs: TypedExpression,
+    span: Span,
+) -> Result<TypedExpression, Diagnostic> {
+    if *op == BinaryOperator::Assignment {
+        ensure_assignable(&lhs)?;
+        let type_ = lhs.type_.clone();
+        let rhs = assign(rhs, &type_, Assignment::Assign)?;
+        return Ok(binary(lhs, op, rhs, &type_, span));
+    }
+    if let Some(arithmetic) = op.compound_operator() {
+        // `lhs op= rhs` is `lhs = lhs op rhs`, with `rhs` converted like in `lhs op rhs`.
+        ensure_assignable(&lhs)?;
+        if let Type::Pointer(_) = rhs.type_ {
+            return Err(binary_error(&lhs, op, &rhs, span));
+        }
+        let type_ = lhs.type_.clone();
+        let (_, rhs, _) = arithmetic_operands(lhs.clone(), &arithmetic, rhs, span)?;
+        return Ok(binary(lhs, op, rhs, &type_, span));
+    }
+    let (lhs, rhs, type_) = arithmetic_operands(value(lhs), op, rhs, span)?;
+    Ok(binary(lhs, op, rhs, &type_, span))
+}
+
+/// operands of the arithmetic or comparison `lhs op rhs` at `span` converted for it, with the
+/// type of the result. for pointer arithmetic, the pointer is the lhs and the integer counts
+/// elements.
+fn arithmetic_operands(
+    lhs: TypedExpression,
+    op: &BinaryOperator,
+    rhs: TypedExpression,
+    span: Span,
+) -> Result<(TypedExpression, TypedExpression, Type), Diagnostic> {
+    let Some(type_) = binary_type(op, &lhs.type_, &rhs.type_) else {
+        return Err(binary_error(&lhs, op, &rhs, span));
+    };
+    let (lhs, rhs) = match (op, &lhs.type_, &rhs.type_) {
+        (BinaryOperator::LShift | BinaryOperator::RShift, _, _) => (promote(lhs), promote(rhs)),
+        (_, l, r) if is_arithmetic(l) && is_arithmetic(r) => arithmetic_conversions(lhs, rhs),
+        (BinaryOperator::Plus | BinaryOperator::Minus, Type::Pointer(_), r) if is_integer(r) => {
+            (lhs, promote(rhs))
+        }
+        (BinaryOperator::Plus, l, Type::Pointer(_)) if is_integer(l) => (rhs, promote(lhs)),
+        // a pointer compares with an integer converted to it.
+        (_, pointer @ Type::Pointer(_), r) if is_integer(r) => {
+            let pointer = pointer.clone();
+            (lhs, convert(rhs, &pointer))
+        }
+        (_, l, pointer @ Type::Pointer(_)) if is_integer(l) => {
+            let pointer = pointer.clone();
+            (convert(lhs, &pointer), rhs)
+        }
+        _ => (lhs, rhs),
+    };
+    Ok((lhs, rhs, type_))
+}
+
+fn binary(
+    lhs: TypedExpression,
+    op: &BinaryOperator,
+    rhs: TypedExpression,
+    type_: &Type,
+    span: Span,
+) -> TypedExpression {
+    TypedExpression::new(
+        ExpressionKind::Binary {
+            lhs: Box::new(lhs),
+            op: op.clone(),
+            rhs: Box::new(rhs),
+        },
+        type_,
+        span,
+    )
+}
+
+/// error for the operation at `span`, pointing at both of its operands.
+fn binary_error(
+    lhs: &TypedExpression,
+    op: &BinaryOperator,
+    rhs: &TypedExpression,
+    span: Span,
+) -> Diagnostic {
+    invalid_operands(
+        format!(
+            "invalid operands to binary `{}` (`{}` and `{}`)",
+            op, lhs.type_, rhs.type_
+        ),
+        span,
+        "invalid operands",
+    )
+    .with_secondary_label(lhs.span, format!("`{}`", lhs.type_))
+    .with_secondary_label(rhs.span, format!("`{}`", rhs.type_))
+}
+
+/// `callee(arguments)` at `span`. the arguments are converted to the types of the parameters,
+/// those in the variable part or of a function without a prototype get the default argument
+/// promotions.
+fn check_call(
+    callee: &Expression,
+    arguments: &[Expression],
+    span: Span,
+) -> Result<TypedExpression, Diagnostic> {
+    let callee = value(check(callee)?);
+    let Type::Pointer(function) = &callee.type_ else {
+        return Err(not_a_function(&callee));
+    };
+    let Type::Function {
+        return_type,
+        parameters,
+        variadic,
+        prototyped,
+    } = function.unqualified().clone()
+    else {
+        return Err(not_a_function(&callee));
+    };
+    let too_few = arguments.len() < parameters.len();
+    if prototyped && (too_few || (arguments.len() > parameters.len() && !variadic)) {
+        return Err(Diagnostic::error(format!(
+            "too {} arguments to function call, expected {}{}, have {}",
+            if too_few { "few" } else { "many" },
+            if variadic { "at least " } else { "" },
+            parameters.len(),
+            arguments.len()
+        ))
+        .with_code("E0105")
+        .with_primary_label(span, "in this call"));
+    }
+    let arguments = arguments
+        .iter()
+        .enumerate()
+        .map(|(i, argument)| {
+            let argument = value(check(argument)?);
+            match parameters.get(i) {
+                Some(parameter) => check_argument(argument, parameter, i),
+                None => Ok(promote_argument(argument)),
+            }
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(TypedExpression::new(
+        ExpressionKind::Call {
+            callee: Box::new(callee),
+            arguments,
+        },
+        &return_type,
+        span,
+    ))
+}
+
+/// `argument` converted to the `parameter` at `index`. unlike an assignment, integers other than
+/// a null pointer constant and pointers do not convert to one another, nor pointers to objects of
+/// another type but through `void *`.
+fn check_argument(
+    argument: TypedExpression,
+    parameter: &Type,
+    index: usize,
+) -> Result<TypedExpression, Diagnostic> {
+    let conversion = match (&argument.type_, parameter.unqualified()) {
+        (Type::Pointer(_), to) if is_integer(to) && *to != Type::Primitive(TypeEnum::Bool) => {
+            "incompatible pointer to integer"
+        }
+        (from, Type::Pointer(_)) if is_integer(from) && fold_integer(&argument) != Some(0) => {
+            "incompatible integer to pointer"
+        }
+        // the qualifiers of what they point to may differ.
+        (Type::Pointer(from), Type::Pointer(to))
+            if from.unqualified() != to.unqualified() && !is_void(from) && !is_void(to) =>
+        {
+            "incompatible pointer type"
+        }
+        _ => return assign(argument, parameter, Assignment::Argument(index)),
+    };
+    Err(Diagnostic::error(format!(
+        "{} conversion passing `{}` to parameter {} of type `{}`",
+        conversion,
+        argument.type_,
+        index + 1,
+        parameter
+    ))
+    .with_code("E0106")
+    .with_primary_label(
+        argument.span,
+        format!("expected `{}`, found `{}`", parameter, argument.type_),
+    ))
+}
+
+fn is_void(type_: &Type) -> bool {
+    *type_.unqualified() == Type::Primitive(TypeEnum::Void)
+}
+
+fn not_a_function(callee: &TypedExpression) -> Diagnostic {
+    Diagnostic::error(format!(
+        "called object `{}` of type `{}` is not a function or function pointer",
+        callee, callee.type_
+    ))
+    .with_code("E0110")
+    .with_primary_label(callee.span, "called here")
+}
+
+/// `(type_)expr` at `span`. scalars convert to one another, and anything to `void`.
+fn check_cast(expr: &Expression, type_: &Type, span: Span) -> Result<TypedExpression, Diagnostic> {
+    let expr = value(check(expr)?);
+    let invalid = match type_ {
+        Type::Primitive(TypeEnum::Void) => None,
+        _ if !is_scalar(type_) => Some(type_),
+        _ if !is_scalar(&expr.type_) => Some(&expr.type_),
+        _ => None,
+    };
+    if let Some(invalid) = invalid {
+        return Err(Diagnostic::error(format!(
+            "used type `{}` where arithmetic or pointer type is required",
+            invalid
+        ))
+        .with_code("E0113")
+        .with_primary_label(span, format!("in this cast to `{}`", type_)));
+    }
+    Ok(TypedExpression::new(
+        ExpressionKind::Cast {
+            expr: Box::new(expr),
+            implicit: false,
+        },
+        type_,
+        span,
+    ))
+}
+
+/// `condition ? then : otherwise` at `span`, whose branches are converted to a common type.
+fn check_conditional(
+    condition: &Expression,
+    then: &Expression,
+    otherwise: &Expression,
+    span: Span,
+) -> Result<TypedExpression, Diagnostic> {
+    let condition = check_condition(condition, "the condition of `?:`")?;
+    let (then, otherwise) = (value(check(then)?), value(check(otherwise)?));
+    let Some(type_) = conditional_type(&then.type_, &otherwise.type_) else {
+        let (l, r) = (&then.type_, &otherwise.type_);
+        return Err(invalid_operands(
+            format!("incompatible operand types (`{}` and `{}`) in `?:`", l, r),
+            span,
+            "in this conditional",
+        )
+        .with_secondary_label(then.span, format!("`{}`", l))
+        .with_secondary_label(otherwise.span, format!("`{}`", r)));
+    };
+    Ok(TypedExpression::new(
+        ExpressionKind::Conditional {
+            condition: Box::new(condition),
+            then: Box::new(convert(then, &type_)),
+            otherwise: Box::new(convert(otherwise, &type_)),
+        },
+        &type_,
+        span,
+    ))
+}
+
+/// whether `expr` designates an object, which has an address.
+fn is_lvalue(expr: &TypedExpression) -> bool {
+    match &expr.kind {
+        ExpressionKind::LocalVariable { .. }
+        | ExpressionKind::GlobalVariable { .. }
+        | ExpressionKind::Index { .. }
+        | ExpressionKind::String(_)
+        | ExpressionKind::Unary {
+            op: UnaryOperator::Dereference,
+            ..
+        } => true,
+        ExpressionKind::Member { expr, .. } => is_lvalue(expr),
+        _ => false,
+    }
+}
+
+/// error unless `expr` is an object which can be assigned to.
+fn ensure_assignable(expr: &TypedExpression) -> Result<(), Diagnostic> {
+    let assignable = is_lvalue(expr)
+        && !matches!(
+            expr.type_,
+            Type::Array { .. } | Type::Function { .. } | Type::Primitive(TypeEnum::Void)
+        );
+    if !assignable {
+        return Err(
+            Diagnostic::error(format!("expression `{}` is not assignable", expr))
+                .with_code("E0201")
+                .with_primary_label(expr.span, "not assignable"),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use ast::{FloatType, IntegerType, StructType};
+
+    use super::*;
+
+    fn local(name: &str, type_: Type) -> Box<Expression> {
+        Box::new(
+            Parsed::LocalVariable {
+                name: String::from(name),
+                offset: 0,
+                type_,
+            }
+            .into(),
+        )
+    }
+
+    fn int(n: u64) -> Box<Expression> {
+        Box::new(Parsed::Integer(n, IntegerType::Int).into())
+    }
+
+    fn binary(lhs: Box<Expression>, op: BinaryOperator, rhs: Box<Expression>) -> Box<Expression> {
+        Box::new(Parsed::Binary { lhs, op, rhs }.into())
+    }
+
+    fn primitive(type_: TypeEnum) -> Type {
+        Type::Primitive(type_)
+    }
+
+    fn pointer(type_: Type) -> Type {
+        Type::Pointer(Box::new(type_))
+    }
+
+    fn array(type_: Type, size: i32) -> Type {
+        Type::Array {
+            type_: Box::new(type_),
+            size,
+        }
+    }
+
+    #[test]
+    fn test_check_expression_type() {
+        let c = || local("c", primitive(TypeEnum::Char));
+        let l = || local("l", primitive(TypeEnum::Long));
+        let a = || local("a", array(primitive(TypeEnum::Int), 3));
+        let p = || local("p", pointer(primitive(TypeEnum::Int)));
+        let u = || local("u", primitive(TypeEnum::UnsignedInt));
+        let uc = || local("uc", primitive(TypeEnum::UnsignedChar));
+        let ul = || local("ul", primitive(TypeEnum::UnsignedLong));
+        let ll = || local("ll", primitive(TypeEnum::LongLong));
+        let b = || local("b", primitive(TypeEnum::Bool));
+        let function = Box::new(
+            Parsed::Function {
+                name: String::from("f"),
+                type_: Type::Function {
+                    return_type: Box::new(primitive(TypeEnum::Short)),
+                    parameters: vec![primitive(TypeEnum::Long)],
+                    variadic: false,
+                    prototyped: true,
+                },
+            }
+            .into(),
+        );
+        let cases = vec![
+            (c(), "char"),
+            (binary(c(), BinaryOperator::Plus, c()), "int"),
+            (binary(c(), BinaryOperator::Asterisk, l()), "long"),
+            (binary(l(), BinaryOperator::LShift, c()), "long"),
+            (binary(c(), BinaryOperator::Lt, l()), "int"),
+            (binary(c(), BinaryOperator::PlusAssignment, l()), "char"),
+            (binary(uc(), BinaryOperator::Asterisk, uc()), "int"),
+            (binary(b(), BinaryOperator::Plus, b()), "int"),
+            (binary(u(), BinaryOperator::Plus, int(1)), "unsigned int"),
+            (binary(u(), BinaryOperator::Slash, l()), "long"),
+            (
+                binary(ul(), BinaryOperator::Minus, ll()),
+                "unsigned long long",
+            ),
+            (
+                binary(ll(), BinaryOperator::BitAnd, ul()),
+                "unsigned long long",
+            ),
+            (binary(u(), BinaryOperator::RShift, l()), "unsigned int"),
+            (binary(uc(), BinaryOperator::LShift, u()), "int"),
+            (a(), "int[3]"),
+            (binary(a(), BinaryOperator::Plus, int(1)), "int *"),
+            (binary(int(1), BinaryOperator::Plus, p()), "int *"),
+            (binary(p(), BinaryOperator::Minus, a()), "long"),
+            (
+                Box::new(
+                    Parsed::Unary {
+                        expr: binary(p(), BinaryOperator::Plus, int(1)),
+                        op: UnaryOperator::Dereference,
+                    }
+                    .into(),
+                ),
+                "int",
+            ),
+            (
+                Box::new(
+                    Parsed::Index {
+                        expr: int(1),
+                        index: a(),
+                    }
+                    .into(),
+                ),
+                "int",
+            ),
+            (
+                Box::new(
+                    Parsed::Unary {
+                        expr: a(),
+                        op: UnaryOperator::Reference,
+                    }
+                    .into(),
+                ),
+                "int (*)[3]",
+            ),
+            (
+                Box::new(
+                    Parsed::Unary {
+                        expr: c(),
+                        op: UnaryOperator::BitNot,
+                    }
+                    .into(),
+                ),
+                "int",
+            ),
+            (
+                Box::new(
+                    Parsed::Conditional {
+                        condition: p(),
+                        then: c(),
+                        otherwise: l(),
+                    }
+                    .into(),
+                ),
+                "long",
+            ),
+            (
+                Box::new(
+                    Parsed::Conditional {
+                        condition: c(),
+                        then: p(),
+                        otherwise: int(0),
+                    }
+                    .into(),
+                ),
+                "int *",
+            ),
+            (
+                Box::new(Parsed::Comma { lhs: c(), rhs: a() }.into()),
+                "int *",
+            ),
+            (Box::new(Parsed::String(b"abc".to_vec()).into()), "char[4]"),
+            (
+                Box::new(
+                    Parsed::Call {
+                        callee: function,
+                        arguments: vec![*c()],
+                    }
+                    .into(),
+                ),
+                "short",
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let typed = check(&input).unwrap();
+            assert_eq!(typed.type_.to_string(), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_fold() {
+        let cast = |expr: Box<Expression>, type_: TypeEnum| {
+            Box::new(
+                Parsed::Cast {
+                    expr,
+                    type_: primitive(type_),
+                }
+                .into(),
+            )
+        };
+        let unsigned = |n: u64| Box::new(Parsed::Integer(n, IntegerType::UnsignedInt).into());
+        let minus = |expr: Box<Expression>| {
+            Box::new(
+                Parsed::Unary {
+                    expr,
+                    op: UnaryOperator::Minus,
+                }
+                .into(),
+            )
+        };
+        let cases = vec![
+            (binary(int(7), BinaryOperator::Slash, minus(int(2))), -3),
+            (
+                binary(minus(unsigned(1)), BinaryOperator::RShift, int(28)),
+                15,
+            ),
+            (binary(minus(int(1)), BinaryOperator::RShift, int(28)), -1),
+            (binary(minus(int(1)), BinaryOperator::Lt, unsigned(0)), 0),
+            (
+                binary(unsigned(7), BinaryOperator::Percent, minus(int(2))),
+                7,
+            ),
+            (cast(int(300), TypeEnum::UnsignedChar), 44),
+            (cast(int(200), TypeEnum::SignedChar), -56),
+            (cast(int(4), TypeEnum::Bool), 1),
+            (cast(minus(int(1)), TypeEnum::UnsignedLong), -1),
+            (
+                Box::new(
+                    Parsed::Unary {
+                        expr: unsigned(0),
+                        op: UnaryOperator::BitNot,
+                    }
+                    .into(),
+                ),
+                u32::MAX as i64,
+            ),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(
+                fold_integer(&check(&input).unwrap()),
+                Some(expected),
+                "{}",
+                input
+            );
+        }
+        let division = binary(int(1), BinaryOperator::Slash, int(0));
+        assert_eq!(fold_integer(&check(&division).unwrap()), None);
+    }
+
+    #[test]
+    fn test_implicit_conversions() {
+        let c = || {
+            TypedExpression::new(
+                ExpressionKind::LocalVariable {
+                    name: String::from("c"),
+                    offset: 0,
+                },
+                &primitive(TypeEnum::Char),
+                Span::default(),
+            )
+        };
+        let implicit = |expr: TypedExpression, type_: Type| {
+            TypedExpression::new(
+                ExpressionKind::Cast {
+                    expr: Box::new(expr),
+                    implicit: true,
+                },
+                &type_,
+                Span::default(),
+            )
+        };
+
+        // the operands are promoted, and converted to their common type.
+        let input = binary(
+            local("c", primitive(TypeEnum::Char)),
+            BinaryOperator::Slash,
+            Box::new(Parsed::Integer(2, IntegerType::Long).into()),
+        );
+        let expected = TypedExpression::new(
+            ExpressionKind::Binary {
+                lhs: Box::new(implicit(c(), primitive(TypeEnum::Long))),
+                op: BinaryOperator::Slash,
+                rhs: Box::new(TypedExpression::new(
+                    ExpressionKind::Integer(2, IntegerType::Long),
+                    &primitive(TypeEnum::Long),
+                    Span::default(),
+                )),
+            },
+            &primitive(TypeEnum::Long),
+            Span::default(),
+        );
+        assert_eq!(check(&input).unwrap(), expected);
+        assert_eq!(expected.to_string(), "c / 2l");
+
+        // arrays are converted to a pointer, and the pointer moved to the lhs.
+        let input = binary(
+            int(1),
+            BinaryOperator::Plus,
+            local("s", array(primitive(TypeEnum::Char), 4)),
+        );
+        let s = TypedExpression::new(
+            ExpressionKind::LocalVariable {
+                name: String::from("s"),
+                offset: 0,
+            },
+            &array(primitive(TypeEnum::Char), 4),
+            Span::default(),
+        );
+        let expected = TypedExpression::new(
+            ExpressionKind::Binary {
+                lhs: Box::new(implicit(s, pointer(primitive(TypeEnum::Char)))),
+                op: BinaryOperator::Plus,
+                rhs: Box::new(TypedExpression::new(
+                    ExpressionKind::Integer(1, IntegerType::Int),
+                    &primitive(TypeEnum::Int),
+                    Span::default(),
+                )),
+            },
+            &pointer(primitive(TypeEnum::Char)),
+            Span::default(),
+        );
+        assert_eq!(check(&input).unwrap(), expected);
+
+        // a floating condition is tested as a `_Bool`.
+        let input = Box::new(
+            Parsed::Unary {
+                expr: Box::new(Parsed::Float(0.5, FloatType::Double).into()),
+                op: UnaryOperator::Not,
+            }
+            .into(),
+        );
+        let half = TypedExpression::new(
+            ExpressionKind::Float(0.5, FloatType::Double),
+            &primitive(TypeEnum::Double),
+            Span::default(),
+        );
+        let expected = TypedExpression::new(
+            ExpressionKind::Unary {
+                expr: Box::new(implicit(half, primitive(TypeEnum::Bool))),
+                op: UnaryOperator::Not,
+            },
+            &primitive(TypeEnum::Int),
+            Span::default(),
+        );
+        assert_eq!(check(&input).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_check_error() {
+        let s = || {
+            local(
+                "s",
+                Type::Struct(StructType::new(
+                    Some(String::from("s")),
+                    false,
+                    vec![(String::from("a"), primitive(TypeEnum::Int))],
+                )),
+            )
+        };
+        let cases = vec![
+            (
+                Box::new(
+                    Parsed::Unary {
+                        expr: int(1),
+                        op: UnaryOperator::Dereference,
+                    }
+                    .into(),
+                ),
+                "error[E0200]: indirection requires a pointer operand, `1` has type `int`",
+            ),
+            (
+                binary(s(), BinaryOperator::Plus, int(1)),
+                "error[E0200]: invalid operands to binary `+` (`struct s` and `int`)",
+            ),
+            (
+                Box::new(
+                    Parsed::Index {
+                        expr: int(1),
+                        index: int(2),
+                    }
+                    .into(),
+                ),
+                "error[E0200]: subscripted value `2` of type `int` is not an array or pointer",
+            ),
+            (
+                binary(int(1), BinaryOperator::Assignment, int(2)),
+                "error[E0201]: expression `1` is not assignable",
+            ),
+            (
+                binary(
+                    local("x", primitive(TypeEnum::Int)),
+                    BinaryOperator::Assignment,
+                    s(),
+                ),
+                "error[E0202]: incompatible types assigning to `int` from `struct s`",
+            ),
+            (
+                Box::new(
+                    Parsed::Array {
+                        elements: vec![*int(1)],
+                    }
+                    .into(),
+                ),
+                "error[E0203]: initializer list `{1}` can only initialize a variable",
+            ),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(check(&input).unwrap_err().to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_analyze_errors() {
+        let at = |start: usize| Span {
+            start,
+            end: start + 1,
+            ..Span::default()
+        };
+        let int = |n: u64, start: usize| {
+            Box::new(Expression::new(
+                Parsed::Integer(n, IntegerType::Int),
+                at(start),
+            ))
+        };
+        let program = ast::Program::new(vec![
+            // 1 = 2; 3; *4;
+            ast::Statement::Expression(
+                Parsed::Binary {
+                    lhs: int(1, 0),
+                    op: BinaryOperator::Assignment,
+                    rhs: int(2, 4),
+                }
+                .into(),
+            ),
+            ast::Statement::Expression(*int(3, 7)),
+            ast::Statement::Expression(
+                Parsed::Unary {
+                    expr: int(4, 11),
+                    op: UnaryOperator::Dereference,
+                }
+                .into(),
+            ),
+        ]);
+
+        let errors = crate::analyze(&program).unwrap_err();
+        let spans = errors
+            .iter()
+            .map(|error| error.primary_span())
+            .collect::<Vec<_>>();
+        assert_eq!(spans, vec![Some(at(0)), Some(at(11))]);
+    }
+}
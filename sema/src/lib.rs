@@ -0,0 +1,200 @@
+use ast::{BinaryOperator, FloatType, IntegerType, Program, Type, UnaryOperator};
+use diagnostic::Diagnostic;
+use span::Span;
+
+mod constant;
+mod conversion;
+mod display;
+mod expression;
+mod statement;
+
+// entry
+/// `program` with every expression annotated with its type, and the conversions C leaves implicit
+/// made explicit. the errors are those of every statement, at most one for each expression.
+pub fn analyze(program: &Program) -> Result<Program<TypedExpression>, Vec<Diagnostic>> {
+    let mut analyzer = Analyzer::default();
+    match analyzer.check_statements(&program.statements) {
+        Some(statements) if analyzer.errors.is_empty() => Ok(Program::new(statements)),
+        _ => Err(analyzer.errors),
+    }
+}
+
+/// expression with the type of its value.
+#[derive(Debug, Clone)]
+pub struct TypedExpression {
+    pub kind: ExpressionKind,
+    /// type without qualifiers. arrays and function designators keep their type where they are
+    /// used as values, inside a `Cast` to a pointer.
+    pub type_: Type,
+    /// source of the expression, that of the converted expression for an implicit conversion.
+    pub span: Span,
+}
+
+impl TypedExpression {
+    fn new(kind: ExpressionKind, type_: &Type, span: Span) -> Self {
+        Self {
+            kind,
+            type_: type_.unqualified().clone(),
+            span,
+        }
+    }
+}
+
+/// like `ast::Expression`, the spans are not compared.
+impl PartialEq for TypedExpression {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.type_ == other.type_
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ExpressionKind {
+    LocalVariable {
+        name: String,
+        offset: usize,
+    },
+    GlobalVariable {
+        name: String,
+    },
+    Function {
+        name: String,
+    },
+    Integer(u64, IntegerType),
+    Float(f64, FloatType),
+    /// operands converted to a common type, but for pointer arithmetic and shifts. a pointer is
+    /// always the lhs of `+` and `-`, the integer it is offset by counts elements.
+    Binary {
+        lhs: Box<TypedExpression>,
+        op: BinaryOperator,
+        rhs: Box<TypedExpression>,
+    },
+    Unary {
+        expr: Box<TypedExpression>,
+        op: UnaryOperator,
+    },
+    /// `callee` is a pointer to a function, the arguments are converted to its parameter types.
+    Call {
+        callee: Box<TypedExpression>,
+        arguments: Vec<TypedExpression>,
+    },
+    /// `expr` is a pointer and `index` an integer, whichever order they are written in.
+    Index {
+        expr: Box<TypedExpression>,
+        index: Box<TypedExpression>,
+    },
+    Member {
+        expr: Box<TypedExpression>,
+        name: String,
+        offset: usize,
+    },
+    /// initializer list, with the type it initializes.
+    Array {
+        elements: Vec<TypedExpression>,
+    },
+    Conditional {
+        condition: Box<TypedExpression>,
+        then: Box<TypedExpression>,
+        otherwise: Box<TypedExpression>,
+    },
+    Comma {
+        lhs: Box<TypedExpression>,
+        rhs: Box<TypedExpression>,
+    },
+    /// conversion of `expr` to the type of the cast, `implicit` when it is not in the source.
+    Cast {
+        expr: Box<TypedExpression>,
+        implicit: bool,
+    },
+    String(Vec<u8>),
+}
+
+/// state of the statements being checked.
+#[derive(Default)]
+struct Analyzer {
+    /// return type of the function being checked.
+    return_type: Option<Type>,
+    errors: Vec<Diagnostic>,
+}
+
+/// error for operands whose types the operation does not take, the one at `span`.
+fn invalid_operands(message: String, span: Span, label: &str) -> Diagnostic {
+    Diagnostic::error(message)
+        .with_code("E0200")
+        .with_primary_label(span, label)
+}
+
+#[cfg(test)]
+mod test {
+    use lex::Lexer;
+    use parse::ParseOptions;
+
+    #[test]
+    fn test_error_location() {
+        let cases = vec![
+            (
+                "int f(int a, int b);\nint main() {\n    return f(1);\n}",
+                "error[E0105]: too few arguments to function call, expected 2, have 1",
+                (3, 12),
+            ),
+            (
+                "int f(int a, ...);\nint main() {\n    return f();\n}",
+                "error[E0105]: too few arguments to function call, expected at least 1, have 0",
+                (3, 12),
+            ),
+            (
+                "int (*f)(int);\nint main() {\n    return f();\n}",
+                "error[E0105]: too few arguments to function call, expected 1, have 0",
+                (3, 12),
+            ),
+            (
+                "int f(void);\nint main() {\n    return f(1);\n}",
+                "error[E0105]: too many arguments to function call, expected 0, have 1",
+                (3, 12),
+            ),
+            (
+                "int f(char *s);\nint main() {\n    return f(1);\n}",
+                "error[E0106]: incompatible integer to pointer conversion passing `int` to parameter 1 of type `char *`",
+                (3, 14),
+            ),
+            (
+                "int f(char *s);\nint main() {\n    return f(0.5);\n}",
+                "error[E0202]: incompatible types passing `double` to parameter 1 of type `char *`",
+                (3, 14),
+            ),
+            (
+                "int main() {\n    int x;\n    return x(1);\n}",
+                "error[E0110]: called object `x` of type `int` is not a function or function pointer",
+                (3, 12),
+            ),
+            (
+                "struct s { int a; } v;\nint main() {\n    return (int)v;\n}",
+                "error[E0113]: used type `struct s` where arithmetic or pointer type is required",
+                (3, 12),
+            ),
+            (
+                "long double strtold(const char *s, char **end);\nint main() {\n    return strtold(\"1\", 0) > 0;\n}",
+                "error[E0300]: `long double` is not supported",
+                (3, 12),
+            ),
+            (
+                "int main() {\n    double d = 1.5L;\n}",
+                "error[E0300]: `long double` is not supported",
+                (2, 16),
+            ),
+            (
+                "void f(int a) {\n    return a;\n}",
+                "error[E0202]: incompatible types returning `int` from a function with result type `void`",
+                (2, 12),
+            ),
+        ];
+
+        for (input, expected, (line, column)) in cases {
+            let lexer = Lexer::new(String::from(input));
+            let (program, _) = parse::parse(lexer, &ParseOptions::default()).unwrap();
+            let errors = super::analyze(&program).unwrap_err();
+            assert_eq!(errors[0].to_string(), expected);
+            let span = errors[0].primary_span().unwrap();
+            assert_eq!((span.line, span.column), (line, column));
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use ast::{is_integer, Expression, ExpressionKind as Parsed, Statement, Type};
+use diagnostic::Diagnostic;
+
+use crate::conversion::{assign, promote, value, Assignment};
+use crate::expression::{check, check_condition};
+use crate::{invalid_operands, Analyzer, ExpressionKind, TypedExpression};
+
+impl Analyzer {
+    /// every statement is checked, `None` if any of them has an error.
+    pub(crate) fn check_statements(
+        &mut self,
+        statements: &[Statement],
+    ) -> Option<Vec<Statement<TypedExpression>>> {
+        let mut checked = Some(Vec::with_capacity(statements.len()));
+        for statement in statements {
+            match (self.check_statement(statement), &mut checked) {
+                (Some(statement), Some(checked)) => checked.push(statement),
+                _ => checked = None,
+            }
+        }
+        checked
+    }
+
+    /// the statement checked, `None` once its errors are recorded. the statements in it are
+    /// checked even when its expressions have errors.
+    fn check_statement(&mut self, statement: &Statement) -> Option<Statement<TypedExpression>> {
+        let boxed = |analyzer: &mut Self, statement: &Statement| {
+            analyzer.check_statement(statement).map(Box::new)
+        };
+        let statement = match statement {
+            Statement::Expression(expr) => {
+                Statement::Expression(self.report(check(expr).map(value))?)
+            }
+            Statement::If {
+                condition,
+                consequence,
+                alternative,
+            } => {
+                let condition = self.report(check_condition(condition, "`if`"));
+                let consequence = boxed(self, consequence);
+                let alternative = alternative.as_ref().map(|a| boxed(self, a));
+                Statement::If {
+                    condition: condition?,
+                    consequence: consequence?,
+                    alternative: match alternative {
+                        Some(alternative) => Some(alternative?),
+                        None => None,
+                    },
+                }
+            }
+            Statement::While { condition, body } => {
+                let condition = self.report(check_condition(condition, "`while`"));
+                let body = boxed(self, body);
+                Statement::While {
+                    condition: condition?,
+                    body: body?,
+                }
+            }
+            Statement::For {
+                init,
+                condition,
+                post,
+                body,
+            } => {
+                let init = init.as_ref().map(|init| boxed(self, init));
+                let condition = condition
+                    .as_ref()
+                    .map(|condition| self.report(check_condition(condition, "`for`")));
+                let post = post.as_ref().map(|post| boxed(self, post));
+                let body = boxed(self, body);
+                Statement::For {
+                    init: match init {
+                        Some(init) => Some(init?),
+                        None => None,
+                    },
+                    condition: match condition {
+                        Some(condition) => Some(condition?),
+                        None => None,
+                    },
+                    post: match post {
+                        Some(post) => Some(post?),
+                        None => None,
+                    },
+                    body: body?,
+                }
+            }
+            Statement::DoWhile { body, condition } => {
+                let body = boxed(self, body);
+                let condition = self.report(check_condition(condition, "`do`"));
+                Statement::DoWhile {
+                    body: body?,
+                    condition: condition?,
+                }
+            }
+            Statement::Switch {
+                condition,
+                body,
+                cases,
+                default,
+            } => {
+                let condition = self.report(check_switch(condition));
+                let body = boxed(self, body);
+                Statement::Switch {
+                    condition: condition?,
+                    body: body?,
+                    cases: cases.clone(),
+                    default: *default,
+                }
+            }
+            Statement::Case { value, body } => Statement::Case {
+                value: *value,
+                body: boxed(self, body)?,
+            },
+            Statement::Break => Statement::Break,
+            Statement::Continue => Statement::Continue,
+            Statement::Label { name, body } => Statement::Label {
+                name: name.clone(),
+                body: boxed(self, body)?,
+            },
+            Statement::Goto(name) => Statement::Goto(name.clone()),
+            Statement::Block(statements) => Statement::Block(self.check_statements(statements)?),
+            Statement::Return(None) => Statement::Return(None),
+            // nothing converts to `void`, so a `void` function returns no value at all.
+            Statement::Return(Some(expr)) => {
+                let expr = check(expr)
+                    .map(value)
+                    .and_then(|expr| match &self.return_type {
+                        Some(type_) => assign(expr, type_, Assignment::Return),
+                        None => Ok(expr),
+                    });
+                Statement::Return(Some(self.report(expr)?))
+            }
+            Statement::FunctionDefinition {
+                name,
+                return_type,
+                arguments,
+                body,
+                stack_size,
+                static_,
+            } => {
+                self.return_type = Some(return_type.clone());
+                let body = self.check_statements(body);
+                self.return_type = None;
+                let arguments = arguments.iter().map(check).collect::<Result<_, _>>();
+                Statement::FunctionDefinition {
+                    name: name.clone(),
+                    return_type: return_type.clone(),
+                    arguments: self.report(arguments)?,
+                    body: body?,
+                    stack_size: *stack_size,
+                    static_: *static_,
+                }
+            }
+            Statement::InitDeclaration {
+                name,
+                offset,
+                type_,
+                init,
+            } => Statement::InitDeclaration {
+                name: name.clone(),
+                offset: *offset,
+                type_: type_.clone(),
+                init: match init {
+                    Some(init) => Some(self.report(check_initializer(init, type_))?),
+                    None => None,
+                },
+            },
+            Statement::Declaration { name, type_ } => Statement::Declaration {
+                name: name.clone(),
+                type_: type_.clone(),
+            },
+            Statement::GlobalDeclaration {
+                name,
+                type_,
+                init,
+                static_,
+            } => Statement::GlobalDeclaration {
+                name: name.clone(),
+                type_: type_.clone(),
+                init: match init {
+                    Some(init) => Some(self.report(check_initializer(init, type_))?),
+                    None => None,
+                },
+                static_: *static_,
+            },
+        };
+        Some(statement)
+    }
+
+    /// the value of `result`, or `None` once its error is recorded.
+    fn report<T>(&mut self, result: Result<T, Diagnostic>) -> Option<T> {
+        result.map_err(|error| self.errors.push(error)).ok()
+    }
+}
+
+/// the controlling expression of a `switch`, promoted.
+fn check_switch(condition: &Expression) -> Result<TypedExpression, Diagnostic> {
+    let condition = value(check(condition)?);
+    if !is_integer(&condition.type_) {
+        return Err(invalid_operands(
+            format!(
+                "`switch` requires an integer value, `{}` has type `{}`",
+                condition, condition.type_
+            ),
+            condition.span,
+            "expected an integer",
+        ));
+    }
+    Ok(promote(condition))
+}
+
+/// `init` converted to the `type_` of the variable it initializes. an initializer list has the
+/// type of the aggregate, its elements those of the elements or members they initialize.
+fn check_initializer(init: &Expression, type_: &Type) -> Result<TypedExpression, Diagnostic> {
+    let elements = match &init.kind {
+        Parsed::Array { elements } => elements,
+        // a string literal initializes a `char` array as it is.
+        Parsed::String(_) if matches!(type_.unqualified(), Type::Array { .. }) => {
+            return check(init)
+        }
+        _ => return assign(value(check(init)?), type_, Assignment::Initialize),
+    };
+    // the types of the elements, those in excess are left as they are.
+    let types = match type_.unqualified() {
+        Type::Array { type_, .. } => vec![type_.as_ref(); elements.len()],
+        Type::Struct(struct_) => {
+            let members = struct_.members.as_deref().unwrap_or_default();
+            let count = if struct_.union { 1 } else { members.len() };
+            members.iter().take(count).map(|m| &m.type_).collect()
+        }
+        _ => vec![],
+    };
+    let elements = elements
+        .iter()
+        .enumerate()
+        .map(|(i, element)| match types.get(i) {
+            Some(type_) => check_initializer(element, type_),
+            None => check(element).map(value),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(TypedExpression::new(
+        ExpressionKind::Array { elements },
+        type_,
+        init.span,
+    ))
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+/// index of a file registered in a `SourceMap`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Hash)]
+pub struct FileId(pub usize);
+
+/// location of a piece of source text.
+///
+/// `start` and `end` are byte offsets into the lexed input, `line` and `column` (both 1-based)
+/// point at the first byte. the column counts bytes too, not characters.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Span {
+    pub file_id: FileId,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(file_id: FileId, start: usize, end: usize, line: usize, column: usize) -> Self {
+        Self {
+            file_id,
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// span covering both `self` and `other`.
+    pub fn to(&self, other: Span) -> Span {
+        let (first, last) = if other.start < self.start {
+            (other, *self)
+        } else {
+            (*self, other)
+        };
+        Span {
+            file_id: first.file_id,
+            start: first.start,
+            end: first.end.max(last.end),
+            line: first.line,
+            column: first.column,
+        }
+    }
+}
+
+pub struct SourceFile {
+    pub name: String,
+    pub src: String,
+}
+
+impl SourceFile {
+    /// text of the given 1-based line without its line terminator.
+    pub fn line(&self, line: usize) -> Option<&str> {
+        self.src
+            .split('\n')
+            .nth(line.checked_sub(1)?)
+            .map(|l| l.strip_suffix('\r').unwrap_or(l))
+    }
+}
+
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_file(&mut self, name: String, src: String) -> FileId {
+        self.files.push(SourceFile { name, src });
+        FileId(self.files.len() - 1)
+    }
+
+    pub fn get(&self, id: FileId) -> Option<&SourceFile> {
+        self.files.get(id.0)
+    }
+
+    pub fn name(&self, id: FileId) -> &str {
+        self.get(id).map(|f| f.name.as_str()).unwrap_or("<unknown>")
+    }
+
+    /// ids of the files by name, the first one registered under a name wins.
+    pub fn file_ids(&self) -> HashMap<String, FileId> {
+        let mut ids = HashMap::new();
+        for (i, file) in self.files.iter().enumerate() {
+            ids.entry(file.name.clone()).or_insert(FileId(i));
+        }
+        ids
+    }
+
+    /// `file:line:column` of the span, with the column in characters.
+    pub fn location(&self, span: Span) -> String {
+        let text = self
+            .get(span.file_id)
+            .and_then(|f| f.line(span.line))
+            .unwrap_or("");
+        let bytes = span.column.saturating_sub(1);
+        // past the end of the line, each missing byte is a column.
+        let column = text.char_indices().take_while(|(i, _)| *i < bytes).count()
+            + bytes.saturating_sub(text.len())
+            + 1;
+        format!("{}:{}:{}", self.name(span.file_id), span.line, column)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_span_to() {
+        let a = Span::new(FileId(0), 4, 6, 1, 5);
+        let b = Span::new(FileId(0), 10, 12, 2, 3);
+        assert_eq!(a.to(b), Span::new(FileId(0), 4, 12, 1, 5));
+        assert_eq!(b.to(a), Span::new(FileId(0), 4, 12, 1, 5));
+        assert_eq!(a.to(b).len(), 8);
+    }
+
+    #[test]
+    fn test_source_map() {
+        let mut map = SourceMap::new();
+        let id = map.add_file(String::from("main.c"), String::from("int a;\r\nint b;\n"));
+        assert_eq!(id, FileId(0));
+        assert_eq!(map.get(id).unwrap().line(1), Some("int a;"));
+        assert_eq!(map.get(id).unwrap().line(2), Some("int b;"));
+        assert_eq!(map.get(id).unwrap().line(0), None);
+        assert_eq!(
+            map.location(Span::new(id, 11, 12, 2, 5)),
+            String::from("main.c:2:5")
+        );
+        let utf8 = map.add_file(String::from("utf8.c"), String::from("\"ü\" x"));
+        assert_eq!(
+            map.location(Span::new(utf8, 5, 6, 1, 6)),
+            String::from("utf8.c:1:5")
+        );
+        map.add_file(String::from("main.c"), String::new());
+        assert_eq!(map.file_ids().get("main.c"), Some(&id));
+    }
+}